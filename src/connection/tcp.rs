@@ -10,7 +10,6 @@ use {
     std::net::{ TcpStream, TcpListener },
     std::collections::HashMap,
     std::time::SystemTime,
-    std::path::Path,
     std::sync::{Arc, Mutex},
     std::fs,
     std::str
@@ -21,7 +20,8 @@ use uuid::Uuid;
 use tokio;
 use crate::inter;
 use serde_json::json; // json macro to create JSON object
-use management::main::Outcomes::*;             
+use management::main::Outcomes::*;
+use crate::management::{sql_storage, sql_wal, sql_transactions::{self, Transaction, IsolationLevel}};             
 use rsa::{self, RsaPrivateKey, RsaPublicKey, pkcs1::{EncodeRsaPrivateKey, EncodeRsaPublicKey, DecodeRsaPrivateKey, DecodeRsaPublicKey}, PublicKey, PaddingScheme};
 use rand;
 use aes_gcm::{
@@ -219,7 +219,7 @@ impl CommandTypes {
                             if val_option.is_some() {
                                 let val_option = val_option.unwrap();
 
-                                if val_option.name == "connect_auto" && val_option.value.len() > 0 && Path::new(&format!("../source/dbs/{}", val_option.value)).exists() {
+                                if val_option.name == "connect_auto" && val_option.value.len() > 0 && sql_wal::data_path().join(val_option.value).exists() {
                                     let db_name = val_option.value.to_string();
                                     Some(db_name)
                                 }
@@ -440,19 +440,19 @@ impl CommandTypes {
                                     let chckdb = user_conn_t_db();
                                     if chckdb.0 {
                                         // Show all database tables
-                                        let path_str = format!("../source/dbs/{db}", db = chckdb.1);
-                                        let path = Path::new(&path_str);
+                                        let path_buf = sql_wal::data_path().join(&chckdb.1);
+                                        let path = path_buf.as_path();
                                         
                                         if path.exists() {
-                                            let mut table_names = vec![] as Vec<String>; // only correct tables names without .json extension
+                                            let mut table_names = vec![] as Vec<String>; // only correct tables names without table file extension
                                             for entry in fs::read_dir(path).unwrap() {
                                                 let entry = entry.unwrap().path();
 
                                                 if entry.is_file() {
                                                     let et_n_s = entry.file_name().unwrap().to_str().unwrap().split(".").collect::<Vec<_>>();
 
-                                                    if et_n_s.len() > 0 && *et_n_s.last().unwrap() == "json" {
-                                                        table_names.push(et_n_s[..(et_n_s.len() - 1)].join(".")) // add table name without .json extension
+                                                    if et_n_s.len() > 0 && *et_n_s.last().unwrap() == sql_storage::TABLE_EXTENSION {
+                                                        table_names.push(et_n_s[..(et_n_s.len() - 1)].join(".")) // add table name without table file extension
                                                     }
                                                 }
                                             };
@@ -479,11 +479,11 @@ impl CommandTypes {
                                     let chckdb = user_conn_t_db();
                                     if chckdb.0 {
                                         // Show table (with columns including their names, datatypes and constraint and also table all records)
-                                        let path_str = format!("../source/dbs/{db}/{tb}.json", db = chckdb.1, tb = what_unit.value);
-                                        let path = Path::new(&path_str);
+                                        let path = sql_storage::table_path(&sql_wal::data_path().join(&chckdb.1), what_unit.value);
                                         
                                         if path.exists() {
-                                            let table = fs::read_to_string(path).unwrap();
+                                            // Table is sent in same JSON form as it was stored before paged table files
+                                            let table = sql_storage::read_table(&path).ok().and_then(|table| serde_json::to_string(&table).ok()).unwrap_or_default();
 
                                             if table.len() > 0 {
                                                 Ok(
@@ -508,8 +508,8 @@ impl CommandTypes {
                                     }
                                 },
                                 "databases" => {
-                                    let path_str = format!("../source/dbs");
-                                    let path = Path::new(&path_str);
+                                    let path_buf = sql_wal::data_path();
+                                    let path = path_buf.as_path();
     
                                     if path.exists() {
                                         let mut databases_names: Vec<String> = vec![];
@@ -669,7 +669,7 @@ impl CommandTypes {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CommandTypeKeyDiff<'s> { 
     name: &'s str,
     value: &'s str
@@ -679,7 +679,11 @@ pub struct CommandTypeKeyDiff<'s> {
 pub struct SessionData {
     timestamp: u128,
     connected_to_database: Option<String>,
-    encryption: Option<CommmunicationEncryption>
+    encryption: Option<CommmunicationEncryption>,
+    #[serde(default)]
+    transaction: Option<Transaction>, // transaction in progress in session
+    #[serde(default)]
+    isolation_level: Option<IsolationLevel> // isolation level of transactions of session (set by "SET SESSION CHARACTERISTICS AS TRANSACTION")
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
@@ -1087,6 +1091,10 @@ pub async fn handle_tcp() {
                     let timestamp_new = get_timestamp();
 
                     if timestamp_new - s_d_ct > inter::MAXIMUM_SESSION_LIVE_TIME_MILS {
+                        // Changes of transaction which hasn't been committed by expired session are discarded
+                        if let Some(transaction) = &s_d.transaction {
+                            sql_transactions::abort(transaction).ok();
+                        };
                         lc.remove(entry.0);
                     }
                 }
@@ -1131,6 +1139,8 @@ pub async fn handle_tcp() {
                                     let encrypted_sdat = SessionData {
                                         timestamp: get_timestamp(),
                                         connected_to_database: None,
+                                        transaction: None,
+                                        isolation_level: None,
                                         encryption: Some(
                                             CommmunicationEncryption { aes_gcm_key: aes_key.to_owned(), nonce: aes_nonce_string.to_owned() }
                                         )
@@ -1176,7 +1186,9 @@ pub async fn handle_tcp() {
                                             let session_data = SessionData {
                                                 timestamp: get_timestamp(),
                                                 connected_to_database: connected_to_db,
-                                                encryption: None
+                                                encryption: None,
+                                                transaction: None,
+                                                isolation_level: None
                                             };
                                             update_session_and_res(&sid, &session_data)
                                         }
@@ -1235,8 +1247,8 @@ pub async fn handle_tcp() {
                                     ResponseTypes::Success(false).handle_response(Some(CommandTypes::Show), Some(&stream), Some(&mut *sessions), None, Some(result))
                                 },
                                 CommandTypes::DatabaseConnectRes(database_name, ses_id) => {
-                                    let ps = format!("../source/dbs/{}", database_name);
-                                    let path_database = Path::new(&ps);
+                                    let ps = sql_wal::data_path().join(&database_name);
+                                    let path_database = ps.as_path();
                                     let ses_id = {
                                         if let Some(ses_id) = ses_id {
                                             // For not encrypted connection
@@ -12,6 +12,15 @@ use clap::{ Command, Arg, ArgAction };
 
 mod management {
     pub mod sql_json;
+    pub mod sql_select;
+    pub mod sql_expr;
+    pub mod sql_datafusion;
+    pub mod sql_references;
+    pub mod sql_sequences;
+    pub mod sql_storage;
+    pub mod sql_wal;
+    pub mod sql_index;
+    pub mod sql_transactions;
 }
 
 #[tokio::main]
@@ -19,6 +28,15 @@ async fn main() {
     // Create required folders and files when don't exists
     create_stuff::create_stuff().expect("Couldn't create files and directories required to duly Database working!");
 
+    // Changes interrupted by crash of previous run are completed (when they were committed) or rolled back
+    management::sql_wal::recover().expect("Couldn't replay write-ahead log!");
+
+    // Tables saved in JSON files (also before cell values were typed) are rewritten to paged table files
+    management::sql_json::migrate_tables(&management::sql_wal::data_path()).expect("Couldn't migrate database tables to paged table files!");
+
+    // Versions of rows left by transactions of previous run are resolved (changes of transactions which weren't committed are removed)
+    management::sql_transactions::recover().expect("Couldn't resolve versions of rows left by transactions!");
+
     // CLI
     let add_user = Command::new("database TUI interface")
         .about("Create new database user")
@@ -86,11 +104,8 @@ async fn main() {
 // WARNING: To start robust testing you must turn on tcp server first
 #[cfg(test)]
 pub mod tests {
-    #[path = "../login-system.rs"]
-    mod login_module;
-
     use std::{ net::TcpStream, io::{Write, Read, BufReader, BufRead}, borrow::Borrow, fmt::format };
-    use login_module::authenticate_user;
+    use super::login_system::authenticate_user;
     use std::str;
     use format as f;
     use crate::connection::tcp::CommmunicationEncryption;
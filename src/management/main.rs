@@ -1,12 +1,13 @@
-use sqlparser::{ dialect::AnsiDialect, parser::Parser as SqlParser, ast::{Statement, ObjectName, SetExpr, Expr, DataType, ColumnOptionDef, ObjectType, SelectItem, TableFactor, AlterTableOperation} };
+use sqlparser::{ dialect::GenericDialect, parser::{Parser as SqlParser, ParserError}, tokenizer::{Token, Tokenizer}, keywords::Keyword, ast::{Statement, TransactionMode, ObjectName, SetExpr, Expr, DataType, ColumnOption, ColumnOptionDef, TableConstraint, ObjectType, TableFactor, AlterTableOperation} };
 #[allow(unused)]
 use datafusion::prelude::*;
 use format as f;
 use Outcomes::*;
-use std::{ fs, path::Path, collections::HashMap, fmt::format, borrow::Borrow };
+use std::{ collections::HashMap, fmt::format, borrow::Borrow };
 
 use crate::connection::tcp::{ CommandTypeKeyDiff, SessionData };
-use crate::management::sql_json::{ self, process_sql, ProcessSQLRowField as Field, SupportedSQLDataTypes, SupportedSQLColumnConstraints, ProcessSQLSupportedQueries, InsertOperations, ConvertSQLParserTypesToSupported, ConvertSQLParserOptionsToSupportedConstraints };
+use crate::management::{sql_select::{self, SelectQuery}, sql_expr::{self, ExprContext}, sql_datafusion, sql_references, sql_sequences, sql_storage, sql_wal, sql_index::{self, IndexColumn, IndexDefinition}, sql_transactions::{self, Transaction, TransactionStatement, IsolationLevel}};
+use crate::management::sql_json::{ self, process_sql, ProcessSQLRowField as Field, SupportedSQLDataTypes, SupportedSQLColumnConstraints, SupportedSQLTableConstraints, ProcessSQLSupportedQueries, InsertOperations, ConvertSQLParserTypesToSupported, ConvertSQLParserOptionsToSupportedConstraints };
 use self::additions::unavailable;
 
 #[path ="../additions"]
@@ -31,660 +32,949 @@ fn get_database_user_connected_to(sessions: &mut HashMap<String, String>, sessio
     return user_con_db;
 }
 
+/// Get path (struct PathBuf) to database directory
+fn get_db_path(db_name: &String) -> std::path::PathBuf {
+    sql_wal::data_path().join(db_name)
+}
+
 /// Get path (struct PathBuf) to table located into database
-fn get_dbtable_path(db_name: &String, table_name: &String) -> std::path::PathBuf {
-    let table_path_str = f!("../source/dbs/{0}/{1}.json", db_name, table_name);
-    let table_path = Path::new(&table_path_str).to_owned();
-    return table_path;
+fn get_dbtable_path(db_name: &String, table_name: &str) -> std::path::PathBuf {
+    sql_storage::table_path(&get_db_path(db_name), table_name)
+}
+
+/// Maintain foreign keys of database after rows of table have been changed (and saved). Rows changed by actions of foreign keys are saved by them
+fn maintain_foreign_keys(db_name: &String, table_name: &str, changes: Vec<sql_references::RowChange>) -> Result<(), String> {
+    sql_references::maintain_foreign_keys(&get_db_path(db_name), table_name, changes)
+}
+
+/// Statement of sql query: statement performed on database or statement which controls transaction of session
+#[derive(Debug)]
+enum QueryStatement {
+    Sql(Box<Statement>),
+    Transaction(TransactionStatement),
 }
 
-/// Process sended sql query
+/// Parse statements from sql query. Identity columns ("GENERATED ALWAYS AS IDENTITY" and "GENERATED BY DEFAULT AS IDENTITY") aren't supported by parser so they are parsed as "AUTO_INCREMENT" columns.
+/// Also "ROLLBACK TO [SAVEPOINT] name" and "RELEASE [SAVEPOINT] name" aren't supported by parser so they are parsed here
+fn parse_query(sql_query: &str) -> Result<Vec<QueryStatement>, ParserError> {
+    let dialect = GenericDialect {};
+    let tokens = Tokenizer::new(&dialect, sql_query).tokenize()?;
+
+    // Replace identity clause by "AUTO_INCREMENT" keyword
+    let word = |token: &Token| match token {
+        Token::Word(word) if word.quote_style.is_none() => word.value.to_uppercase(),
+        _ => String::new()
+    };
+    let mut ready_tokens = vec![] as Vec<Token>;
+    let mut position = 0;
+    while position < tokens.len() {
+        let next_words = tokens[position..]
+            .iter()
+            .enumerate()
+            .filter(|(_, token)| !matches!(token, Token::Whitespace(_)))
+            .take(5)
+            .map(|(offset, token)| (position + offset, word(token)))
+            .collect::<Vec<_>>();
+        let words = next_words.iter().map(|(_, word)| word.as_str()).collect::<Vec<_>>();
+
+        let identity_length = match words.as_slice() {
+            ["GENERATED", "ALWAYS", "AS", "IDENTITY", ..] => Some(4),
+            ["GENERATED", "BY", "DEFAULT", "AS", "IDENTITY", ..] => Some(5),
+            _ => None
+        };
+        match identity_length {
+            Some(length) => {
+                ready_tokens.push(Token::make_keyword("AUTO_INCREMENT"));
+                position = next_words[length - 1].0 + 1;
+            },
+            None => {
+                ready_tokens.push(tokens[position].clone());
+                position += 1;
+            }
+        }
+    };
+
+    // Statements are separated by semicolons
+    let mut parser = SqlParser::new(ready_tokens, &dialect);
+    let mut statements = vec![];
+    loop {
+        while parser.consume_token(&Token::SemiColon) {}
+        if parser.peek_token() == Token::EOF {
+            break;
+        };
+
+        let rollback_to = word(&parser.peek_token()) == "ROLLBACK" && (1..=2).any(|position| word(&parser.peek_nth_token(position)) == "TO");
+        let statement = if rollback_to {
+            parser.expect_keyword(Keyword::ROLLBACK)?;
+            let _ = parser.parse_one_of_keywords(&[Keyword::TRANSACTION, Keyword::WORK]);
+            parser.expect_keyword(Keyword::TO)?;
+            let _ = parser.parse_keyword(Keyword::SAVEPOINT);
+            QueryStatement::Transaction(TransactionStatement::RollbackToSavepoint(parser.parse_identifier()?.value))
+        }
+        else if parser.parse_keyword(Keyword::RELEASE) {
+            let _ = parser.parse_keyword(Keyword::SAVEPOINT);
+            QueryStatement::Transaction(TransactionStatement::ReleaseSavepoint(parser.parse_identifier()?.value))
+        }
+        else {
+            let statement = parser.parse_statement()?;
+            match transaction_statement(&statement) {
+                Some(transaction_statement) => QueryStatement::Transaction(transaction_statement),
+                None => QueryStatement::Sql(Box::new(statement))
+            }
+        };
+
+        statements.push(statement);
+        if parser.peek_token() != Token::SemiColon && parser.peek_token() != Token::EOF {
+            return parser.expected("end of statement", parser.peek_token());
+        };
+    };
+
+    Ok(statements)
+}
+
+/// Isolation level set by transaction modes (None when modes contain access mode, e.g: "READ ONLY", which isn't supported)
+fn modes_isolation_level(modes: &[TransactionMode]) -> Option<Option<IsolationLevel>> {
+    modes.iter().try_fold(None, |_, mode| match mode {
+        TransactionMode::IsolationLevel(level) => Some(Some(IsolationLevel::convert(level))),
+        TransactionMode::AccessMode(_) => None
+    })
+}
+
+/// Statement which controls transaction of session
+fn transaction_statement(statement: &Statement) -> Option<TransactionStatement> {
+    match statement {
+        Statement::StartTransaction { modes } => modes_isolation_level(modes).map(TransactionStatement::Begin),
+        Statement::SetTransaction { modes, snapshot: None, session } => match modes_isolation_level(modes) {
+            Some(Some(level)) => Some(TransactionStatement::SetIsolationLevel { level, session: *session }),
+            _ => None
+        },
+        Statement::Commit { chain } => Some(TransactionStatement::Commit { chain: *chain }),
+        Statement::Rollback { chain } => Some(TransactionStatement::Rollback { chain: *chain }),
+        Statement::Savepoint { name } => Some(TransactionStatement::Savepoint(name.value.clone())),
+        _ => None
+    }
+}
+
+/// Process sended sql query. All statements from query are executed in order and result for each of them is returned.
+/// Execution stops on first statement which couldn't be performed
 pub fn process_query(query: &str, auto_connect: Option<crate::connection::tcp::CommandTypeKeyDiff>, session_id: String, sessions: &mut HashMap<String, String>) -> Outcomes {
     let sql_query = query;
-    let selected_sql_dialect = AnsiDialect {};
-    let parse_operation = SqlParser::parse_sql(&selected_sql_dialect, sql_query);
+    let parse_operation = parse_query(sql_query);
 
     match parse_operation {
         Ok(parse_op_result) => {
             println!("{:?}", parse_op_result);
+            let statements_count = parse_op_result.len();
+            if statements_count == 0 {
+                return Error("Query doesn't contain any statement".to_string());
+            };
+
             // Process SQL Query and do amazing things
-            let mut it = 0;
-            loop {
-                let lexical_sql = parse_op_result[it].clone();
-                // println!("\nQuery:\n\n{:#?}", lexical_sql);
-                it += 1;
-
-                // Do specific action
-                match lexical_sql {
-                    // Create SQL database
-                    Statement::CreateDatabase { db_name: ObjectName(data_base), if_not_exists: _, location: _, managed_location: _ } => {
-                        let db_name_val = &data_base[0].value;
-                        
-                        if db_name_val.len() > 0 && !unavailable::os_file_system_check_unavailable_characters_into(&db_name_val) && !unavailable::FILENAMES_WINDOWS.contains(&db_name_val.as_str()) {
-                            let session_data = sessions.get(&session_id).unwrap(); // here session must exists FIXME: In feature (after addition system to remove session after crossed "session persists time (TTL otherwise)" time that session can stop exists here)
-                            let mut session_data = serde_json::from_str::<SessionData>(session_data).unwrap();
-                            
-                            // create database + response
-                            let loc = f!("../source/dbs/{db_name}", db_name = db_name_val);
-                            let db_path = Path::new(loc.as_str());
-    
-                                // database can be created only when it actualy doesn't exists
-                            if !db_path.exists() {
-                                if let Ok(_) = fs::create_dir(db_path) {
-                                        // Connect user with database when he would like get that by place appropriate command
-                                    if let Some(CommandTypeKeyDiff { name: _, value }) = auto_connect {
-                                        if value == "true" {
-                                            // Update session on session storage
-                                            session_data.connected_to_database = Some(db_name_val.to_owned());
-                                            let session_data = serde_json::to_string(&session_data).unwrap();
-                                            sessions.insert(session_id, session_data);
-                                        };
-                                    };
-    
-                                    // Send result
-                                    break Success(None);
-                                }; 
-    
-                                break Error(f!("Database couldn't been created!"));
-                            };
-                            
-                            break Error(f!("Provided database \"{}\" couldn't be created because this database already exists", db_name_val));
-                        }
-                        else {
-                            break Error(f!("Database name is not correct!"));
+            let mut results = vec![] as Vec<Option<String>>;
+            for (statement_id, query_statement) in parse_op_result.into_iter().enumerate() {
+                let mut session_data = serde_json::from_str::<SessionData>(sessions.get(&session_id).unwrap()).unwrap();
+                let outcome = match query_statement {
+                    QueryStatement::Transaction(transaction_statement) => {
+                        let performed = sql_transactions::perform(transaction_statement, &mut session_data.transaction, &mut session_data.isolation_level);
+                        sessions.insert(session_id.clone(), serde_json::to_string(&session_data).unwrap());
+                        match performed {
+                            Ok(_) => Success(None),
+                            Err(reason) => Error(reason)
                         }
                     },
-                    Statement::CreateTable { 
-                        or_replace: _, 
-                        temporary: _, 
-                        external: _, 
-                        global: _, 
-                        if_not_exists: _, 
-                        name, 
-                        columns, 
-                        constraints: _, 
-                        hive_distribution: _, 
-                        hive_formats: _, 
-                        table_properties: _, 
-                        with_options: _, 
-                        file_format: _, 
-                        location: _, 
-                        query: _on1, 
-                        without_rowid: _, 
-                        like: _, 
-                        clone: _, 
-                        engine: _, 
-                        default_charset: _, 
-                        collation: _, 
-                        on_commit: _, 
-                        on_cluster: _ 
-                    } => {
-                        let session_data = get_database_user_connected_to(sessions, &session_id);
-
-                        if session_data.is_some() {
-                            let database_name = session_data.unwrap();
-                            if Path::new(&f!("../source/dbs/{db}", db = database_name)).exists() {
-                                // Obtain table name and put it into Option<String>
-                                let table_name = if name.0.len() > 0 {
-                                    Some(&name.0[0].value)
-                                }
-                                else {
-                                    None
-                                };
+                    QueryStatement::Sql(lexical_sql) => {
+                        let mut transaction = session_data.transaction;
+                        let outcome = process_logged_statement(*lexical_sql, auto_connect.clone(), session_id.clone(), sessions, &mut transaction);
 
-                                // Table name must be attached in query!
-                                if let Some(table_name) = table_name {
-                                    let f_p = get_dbtable_path(&database_name, table_name);
-
-                                    if !f_p.exists() {                                    
-                                        // obtain column properties in order to allow create a table
-                                        let mut columns_cv = vec![] as Vec<(String, SupportedSQLDataTypes, Option<Vec<SupportedSQLColumnConstraints>>)>;
-                                        for column in &columns {
-                                            // obtain required properties from column
-                                            let col_name = column.name.clone().value;
-                                            let col_data_type = if let Some(r#type) = DataType::convert(&column.data_type) {
-                                                r#type
-                                            }
-                                            else {
-                                                // when type from query isn't supported then break whole loop from ACID model reason
-                                                break;
-                                            };
-                                            let col_constraints = {
-                                                let mut constraints = vec![] as Vec<SupportedSQLColumnConstraints>;
-                                                if column.options.len() > 0 {
-                                                    for option in column.options.clone() {
-                                                        if let Some(constraint) = ColumnOptionDef::convert(option) {
-                                                            constraints.push(constraint)
-                                                        }
-                                                        else {
-                                                            // When option isn't supported
-                                                            break;
-                                                        }
-                                                    }
-                                                }
-                                                constraints
-                                            };
+                        // Statement could change session (e.g: connect it to database), so only state of transaction is replaced
+                        if transaction.is_some() {
+                            let mut session_data = serde_json::from_str::<SessionData>(sessions.get(&session_id).unwrap()).unwrap();
+                            session_data.transaction = transaction;
+                            sessions.insert(session_id.clone(), serde_json::to_string(&session_data).unwrap());
+                        };
+                        outcome
+                    }
+                };
+                // Versions of finished transactions are resolved after many of them have finished (failed checkpoint is reported and tried again after next finished transactions)
+                if let Err(reason) = sql_transactions::checkpoint() {
+                    println!("{}", reason);
+                };
+                match outcome {
+                    Success(description) => results.push(description),
+                    // For single statement query error is returned without change
+                    Error(reason) if statements_count == 1 => return Error(reason),
+                    Error(reason) => return Error(f!("Statement {} (of {}) couldn't be performed: {}", statement_id + 1, statements_count, reason))
+                }
+            }
 
-                                            // compose column and attach it to vector
-                                            let ready_column = (col_name, col_data_type, {
-                                                if col_constraints.len() > 0 {
-                                                    Some(col_constraints)
-                                                }
-                                                else {
-                                                    None
-                                                }
-                                            });
-                                            columns_cv.push(ready_column);
-                                        };
-                                        if columns_cv.len() != columns.len() { // when all columns wasn't correctly processed
-                                            break Error(f!("In query you attach unsupported type or this has been caused by other query inconsistent factor"));
-                                        };
+            if statements_count == 1 {
+                return Success(results.remove(0));
+            };
+
+            // Results of all statements as JSON list. Result which is in JSON format (e.g: SELECT records) is placed as it is, other as string
+            let results = results
+                .into_iter()
+                .map(|description| match description {
+                    Some(description) => serde_json::from_str::<serde_json::Value>(&description).unwrap_or(serde_json::Value::String(description)),
+                    None => serde_json::Value::Null
+                })
+                .collect::<Vec<_>>();
+            match serde_json::to_string(&results) {
+                Ok(results) => Success(Some(results)),
+                Err(_) => Error("Couldn't convert results to redable form".to_string())
+            }
+        },
+        Err(_) => Error("SQL Syntax Error".to_string())
+    }
+}
 
-                                        // Create table in json format and write it to file located into database folder. Table file name is table name attached to query
-                                        match process_sql(ProcessSQLSupportedQueries::CreateTable(
-                                            table_name.into(), 
-                                            columns_cv
-                                        )) {
-                                            Ok(table) => {
-                                                let r_json = serde_json::to_string(&table); // for pretty format data use serde_json::to_string_pretty(&table), but it will use unnecessary characters (for pretty print u can use nested VS Code .json formater) 
+/// Process single statement from sql query in statement of write-ahead log and in transaction of session (or in own transaction when session hasn't got transaction in progress).
+/// Changes of statement are committed when statement has been performed (or they stay in transaction of session) and reverted when statement couldn't be performed
+fn process_logged_statement(lexical_sql: Statement, auto_connect: Option<crate::connection::tcp::CommandTypeKeyDiff>, session_id: String, sessions: &mut HashMap<String, String>, transaction: &mut Option<Transaction>) -> Outcomes {
+    // Changes of rows are versioned, so only changes of definitions (e.g: created or altered tables) are kept in write-ahead log until transaction ends
+    let changes_rows = matches!(lexical_sql, Statement::Query(_) | Statement::Insert { .. } | Statement::Update { .. } | Statement::Delete { .. });
+    let statement = match transaction.as_ref().map(Transaction::log_statement) {
+        Some(log_statement) if !changes_rows => match sql_wal::Statement::begin_inside(log_statement) {
+            Ok(statement) => statement,
+            Err(_) => return Error("Couldn't begin statement".to_string())
+        },
+        log_statement => sql_wal::Statement::begin(log_statement)
+    };
+    sql_transactions::begin_statement(transaction.as_mut());
 
-                                                if let Err(_) = r_json {
-                                                    break Error(f!("Couldn't create table"));
-                                                };
+    let outcome = process_statement(lexical_sql, auto_connect, session_id, sessions);
+    let (conflicted, row_conflicted) = (statement.conflicted(), sql_transactions::conflicted());
+    let outcome = match sql_transactions::end_statement(transaction.as_mut(), matches!(outcome, Success(_))) {
+        Ok(_) => outcome,
+        Err(reason) => Error(reason)
+    };
+    match outcome {
+        Success(description) => match statement.commit() {
+            Ok(_) => Success(description),
+            Err(_) => Error("Couldn't save changes performed by statement".to_string())
+        },
+        Error(reason) => match statement.rollback() {
+            Ok(_) if conflicted => Error("Table is used by transaction of another session".to_string()),
+            Ok(_) if row_conflicted => Error("Row has been changed by transaction of another session".to_string()),
+            Ok(_) => Error(reason),
+            Err(_) => Error("Couldn't revert changes performed by statement".to_string())
+        }
+    }
+}
 
-                                                if let Ok(_) = fs::write(f_p, r_json.unwrap()) {
-                                                    break Success(None);
-                                                }
-                                                else {
-                                                    break Error(f!("Couldn't create table"));
-                                                }
-                                            },
-                                            // is returned for exmaple when: to column is attached unsupported type by function compared "process_sql" function
-                                            Err(_) => break Error(f!("Couldn't create table"))
-                                        }
-                                    }
-                                    else {
-                                        break Error(f!("This table already exists so it can't be re-created"));
-                                    }
-                                }
+/// Process single statement from sql query
+fn process_statement(lexical_sql: Statement, auto_connect: Option<crate::connection::tcp::CommandTypeKeyDiff>, session_id: String, sessions: &mut HashMap<String, String>) -> Outcomes {
+    // Do specific action
+    match lexical_sql {
+        // Create SQL database
+        Statement::CreateDatabase { db_name: ObjectName(data_base), if_not_exists: _, location: _, managed_location: _ } => {
+            let db_name_val = &data_base[0].value;
+            
+            if db_name_val.len() > 0 && !unavailable::os_file_system_check_unavailable_characters_into(&db_name_val) && !unavailable::FILENAMES_WINDOWS.contains(&db_name_val.as_str()) {
+                let session_data = sessions.get(&session_id).unwrap(); // here session must exists FIXME: In feature (after addition system to remove session after crossed "session persists time (TTL otherwise)" time that session can stop exists here)
+                let mut session_data = serde_json::from_str::<SessionData>(session_data).unwrap();
+                
+                // create database + response
+                let db_path = get_db_path(db_name_val);
+
+                    // database can be created only when it actualy doesn't exists
+                if !db_path.exists() {
+                    if let Ok(_) = sql_wal::create_dir(&db_path) {
+                            // Connect user with database when he would like get that by place appropriate command
+                        if let Some(CommandTypeKeyDiff { name: _, value }) = auto_connect {
+                            if value == "true" {
+                                // Update session on session storage
+                                session_data.connected_to_database = Some(db_name_val.to_owned());
+                                let session_data = serde_json::to_string(&session_data).unwrap();
+                                sessions.insert(session_id, session_data);
                             };
+                        };
 
-                            break Error(f!("Database to which you're connected doesn't exists!"));
-                        }
+                        // Send result
+                        return Success(None);
+                    }; 
 
-                        break Error(f!("You're not connected to any database. In order to execute this command you must be connected!"));
-                    },
-                    Statement::Insert { 
-                        or: _, 
-                        into, 
-                        table_name, 
-                        columns, // TODO: Add later support for attachement for specific columns
-                        overwrite,
-                        source, 
-                        partitioned: _, 
-                        after_columns: _, 
-                        table: _, // indictaes whethe "table" keyword was attached to INSER OVERWRITE query
-                        on: _ 
-                    } => {
-                        let session_data = serde_json::from_str::<SessionData>(sessions.get(&session_id).unwrap()).unwrap();
-                        let user_con_db = session_data.connected_to_database.clone();
-                        
-                        if user_con_db.is_some() { // user must be firsly connected to database
-                            // Support for both operations types "INSERT INTO" and "INSERT OVERWRITE TABLE"
-                            let op_type: Option<InsertOperations> = {
-                                if into {
-                                    Some(InsertOperations::Into)
+                    return Error(f!("Database couldn't been created!"));
+                };
+                
+                Error(f!("Provided database \"{}\" couldn't be created because this database already exists", db_name_val))
+            }
+            else {
+                Error(f!("Database name is not correct!"))
+            }
+        },
+        Statement::CreateTable { 
+            or_replace: _, 
+            temporary: _, 
+            external: _, 
+            global: _, 
+            if_not_exists: _, 
+            name, 
+            columns, 
+            constraints, 
+            hive_distribution: _, 
+            hive_formats: _, 
+            table_properties: _, 
+            with_options: _, 
+            file_format: _, 
+            location: _, 
+            query: _on1, 
+            without_rowid: _, 
+            like: _, 
+            clone: _, 
+            engine: _, 
+            default_charset: _, 
+            collation: _, 
+            on_commit: _, 
+            on_cluster: _ 
+        } => {
+            let session_data = get_database_user_connected_to(sessions, &session_id);
+
+            if session_data.is_some() {
+                let database_name = session_data.unwrap();
+                if get_db_path(&database_name).exists() {
+                    // Obtain table name and put it into Option<String>
+                    let table_name = if name.0.len() > 0 {
+                        Some(&name.0[0].value)
+                    }
+                    else {
+                        None
+                    };
+
+                    // Table name must be attached in query!
+                    if let Some(table_name) = table_name {
+                        let f_p = get_dbtable_path(&database_name, table_name);
+
+                        if !f_p.exists() {                                    
+                            // obtain column properties in order to allow create a table
+                            let mut columns_cv = vec![] as Vec<(String, SupportedSQLDataTypes, Option<Vec<SupportedSQLColumnConstraints>>)>;
+                            let mut column_references = vec![] as Vec<TableConstraint>; // "REFERENCES" and "CHECK" attached to column are stored as table constraints "FOREIGN KEY" and "CHECK" for this column
+                            for column in &columns {
+                                // obtain required properties from column
+                                let col_name = column.name.clone().value;
+                                // "SERIAL" column is INT column with AUTO_INCREMENT
+                                let serial = matches!(&column.data_type, DataType::Custom(name) if ["SERIAL", "SMALLSERIAL", "BIGSERIAL"].contains(&name.to_string().to_uppercase().as_str()));
+                                let col_data_type = if serial {
+                                    SupportedSQLDataTypes::INT
                                 }
-                                else if overwrite {
-                                    Some(InsertOperations::Overwrite)
+                                else if let Some(r#type) = DataType::convert(&column.data_type) {
+                                    r#type
                                 }
                                 else {
-                                    None
-                                }
-                            };
-                            
-                                // ...rust required safeguards for support only 2 insert operations
-                            if op_type.is_some() {
-                                let op_type = op_type.unwrap();
-
-                                // Obtain table name
-                                let table_name = &table_name.0[0].value;
-
-                                // Obtain database name to which user is connected
-                                let user_con_db = session_data.connected_to_database.unwrap();
-
-                                // Db table path
-                                let dbt_path = get_dbtable_path(&user_con_db, table_name);
-
-                                // Create only when database and tab;e exists
-                                if dbt_path.exists() {
-                                    // Obtain for which coulmns operation must be performed only
-                                    let columns_from_query = {
-                                        // To return Some(_) columns len from lexer must be greater then 0 hence them must exists
-                                        if columns.len() > 0 {
-                                            let mut c_r = Vec::new() as Vec<String>;
-                                            for column in columns {
-                                                let column_name = column.value;
-                                                c_r.push(column_name);
-                                            };
-                                            Some(c_r)
-                                        }
-                                        else {
-                                            None
-                                        }
+                                    // when type from query isn't supported then break whole loop from ACID model reason
+                                    break;
+                                };
+                                let col_constraints = {
+                                    let mut constraints = vec![] as Vec<SupportedSQLColumnConstraints>;
+                                    if serial {
+                                        constraints.push(SupportedSQLColumnConstraints::AUTO_INCREMENT(0));
                                     };
-                                    
-                                    // Obtain values (to insert for columns) from insert query (whole) // Error: When value coudn't be converted or vector with converted results is shorter then this from query values then loop is break inside brackets "{}" and further (below) code won't be performing as next
-                                    let values_from_query = {
-                                        // Type which store values for Query must be Values()
-                                        if let SetExpr::Values(vals) = *source.body {
-                                            let vals = vals.0;
-                                            // Ready to insert: List with all rows and it's values to insert
-                                            let mut allrows_values_list: Vec<Vec<Field>> = vec![]; // 1st vector = store rows, 2nd vector = store values for columns for single row
-                                        
-                                            // Iterte over each row with values to insert for each column
-                                            for each_row in vals.clone() {
-                                                let mut onerow_values_list: Vec<Field> = vec![];
-
-                                                // Extract all values from query and assing it to appropriate type supported by this database or break whole extract operation when some type from query isn't supported by this database
-                                                // Iterate over values from one row and extract values (extract in this "scenario" obtain value and it type from query and assign it to datatype supported by this database). When datatype from query isn't supported then whole (insert) operation will be stopped and not performed
-                                                for val_ins in &each_row {
-                                                    if let Expr::Value(dat_type) = val_ins {
-                                                        use sqlparser::ast::Value::*; // get types for query (required to assign)
-                                                        use sql_json::SupportedSQLDataTypes as sup; // get supported types list
-                                                    
-                                                        // extract types and assign their values to supported datatypes / or stop loop over row values when some type isn't supported
-                                                        match dat_type {
-                                                            SingleQuotedString(str) | DoubleQuotedString(str) => onerow_values_list.push(Field(str.into(), sup::VARCHAR(None))), // string are interpreted as "TEXT" (up to 16_777_215 characters iin one column) type in this place but also can be VARCHAR (which support to 65_535 characters in one string)
-                                                            Null => onerow_values_list.push(Field("null".to_string(), sup::NULL)),
-                                                            Boolean(val) => onerow_values_list.push(Field(val.to_string(), sup::BOOLEAN)),
-                                                            Number(num, _) => onerow_values_list.push(Field(num.into(), sup::INT)),
-                                                            _ => break // for unsuported data types
-                                                        }
-                                                    }
-                                                    else {
-                                                        break;
-                                                    };
-                                                };
-                                            
-                                                // ACID rules must be fullfiled so: (...to perform query all types must be correctly extracted so (extracted_values_from_row_stored.len() == query_row_values.len()) otheriwise don't perform any slice of whole query to maintain data consistancy and break loop here)
-                                                if onerow_values_list.len() == each_row.len() {
-                                                    allrows_values_list.push(onerow_values_list);
-                                                }
-                                                else {
-                                                    break;
-                                                };
-                                            };
-                                        
-                                            // ACID principles must be fullfiled so ...rows_query.len() must be equal rows_with_converted_values.len() otherwise operation won't be performed
-                                            if allrows_values_list.len() == vals.len() {
-                                                allrows_values_list
+                                    if column.options.len() > 0 {
+                                        for option in column.options.clone() {
+                                            if let ColumnOption::ForeignKey { foreign_table, referred_columns, on_delete, on_update } = option.option {
+                                                column_references.push(TableConstraint::ForeignKey { name: option.name, columns: vec![column.name.clone()], foreign_table, referred_columns, on_delete, on_update });
                                             }
-                                            else {
-                                                break Error(f!(r#"Some type from your "INSERT" query isn't supported, from this plaintiff whole operation can't be perfomed"#));
+                                            else if let ColumnOption::Check(expr) = option.option {
+                                                column_references.push(TableConstraint::Check { name: option.name, expr: Box::new(expr) });
                                             }
-                                        }
-                                        else {
-                                            break Error(f!("Query couldn't be executed"));
-                                        }
-                                    };
-
-                                    // Create table with new inserted records and save it
-                                    // When operation must be performed for specific columns then columns correcteness and whether that operation can be performed is check inside process_sql function -> because there exists deserialized JSON table
-                                    match process_sql(ProcessSQLSupportedQueries::Insert(&dbt_path, columns_from_query, values_from_query, op_type)) {
-                                        Ok(ready_table) => {
-                                            // Put table into string
-                                            let table_ready_stri_op = serde_json::to_string(&ready_table);
-                                            if let Ok(table_ready_stri) = table_ready_stri_op {
-                                                // Save result into table file + return operation result
-                                                if let Ok(_) = fs::write(dbt_path, table_ready_stri) {
-                                                    break Success(Some(f!(r#"INSERT operation has been performed"#)));
-                                                }
-                                                else {
-                                                    break Error(f!("Coludn't save results of operation from some reason"));
-                                                }
+                                            else if let Some(constraint) = ColumnOptionDef::convert(option) {
+                                                constraints.push(constraint)
                                             }
                                             else {
-                                                break Error(f!("Couldn't convert operation results to JSON format"));
+                                                // When option isn't supported
+                                                break;
                                             }
-                                        },
-                                        Err(_) => {break Error(f!("Values couldn't been inserted to table"))}
+                                        }
                                     }
+                                    constraints
+                                };
+
+                                // compose column and attach it to vector
+                                let ready_column = (col_name, col_data_type, {
+                                    if col_constraints.len() > 0 {
+                                        Some(col_constraints)
+                                    }
+                                    else {
+                                        None
+                                    }
+                                });
+                                columns_cv.push(ready_column);
+                            };
+                            if columns_cv.len() != columns.len() { // when all columns wasn't correctly processed
+                                return Error(f!("In query you attach unsupported type or this has been caused by other query inconsistent factor"));
+                            };
+
+                            // obtain constraints defined for whole table (e.g: "PRIMARY KEY (a, b)")
+                            let mut table_constraints = vec![] as Vec<SupportedSQLTableConstraints>;
+                            let table_columns = columns_cv.iter().map(|(name, ..)| name.clone()).collect::<Vec<_>>();
+                            for constraint in column_references.iter().chain(&constraints) {
+                                match SupportedSQLTableConstraints::from_table_constraint(constraint, table_name, &table_columns) {
+                                    Some(constraint) => table_constraints.push(constraint),
+                                    None => return Error(f!("Table constraint \"{}\" isn't supported", constraint))
                                 }
-                                else {
-                                    break Error(f!("Database to which you're connected doesn't exists | Or table to which you try attach data doesn't exists in database to which you're connected"));
-                                }
-                            }
-                            else {
-                                break Error(f!("This \"INSERT\" operation isn't supported"));
                             };
+
+                            // Create table in json format and write it to file located into database folder. Table file name is table name attached to query
+                            match process_sql(ProcessSQLSupportedQueries::CreateTable(
+                                table_name.into(), 
+                                columns_cv,
+                                table_constraints
+                            )) {
+                                Ok(table) => {
+                                    if sql_storage::write_table(&f_p, &table).is_err() {
+                                        return Error(f!("Couldn't create table"));
+                                    };
+
+                                    // Foreign keys must reffer to keys of existing tables (or to key of created table). Statement is reverted when they don't
+                                    if let Err(reason) = maintain_foreign_keys(&database_name, table_name, vec![]) {
+                                        return Error(reason);
+                                    };
+                                    return Success(None);
+                                },
+                                // is returned for exmaple when: to column is attached unsupported type by function compared "process_sql" function or when keys are incorrect (e.g: key reffers to not existing column or table has got many primary keys)
+                                Err(_) => return Error(f!("Couldn't create table"))
+                            }
                         }
                         else {
-                            break Error(f!("\"INSERT\" query must include \"INTO\""));
+                            return Error(f!("This table already exists so it can't be re-created"));
                         }
-                    },
-                    Statement::Truncate { 
-                        table_name, 
-                        partitions: _ 
-                    } => {
-                        // Get whether user is connected to database and database name to which is
-                        let user_con_db = get_database_user_connected_to(sessions, &session_id);
-
-                        if user_con_db.is_some() {
-                            let user_con_db = user_con_db.unwrap();
-                            
-                            // Truncate table rows operation
-                            let table_name = &table_name.0[0].value;
-                            let table_path = get_dbtable_path(&user_con_db, table_name);
-
-                            // Perform operation only when table exists into specified database
-                            if table_path.exists() {
-                                // Begin truncate operation and its results
-                                match process_sql(ProcessSQLSupportedQueries::Truncate(&table_path)) {
-                                    Ok(tr_table) => {
-                                        // serialize table to String
-                                        let ready_table = {
-                                            if let Ok(table_str) = serde_json::to_string(&tr_table) {
-                                                table_str
-                                            }
-                                            else {
-                                                break Error(f!("Coludn't truncate table"));
-                                            }
-                                        };
+                    }
+                };
 
-                                        // Save truncated table to file
-                                        match fs::write(table_path, ready_table) {
-                                            Ok(_) => break Success(None),
-                                            Err(_) => break Error(f!("Durning operation table begin stop existing"))
-                                        }
-                                    },
-                                    Err(_) => break Error(f!("Coludn't truncate table"))
-                                }
+                return Error(f!("Database to which you're connected doesn't exists!"));
+            }
+
+            Error(f!("You're not connected to any database. In order to execute this command you must be connected!"))
+        },
+        Statement::Insert { 
+            or: _, 
+            into, 
+            table_name, 
+            columns,
+            overwrite,
+            source, 
+            partitioned: _, 
+            after_columns: _, 
+            table: _, // indictaes whethe "table" keyword was attached to INSER OVERWRITE query
+            on: _ 
+        } => {
+            let session_data = serde_json::from_str::<SessionData>(sessions.get(&session_id).unwrap()).unwrap();
+            let user_con_db = session_data.connected_to_database.clone();
+            
+            if user_con_db.is_some() { // user must be firsly connected to database
+                // Support for both operations types "INSERT INTO" and "INSERT OVERWRITE TABLE"
+                let op_type: Option<InsertOperations> = {
+                    if into {
+                        Some(InsertOperations::Into)
+                    }
+                    else if overwrite {
+                        Some(InsertOperations::Overwrite)
+                    }
+                    else {
+                        None
+                    }
+                };
+                
+                    // ...rust required safeguards for support only 2 insert operations
+                if op_type.is_some() {
+                    let op_type = op_type.unwrap();
+
+                    // Obtain table name
+                    let table_name = &table_name.0[0].value;
+
+                    // Obtain database name to which user is connected
+                    let user_con_db = session_data.connected_to_database.unwrap();
+
+                    // Db table path
+                    let dbt_path = get_dbtable_path(&user_con_db, table_name);
+
+                    // Create only when database and tab;e exists
+                    if dbt_path.exists() {
+                        // Obtain for which coulmns operation must be performed only
+                        let columns_from_query = {
+                            // To return Some(_) columns len from lexer must be greater then 0 hence them must exists
+                            if columns.len() > 0 {
+                                let mut c_r = Vec::new() as Vec<String>;
+                                for column in columns {
+                                    let column_name = column.value;
+                                    c_r.push(column_name);
+                                };
+                                Some(c_r)
                             }
                             else {
-                                break Error(f!("Entered table doesn't exist within Database"));
+                                None
                             }
-                        }
-                    },
-                    Statement::Drop { // For both table and database but indicator on what unit operation should be performed is "object_type" property
-                        object_type, 
-                        if_exists:_, 
-                        names, 
-                        cascade: _, 
-                        restrict:_, 
-                        purge: _ 
-                    } => {
-                        let object_name = &(&(&names[0] as &ObjectName).0[0] as &sqlparser::ast::Ident).value;
+                        };
                         
-                        match object_type {
-                            ObjectType::Table => {
-                                let connected_to_database = get_database_user_connected_to(sessions, &session_id);
-
-                                if let Some(database) = connected_to_database {
-                                    let table_path = get_dbtable_path(&database, &object_name);
-                                    
-                                    if table_path.exists() {
-                                        match fs::remove_file(table_path) {
-                                            Ok(_) => break Success(None),
-                                            Err(_) => break Error(f!("Couldn't delete table"))
-                                        }
-                                    }
-                                    else {
-                                        break Error(f!("This table doesn't exists"));
-                                    }
-                                }
-                                else {
-                                    break Error(f!("To perform this operation you must be connected to database firstly!"));
-                                }
-                            },
-                            _ => break Error(f!("Couldn't perform operation"))
-                        }
-                    },
-                    Statement::Query(query) => {
-                        match *query.body {
-                            SetExpr::Select(select_query) => {
-                                if let Some(db) = get_database_user_connected_to(sessions, &session_id) {
-                                        // Extract data from parser SQL query
-                                    let sel_proj = { // SELECT filter ... -> (// Always Must be returned list containing string with speecific name or single "all" value // When error in identifing selection then return Error as operation result)
-                                        let sel_proj = select_query.projection;
-                                        if sel_proj.len() > 0 {
-                                            let mut result_proj = vec![] as Vec<String>;
-                                            for proj in &sel_proj {
-                                                match proj {
-                                                    SelectItem::Wildcard => { // select all result fields from record
-                                                        result_proj.push(String::from("all"));
-                                                        break;
-                                                    },
-                                                    SelectItem::UnnamedExpr(inside) => { // single attribute to display
-                                                        if let Expr::Identifier(indent) = inside.clone() {
-                                                            if indent.value.len() > 0 {
-                                                                result_proj.push(indent.value)
-                                                            }
-                                                            else {
-                                                                // Don't allow to empty field names
-                                                                break;
-                                                            }
-                                                        }
-                                                        else {
-                                                            break;
-                                                        }
+                        // Obtain values (to insert for columns) from insert query (whole) // Error: When value coudn't be converted or vector with converted results is shorter then this from query values then loop is break inside brackets "{}" and further (below) code won't be performing as next
+                        let values_from_query = {
+                            // Values are attached directly into query (INSERT INTO ... VALUES ...)
+                            if let SetExpr::Values(vals) = source.body.as_ref() {
+                                let vals = &vals.0;
+                                // Ready to insert: List with all rows and it's values to insert
+                                let mut allrows_values_list: Vec<Vec<Field>> = vec![]; // 1st vector = store rows, 2nd vector = store values for columns for single row
+
+                                // Table columns are required to replace keyword DEFAULT by default value of column to which value is inserted
+                                let table_columns = sql_storage::read_definition(&dbt_path)
+                                    .map(|table| table.columns)
+                                    .unwrap_or_default();
+                            
+                                // Iterte over each row with values to insert for each column
+                                for each_row in vals.clone() {
+                                    let mut onerow_values_list: Vec<Field> = vec![];
+
+                                    // Extract all values from query and assing it to appropriate type supported by this database or break whole extract operation when some type from query isn't supported by this database
+                                    // Iterate over values from one row and extract values (extract in this "scenario" obtain value and it type from query and assign it to datatype supported by this database). When datatype from query isn't supported then whole (insert) operation will be stopped and not performed
+                                    for (position, val_ins) in each_row.iter().enumerate() {
+                                        if let Expr::Identifier(ident) = val_ins {
+                                            if ident.quote_style.is_none() && ident.value.to_uppercase() == "DEFAULT" {
+                                                // Column is taken from columns list of query or from table columns order when list wasn't attached
+                                                let column = match &columns_from_query {
+                                                    Some(columns_names) => columns_names.get(position).and_then(|name| table_columns.iter().find(|column| &column.name == name)),
+                                                    None => table_columns.get(position)
+                                                };
+
+                                                match column.map(|column| column.default_value().map(|value| Field::from_value(value, &column.d_type))) {
+                                                    Some(Ok(field)) => {
+                                                        onerow_values_list.push(field);
+                                                        continue;
                                                     },
                                                     _ => break
                                                 }
-                                            };
-
-                                            if result_proj.len() == sel_proj.len() {
-                                                result_proj
                                             }
-                                            else {
-                                                break Error(f!("Incompatible projection values!"));
-                                            }
-                                        }
-                                        else {
-                                            break Error(f!("Incompatible projection values!"));
+                                        };
+
+                                        // Functions (e.g: nextval('orders_seq') or CURRENT_TIMESTAMP) are computed before insertion
+                                        let field = Field::from_literal(val_ins).or_else(|| match val_ins {
+                                            Expr::Function(_) => {
+                                                let db_path = get_db_path(&user_con_db);
+                                                let context = ExprContext { database: Some(&db_path), ..ExprContext::default() };
+                                                sql_expr::evaluate(val_ins, &[], &[], &context).ok().map(|value| Field::from_value(value, &sql_expr::expression_type(val_ins, &[])))
+                                            },
+                                            _ => None
+                                        });
+                                        match field {
+                                            Some(field) => onerow_values_list.push(field),
+                                            None => break // for unsuported data types
                                         }
                                     };
-                                    let sel_from_table = { // ... FROM "table_name" (// Benath is always not empty string as this representing table name, when something went wrong durning check then opeartion is break with returned "Error(reason_string)" outside)
-                                    let from = select_query.from;
-                                        // obtain single "table name"
-                                    if from.len() == 1 {
-                                        let from = &from[0].relation;
-                                        if let TableFactor::Table { name, alias: _, args: _, with_hints: _ } = from {
-                                                // Go ahead only when list with parsed table_names isn't empty
-                                            if name.0.len() > 0 {
-                                                let table_name = name.0[0].value.to_owned();
-                                                    // table name length must not be empty e.g: empty quotes "" or ''
-                                                if table_name.len() > 0 {
-                                                    table_name
-                                                }
-                                                else {
-                                                    break Error(f!("Table name doesn't fullfill requirements"));
-                                                }
-                                            }
-                                            else {
-                                                break Error(f!("Couldn't obtain table name"));
-                                            }
-                                        }
-                                        else {
-                                            break Error(f!("Couldn't obtain table name"));
-                                        }
+                                
+                                    // ACID rules must be fullfiled so: (...to perform query all types must be correctly extracted so (extracted_values_from_row_stored.len() == query_row_values.len()) otheriwise don't perform any slice of whole query to maintain data consistancy and break loop here)
+                                    if onerow_values_list.len() == each_row.len() {
+                                        allrows_values_list.push(onerow_values_list);
                                     }
                                     else {
-                                        // Don't allow to add multiple tables to perf selection from
-                                        break Error(f!("Incompatible selection from format!"));
-                                    }
+                                        break;
                                     };
-                                    let sel_statements = select_query.selection; // WHERE ...
-
-                                    let table_path = get_dbtable_path(&db, &sel_from_table);
-                                    if table_path.exists() {
-                                        match process_sql(ProcessSQLSupportedQueries::Select(&table_path, sel_proj, sel_statements)) {
-                                            Ok(table_records) => {
-                                                    // Convert obtained record to JSON format and when serialization has been finalized with error return communicate otherwise obtained data in JSON format
-                                                let records_str = serde_json::to_string(&table_records.rows)
-                                                    .map_or_else(|_err| (false, String::new()), |suc| (true, suc));
-                                                
-                                                if records_str.0 {
-                                                    // Send to user only finded rows without table boilerplate
-                                                    // When rows are empty then send "null" as records result
-                                                    break Success(Some(records_str.1));
-                                                }
-                                                else {
-                                                    break Error(f!("Couldn't convert records to redable form"));
-                                                }
-                                            },
-                                            Err(_) => ()
-                                        }
-                                    }
-                                    else {
-                                        break Error(f!("Table given by you doesn't exists in database to which you're connected"));
-                                    }
+                                };
+                            
+                                // ACID principles must be fullfiled so ...rows_query.len() must be equal rows_with_converted_values.len() otherwise operation won't be performed
+                                if allrows_values_list.len() == vals.len() {
+                                    allrows_values_list
                                 }
                                 else {
-                                    break Error(f!("You're not connected to database"));
+                                    return Error(f!(r#"Some type from your "INSERT" query isn't supported, from this plaintiff whole operation can't be perfomed"#));
+                                }
+                            }
+                            else {
+                                // Values are rows returned by SELECT query (INSERT INTO ... SELECT ...). Type of each value is type of column returned by query
+                                let select_query = match SelectQuery::from_query(&get_db_path(&user_con_db), &source) {
+                                    Ok(select_query) => select_query,
+                                    Err(reason) => return Error(reason)
                                 };
 
-                                // println!("\nExpr: {:?}\n\nTable name: {}", sel_proj, sel_from);
-                                break Success(None);
-                            },
-                            _ => break Error(f!("Not supported query"))
-                        };
-                    },
-                    Statement::Delete { table_name, using: _, selection: condition } => {
-                        if let Some(db) = get_database_user_connected_to(sessions, &session_id) {
-                            let table_name = {
-                                if let TableFactor::Table { name, alias: _, args: _, with_hints: _ } = table_name {
-                                    (name.0)[0].value.clone()
-                                }
-                                else {
-                                    return Error(f!("Couldn't perform query"))
+                                match sql_select::select(&select_query, None) {
+                                    Ok((select_columns, select_rows)) => {
+                                        select_rows
+                                            .into_iter()
+                                            .map(|row| {
+                                                row.into_iter()
+                                                    .zip(select_columns.iter())
+                                                    .map(|(cell, column)| Field::from_value(cell.value, &column.d_type))
+                                                    .collect::<Vec<Field>>()
+                                            })
+                                            .collect::<Vec<_>>()
+                                    },
+                                    Err(_) => return Error("Couldn't perform SELECT query from which rows should be inserted".to_string())
                                 }
+                            }
+                        };
+
+                        // SELECT query can return no rows and then there is nothing to insert (but "INSERT OVERWRITE" still removes all table rows)
+                        let overwrite = matches!(op_type, InsertOperations::Overwrite);
+                        if values_from_query.is_empty() {
+                            if !overwrite {
+                                return Success(Some("INSERT operation has been performed".to_string()));
                             };
-    
-                            let table_path = get_dbtable_path(&db, &table_name);
-                            if table_path.exists() {
-                                match process_sql(ProcessSQLSupportedQueries::Delete(&table_path, condition)) {
-                                    Ok(deleted_rows) => {
-                                        // Send only deleted rows
-                                        // Table without deleted rows is creating into process_sql function especially for it
-                                        match serde_json::to_string(&deleted_rows.rows) {
-                                            Ok(rows) => break Success(Some(rows)),
-                                            Err(_) => break Error(f!("SQL query couldn't been performed"))
-                                        }
-                                    },
-                                    Err(_) => break Error(f!("SQL query couldn't been performed"))
+
+                            return match process_sql(ProcessSQLSupportedQueries::Truncate(&dbt_path)) {
+                                Ok(table) if sql_storage::write_table(&dbt_path, &table).is_ok() => Success(Some("INSERT operation has been performed".to_string())),
+                                _ => Error("Coludn't save results of operation from some reason".to_string())
+                            };
+                        };
+
+                        // Create table definition with new inserted records and save them beside stored rows
+                        // When operation must be performed for specific columns then columns correcteness and whether that operation can be performed is check inside process_sql function
+                        match process_sql(ProcessSQLSupportedQueries::Insert(&dbt_path, columns_from_query, values_from_query, op_type)) {
+                            Ok(ready_table) => {
+                                // Rows can be saved only when they don't violate table constraints (e.g: duplicated value of PRIMARY KEY) and unique indexes
+                                let changes = match sql_json::insert_rows(&dbt_path, &ready_table, overwrite) {
+                                    Ok(changes) => changes,
+                                    Err(reason) => return Error(reason)
+                                };
+
+                                // Inserted rows must reffer to existing rows by their foreign keys (and rows which reffer to rows removed by "INSERT OVERWRITE" are changed according to foreign keys actions)
+                                match maintain_foreign_keys(&user_con_db, table_name, changes) {
+                                    Ok(_) => Success(Some(f!(r#"INSERT operation has been performed"#))),
+                                    Err(reason) => Error(reason)
                                 }
+                            },
+                            Err(_) => {Error(f!("Values couldn't been inserted to table"))}
+                        }
+                    }
+                    else {
+                        Error(f!("Database to which you're connected doesn't exists | Or table to which you try attach data doesn't exists in database to which you're connected"))
+                    }
+                }
+                else {
+                    Error(f!("This \"INSERT\" operation isn't supported"))
+                }
+            }
+            else {
+                Error(f!("\"INSERT\" query must include \"INTO\""))
+            }
+        },
+        Statement::Truncate { 
+            table_name, 
+            partitions: _ 
+        } => {
+            // Get whether user is connected to database and database name to which is
+            let user_con_db = get_database_user_connected_to(sessions, &session_id);
+
+            if user_con_db.is_some() {
+                let user_con_db = user_con_db.unwrap();
+                
+                // Truncate table rows operation
+                let table_name = &table_name.0[0].value;
+                let table_path = get_dbtable_path(&user_con_db, table_name);
+
+                // Perform operation only when table exists into specified database
+                if table_path.exists() {
+                    // Rows removed by truncation are kept only for actions of foreign keys which reffer to them
+                    let referenced = match sql_references::is_referenced(&get_db_path(&user_con_db), table_name) {
+                        Ok(referenced) => referenced,
+                        Err(reason) => return Error(reason)
+                    };
+
+                    // Begin truncate operation and its results
+                    match process_sql(ProcessSQLSupportedQueries::Truncate(&table_path)) {
+                        Ok(_) => {
+                            // Remove rows from table file page after page
+                            let mut changes = vec![];
+                            if sql_storage::remove_rows(&table_path, |row| if referenced { changes.push((row, None)) }).is_err() {
+                                return Error(f!("Durning operation table begin stop existing"));
+                            };
+
+                            // Rows which reffer to removed rows by foreign keys are changed according to foreign keys actions
+                            match maintain_foreign_keys(&user_con_db, table_name, changes) {
+                                Ok(_) => Success(None),
+                                Err(reason) => Error(reason)
                             }
-                            else {
-                                break Error(f!("Table given by you doesn't exists in database to which you're connected"));
+                        },
+                        Err(_) => Error(f!("Coludn't truncate table"))
+                    }
+                }
+                else {
+                    Error(f!("Entered table doesn't exist within Database"))
+                }
+            }
+            else {
+                Error("You're not connected to database".to_string())
+            }
+        },
+        Statement::Drop { // For both table and database but indicator on what unit operation should be performed is "object_type" property
+            object_type, 
+            if_exists, 
+            names, 
+            cascade: _, 
+            restrict:_, 
+            purge: _ 
+        } => {
+            let object_name = &(&(&names[0] as &ObjectName).0[0] as &sqlparser::ast::Ident).value;
+            
+            match object_type {
+                ObjectType::Table => {
+                    let connected_to_database = get_database_user_connected_to(sessions, &session_id);
+
+                    if let Some(database) = connected_to_database {
+                        let table_path = get_dbtable_path(&database, &object_name);
+                        
+                        if table_path.exists() {
+                            // Table can't be removed when other table reffers to it by foreign key
+                            match sql_references::database_tables(&get_db_path(&database)).map(|tables| sql_references::referencing_key(object_name, &tables)) {
+                                Ok(Some((key_name, referencing_table_name))) => return Error(f!("Table can't be removed because it is referenced by foreign key \"{}\" of table \"{}\"", key_name, referencing_table_name)),
+                                Err(reason) => return Error(reason),
+                                Ok(None) => ()
+                            };
+
+                            // Indexes of table are removed together with table
+                            match sql_wal::remove_file(&table_path).and_then(|_| sql_index::drop_table_indexes(&get_db_path(&database), object_name)) {
+                                Ok(_) => Success(None),
+                                Err(_) => Error(f!("Couldn't delete table"))
                             }
                         }
                         else {
-                            break Error(f!("You're not connected to database"));
+                            Error(f!("This table doesn't exists"))
                         }
+                    }
+                    else {
+                        Error(f!("To perform this operation you must be connected to database firstly!"))
+                    }
+                },
+                ObjectType::Index => {
+                    match get_database_user_connected_to(sessions, &session_id) {
+                        Some(database) => sql_index::drop_index(&get_db_path(&database), object_name, if_exists).map_or_else(Error, |_| Success(None)),
+                        None => Error("To perform this operation you must be connected to database firstly!".to_string())
+                    }
+                },
+                ObjectType::Sequence => {
+                    match get_database_user_connected_to(sessions, &session_id) {
+                        Some(database) => sql_sequences::drop_sequence(&get_db_path(&database), object_name, if_exists).map_or_else(Error, |_| Success(None)),
+                        None => Error("To perform this operation you must be connected to database firstly!".to_string())
+                    }
+                },
+                _ => Error(f!("Couldn't perform operation"))
+            }
+        },
+        Statement::CreateIndex { name, table_name, columns, unique, if_not_exists } => {
+            // Index is created for table from database to which user is connected. Indexed columns must be reffered by their names
+            let database = match get_database_user_connected_to(sessions, &session_id) {
+                Some(database) => database,
+                None => return Error("You're not connected to database".to_string())
+            };
+            let (index_name, table_name) = match (name.0.last(), table_name.0.last()) {
+                (Some(index_name), Some(table_name)) => (index_name.value.clone(), table_name.value.clone()),
+                _ => return Error("Couldn't obtain index name or table name".to_string())
+            };
+            if index_name.is_empty() || unavailable::os_file_system_check_unavailable_characters_into(&index_name) || unavailable::FILENAMES_WINDOWS.contains(&index_name.as_str()) {
+                return Error("Index name doesn't fullfill requirements".to_string());
+            };
+            if !get_dbtable_path(&database, &table_name).exists() {
+                return Error(f!("Table \"{}\" doesn't exists in database to which you're connected", table_name));
+            };
+
+            let table = match sql_storage::read_definition(&get_dbtable_path(&database, &table_name)) {
+                Ok(table) => table,
+                Err(_) => return Error(f!("Couldn't read table \"{}\"", table_name))
+            };
+            let mut index_columns = vec![] as Vec<IndexColumn>;
+            for column in &columns {
+                match &column.expr {
+                    Expr::Identifier(ident) => match table.get_column_type(&ident.value) {
+                        Some(d_type) => index_columns.push(IndexColumn { name: ident.value.clone(), d_type }),
+                        None => return Error(f!("Column \"{}\" doesn't exist in table \"{}\"", ident.value, table_name))
                     },
-                    Statement::Update { table, assignments: set, from: _, selection: condition } => {
-                        if let Some(db) = get_database_user_connected_to(sessions, &session_id) {
-                            let table_name = if let TableFactor::Table { name, alias: _, args: _, with_hints: _ } = table.relation {
-                                (name.0)[0].value.clone()
-                            }
-                            else {
-                                break Error(f!("Couldn't perform query"));
-                            };
+                    _ => return Error("Only columns can be indexed".to_string())
+                };
+            }
 
-                            let table_path = get_dbtable_path(&db, &table_name);
-                            if table_path.exists() {
-                                // When table wasn''t updated from some logical manner then unupdated table will be returned 
-                                match process_sql(ProcessSQLSupportedQueries::Update(&table_path, set, condition)) {
-                                    Ok(updated_or_not_table) => {
-                                        // Re-save regardless on that it has been updated or not
-                                        let updated_table_stri = serde_json::to_string(&updated_or_not_table).unwrap();
-                                        match fs::write(table_path, &updated_table_stri) {
-                                            Ok(_) => break Success(Some(serde_json::to_string(&updated_or_not_table.rows.unwrap()).unwrap())),
-                                            Err(_) => break Error(f!("SQL query couldn't been performed"))
-                                        }
-                                    },
-                                    Err(_) => break Error(f!("SQL query couldn't been performed"))
-                                }
-                            }
-                            else {
-                                break Error(f!("Table given by you doesn't exists in database to which you're connected"));
-                            }
+            let definition = IndexDefinition { name: index_name, table: table_name, columns: index_columns, unique, key: false, primary: false };
+            sql_index::create_index(&get_db_path(&database), definition, if_not_exists).map_or_else(Error, |_| Success(None))
+        },
+        Statement::CreateSequence { temporary: _, if_not_exists, name } => {
+            // Sequence is created in database to which user is connected
+            match (get_database_user_connected_to(sessions, &session_id), name.0.last()) {
+                (Some(database), Some(sequence_name)) => sql_sequences::create_sequence(&get_db_path(&database), &sequence_name.value, if_not_exists).map_or_else(Error, |_| Success(None)),
+                (None, _) => Error("You're not connected to database".to_string()),
+                (_, None) => Error("Couldn't obtain sequence name".to_string())
+            }
+        },
+        Statement::Query(query) => {
+            if let Some(db) = get_database_user_connected_to(sessions, &session_id) {
+                // Extract data from parser SQL query and compute it (// When something went wrong durning check or computation then reason is returned as error)
+                let db_path = get_db_path(&db);
+                let records = if sql_select::uses_unsupported_constructs(&query) {
+                    // Queries which can't be computed by own engine (e.g.: with window functions) are computed by DataFusion
+                    sql_datafusion::select_blocking(&db_path, &query.to_string())
+                        .map(|(_, rows)| if rows.is_empty() { None } else { Some(rows) })
+                        .map_err(|err| f!("Couldn't perform SELECT query: {}", err))
+                }
+                else {
+                    match SelectQuery::from_query(&db_path, &query) {
+                        Ok(select_query) => process_sql(ProcessSQLSupportedQueries::Select(select_query))
+                            .map(|table_records| table_records.rows)
+                            .map_err(|_| f!("Couldn't perform SELECT query. Check whether all reffered columns exists, aren't ambiguous and are grouped when aggregate functions are used")),
+                        Err(reason) => Err(reason)
+                    }
+                };
+
+                match records {
+                    Ok(rows) => {
+                            // Convert obtained record to JSON format and when serialization has been finalized with error return communicate otherwise obtained data in JSON format
+                        let records_str = serde_json::to_string(&rows)
+                            .map_or_else(|_err| (false, String::new()), |suc| (true, suc));
+                        
+                        if records_str.0 {
+                            // Send to user only finded rows without table boilerplate
+                            // When rows are empty then send "null" as records result
+                            Success(Some(records_str.1))
                         }
                         else {
-                            break Error(f!("You're not connected to database"));
+                            Error(f!("Couldn't convert records to redable form"))
                         }
                     },
-                    Statement::AlterTable { name, operation } => {
-                        // User must be prior connected to specific database
-                        if let Some(db) = get_database_user_connected_to(sessions, &session_id) {
-                            let ex_table_name = &name.0[0].value; // Existsing table name (on that operation is performing)
-                            let table_path = get_dbtable_path(&db, ex_table_name);
-
-                            if table_path.exists() {
-                                // Operation that depends on file system will be performed here but those operating on json will be performed by sql_json.rs function but save if success will be performed here as in others ops
-                                match operation {
-                                    AlterTableOperation::RenameTable { table_name } => {
-                                        let ren_table_name = table_name.0[0].value.borrow() as &String;
-                                        let ren_table_path = get_dbtable_path(&db, ren_table_name);
-                                        
-                                        // To setup specific name for table in database must doesn't exists that table name thus also table name can't be changed to same name
-                                        if !ren_table_path.exists() {
-                                            // Determine path to file
-                                            let tb_path = table_path.clone();
-                                            let tb_path = tb_path.parent().expect("Couldn't get table directory");
-                                            let tb_path = tb_path.as_os_str().to_str().expect("Couldn't convert table path to string");
-                                        
-
-                                            // Rename table
-                                            break fs::rename(table_path, format!("{pth}/{tb}.json", tb = table_name, pth = tb_path))
-                                                .map_or_else(
-                                                    |_| Error(f!("Couldn't rename table")),
-                                                    |_| Success(None)
-                                                );
-                                        }
-                                        else {
-                                            break Error("Table name to that you would like update table already exists in that database".to_string());
-                                        }
-                                    },
-                                    _ => {
-                                        // Process (save, return adequate response to op result)
-                                        let sql = process_sql(ProcessSQLSupportedQueries::AlterTable(&table_path, operation));
-                                        match sql {
-                                            Ok(modified_table) => {
-                                                // Save result to same table file
-                                                if fs::write(table_path, serde_json::to_string(&modified_table).expect("Couldn't convert table to json format")).is_ok() {
-                                                    break Success(None);
-                                                };
-                                                
-                                                // When above operation couldn't been perf. return explicity Error
-                                                break Error(f!("Couldn't save result of performed operation"));
-                                            },
-                                            Err(_) => break Error("Couldn't perform operation. Maybe operation isn't supported".to_string()) // a.o: For unsuppoted AlterTable operations via "process_sql(_)" function 
-                                        }
-                                    }
-                                }
+                    Err(reason) => Error(reason)
+                }
+            }
+            else {
+                Error(f!("You're not connected to database"))
+            }
+        },
+        Statement::Delete { table_name, using: _, selection: condition } => {
+            if let Some(db) = get_database_user_connected_to(sessions, &session_id) {
+                let table_name = {
+                    if let TableFactor::Table { name, alias: _, args: _, with_hints: _ } = table_name {
+                        (name.0)[0].value.clone()
+                    }
+                    else {
+                        return Error(f!("Couldn't perform query"))
+                    }
+                };
+
+                let table_path = get_dbtable_path(&db, &table_name);
+                if table_path.exists() {
+                    // Only deleted rows are removed from table file (deletion is reverted together with statement when it can't be performed because of foreign keys)
+                    match sql_json::change_rows(&table_path, None, condition.as_ref()) {
+                        Ok((_, changes)) => {
+                            // Rows which reffer to deleted rows by foreign keys are changed according to foreign keys actions
+                            let deleted_rows = changes.iter().map(|(row, _)| row.clone()).collect::<Vec<_>>();
+                            if let Err(reason) = maintain_foreign_keys(&db, &table_name, changes) {
+                                return Error(reason);
+                            };
+
+                            // Send only deleted rows
+                            match serde_json::to_string(&if !deleted_rows.is_empty() { Some(deleted_rows) } else { None }) {
+                                Ok(rows) => Success(Some(rows)),
+                                Err(_) => Error(f!("SQL query couldn't been performed"))
+                            }
+                        },
+                        Err(reason) => Error(reason)
+                    }
+                }
+                else {
+                    Error(f!("Table given by you doesn't exists in database to which you're connected"))
+                }
+            }
+            else {
+                Error(f!("You're not connected to database"))
+            }
+        },
+        Statement::Update { table, assignments: set, from: _, selection: condition } => {
+            if let Some(db) = get_database_user_connected_to(sessions, &session_id) {
+                let table_name = if let TableFactor::Table { name, alias: _, args: _, with_hints: _ } = table.relation {
+                    (name.0)[0].value.clone()
+                }
+                else {
+                    return Error(f!("Couldn't perform query"));
+                };
+
+                let table_path = get_dbtable_path(&db, &table_name);
+                if table_path.exists() {
+                    // Only updated rows are changed in table file. Updated rows can't violate table constraints (e.g: two rows with same value of UNIQUE column) and unique indexes
+                    match sql_json::change_rows(&table_path, Some(&set), condition.as_ref()) {
+                        Ok((_, changes)) => {
+                            // Updated rows must reffer to existing rows by foreign keys and rows which reffer to updated rows are changed according to foreign keys actions
+                            let updated_rows = changes.iter().filter_map(|(_, new_row)| new_row.clone()).collect::<Vec<_>>();
+                            if let Err(reason) = maintain_foreign_keys(&db, &table_name, changes) {
+                                return Error(reason);
+                            };
+
+                            // Send only updated rows
+                            match serde_json::to_string(&updated_rows) {
+                                Ok(rows) => Success(Some(rows)),
+                                Err(_) => Error(f!("SQL query couldn't been performed"))
+                            }
+                        },
+                        Err(reason) => Error(reason)
+                    }
+                }
+                else {
+                    Error(f!("Table given by you doesn't exists in database to which you're connected"))
+                }
+            }
+            else {
+                Error(f!("You're not connected to database"))
+            }
+        },
+        Statement::AlterTable { name, operation } => {
+            // User must be prior connected to specific database
+            if let Some(db) = get_database_user_connected_to(sessions, &session_id) {
+                let ex_table_name = &name.0[0].value; // Existsing table name (on that operation is performing)
+                let table_path = get_dbtable_path(&db, ex_table_name);
+
+                if table_path.exists() {
+                    // Operation that depends on file system will be performed here but those operating on json will be performed by sql_json.rs function but save if success will be performed here as in others ops
+                    match operation {
+                        AlterTableOperation::RenameTable { table_name } => {
+                            let ren_table_name = table_name.0[0].value.borrow() as &String;
+                            let ren_table_path = get_dbtable_path(&db, ren_table_name);
+                            
+                            // To setup specific name for table in database must doesn't exists that table name thus also table name can't be changed to same name
+                            if !ren_table_path.exists() {
+                                // Rename table (its indexes are attached to new name)
+                                sql_wal::rename(&table_path, &ren_table_path)
+                                    .and_then(|_| sql_index::rename_table(&get_db_path(&db), ex_table_name, ren_table_name))
+                                    .map_or_else(
+                                        |_| Error(f!("Couldn't rename table")),
+                                        |_| Success(None)
+                                    )
                             }
                             else {
-                                break Error(f!("Table given by you doesn't exists in database to which you're connected"));
+                                Error("Table name to that you would like update table already exists in that database".to_string())
+                            }
+                        },
+                        _ => {
+                            // Indexes reffer to renamed column by its new name
+                            let renamed_column = match &operation {
+                                AlterTableOperation::RenameColumn { old_column_name, new_column_name } => Some((old_column_name.value.clone(), new_column_name.value.clone())),
+                                _ => None
+                            };
+
+                            // Process (save, return adequate response to op result)
+                            let sql = process_sql(ProcessSQLSupportedQueries::AlterTable(&table_path, operation));
+                            match sql {
+                                Ok(modified_table) => {
+                                    if let Some((old_column_name, new_column_name)) = renamed_column {
+                                        if sql_index::rename_column(&get_db_path(&db), ex_table_name, &old_column_name, &new_column_name).is_err() {
+                                            return Error("Couldn't change indexes of table".to_string());
+                                        };
+                                    };
+
+                                    // Save changed definition and rows changed by it (page after page) to same table file. Rows must fulfil constraints of changed table (e.g: added CHECK constraint) and unique indexes
+                                    if let Err(reason) = sql_json::alter_rows(&table_path, &modified_table) {
+                                        return Error(reason);
+                                    };
+
+                                    // Foreign keys of rows of changed table must reffer to existing rows (statement is reverted when they don't)
+                                    match sql_references::check_foreign_keys(&get_db_path(&db), ex_table_name) {
+                                        Ok(_) => Success(None),
+                                        Err(reason) => Error(reason)
+                                    }
+                                },
+                                Err(_) => Error("Couldn't perform operation. Maybe operation isn't supported".to_string()) // a.o: For unsuppoted AlterTable operations via "process_sql(_)" function 
                             }
-                        }
-                        else {
-                            break Error(f!("You're not connected to database"));
-                        }
-                    },
-                    _ => {
-                        if parse_op_result.len() > it {
-                            continue;
-                        }
-                        else {
-                            break Error(f!("SQL query couldn't been performed"));
                         }
                     }
                 }
+                else {
+                    Error(f!("Table given by you doesn't exists in database to which you're connected"))
+                }
+            }
+            else {
+                Error(f!("You're not connected to database"))
             }
         },
-        Err(_) => return Error(f!("SQL Syntax Error"))
+        _ => Error("This kind of statement isn't supported".to_string())
     }
 }
 
@@ -700,3 +990,658 @@ fn sql_parser_test() {
     let parsed_sql = sqlparser::parser::Parser::parse_sql(&sql_dialect, sql).unwrap();
     println!("{:?}", parsed_sql)
 }
+
+#[cfg(test)]
+/// Create database in temporary data directory (used by thread instead of data directory of server, so test has got own write-ahead log and transactions) and connect sessions to it.
+/// Returns data directory (removed after test), directory of database and sessions
+fn test_database(session_ids: &[&str]) -> (std::path::PathBuf, std::path::PathBuf, HashMap<String, String>) {
+    let data_path = std::env::temp_dir().join(f!("wastledb_{}", uuid::Uuid::new_v4().simple()));
+    let db_path = data_path.join("test");
+    std::fs::create_dir_all(&db_path).unwrap();
+    sql_wal::set_data_path(&data_path);
+
+    let sessions = session_ids
+        .iter()
+        .map(|session_id| (session_id.to_string(), r#"{"timestamp":0,"connected_to_database":"test","encryption":null}"#.to_string()))
+        .collect::<HashMap<_, _>>();
+    (data_path, db_path, sessions)
+}
+
+#[test]
+fn test_process_query_statements() {
+    // Session connected to temporary database
+    let (data_path, _, mut sessions) = test_database(&["session"]);
+    let mut run = |query: &str| process_query(query, None, "session".to_string(), &mut sessions);
+
+    // Statements are executed in order until first failed statement
+    match run("CREATE TABLE users (id INT, name VARCHAR(20)); INSERT INTO users VALUES (1, 'ann'); SELECT name FROM missing; INSERT INTO users VALUES (2, 'bob')") {
+        Error(reason) => assert!(reason.starts_with("Statement 3 (of 4) couldn't be performed: ")),
+        Success(_) => panic!("Query with failed statement can't succeed")
+    };
+
+    // Each statement has got own result
+    match run("SELECT name FROM users; SELECT COUNT(*) AS count FROM users") {
+        Success(Some(results)) => assert_eq!(results, r#"[[[{"col":"name","value":"ann"}]],[[{"col":"count","value":1}]]]"#),
+        other => panic!("Unexpected result {:?}", other)
+    };
+
+    // Single statement result isn't wrapped
+    match run("SELECT id FROM users") {
+        Success(Some(results)) => assert_eq!(results, r#"[[{"col":"id","value":1}]]"#),
+        other => panic!("Unexpected result {:?}", other)
+    };
+
+    // Query which can't be computed by own engine is computed by DataFusion
+    match run("SELECT id, RANK() OVER (ORDER BY id) AS position FROM users") {
+        Success(Some(results)) => assert_eq!(results, r#"[[{"col":"id","value":1},{"col":"position","value":1}]]"#),
+        other => panic!("Unexpected result {:?}", other)
+    };
+    match run("WITH named AS (SELECT name FROM users) SELECT name FROM named") {
+        Success(Some(results)) => assert_eq!(results, r#"[[{"col":"name","value":"ann"}]]"#),
+        other => panic!("Unexpected result {:?}", other)
+    };
+
+    // Errors of query supported by own engine are returned without trying DataFusion
+    match run("SELECT nme FROM users") {
+        Error(reason) => assert!(reason.starts_with("Couldn't perform SELECT query. Check whether all reffered columns exists")),
+        other => panic!("Unexpected result {:?}", other)
+    };
+
+    std::fs::remove_dir_all(&data_path).unwrap();
+}
+
+#[test]
+fn test_process_query_insert() {
+    // Session connected to temporary database
+    let (data_path, _, mut sessions) = test_database(&["session"]);
+    let mut run = |query: &str| process_query(query, None, "session".to_string(), &mut sessions);
+
+    assert!(matches!(run("CREATE TABLE users (id INT, name VARCHAR(20), role TEXT DEFAULT 'guest')"), Success(_)));
+    assert!(matches!(run("CREATE TABLE archive (name TEXT, id INT)"), Success(_)));
+
+    // Omitted columns get their default value or NULL
+    assert!(matches!(run("INSERT INTO users (name, id) VALUES ('ann', 1), ('bob', 2)"), Success(_)));
+    assert!(matches!(run("INSERT INTO users (id) VALUES (3)"), Success(_)));
+    match run("SELECT id, name, role FROM users WHERE id > 1") {
+        Success(Some(results)) => assert_eq!(results, r#"[[{"col":"id","value":2},{"col":"name","value":"bob"},{"col":"role","value":"guest"}],[{"col":"id","value":3},{"col":"name","value":null},{"col":"role","value":"guest"}]]"#),
+        other => panic!("Unexpected result {:?}", other)
+    };
+
+    // Unknown and repeated columns aren't allowed
+    assert!(matches!(run("INSERT INTO users (id, missing) VALUES (4, 'x')"), Error(_)));
+    assert!(matches!(run("INSERT INTO users (id, id) VALUES (4, 5)"), Error(_)));
+
+    // Rows are copied from results of SELECT query
+    assert!(matches!(run("INSERT INTO archive (id, name) SELECT id, name FROM users WHERE name IS NOT NULL"), Success(_)));
+    assert!(matches!(run("INSERT INTO archive SELECT name, id FROM users WHERE id = 3"), Success(_)));
+    assert!(matches!(run("INSERT INTO archive SELECT name, id FROM users WHERE id > 100"), Success(_)));
+    match run("SELECT name, id FROM archive ORDER BY id") {
+        Success(Some(results)) => assert_eq!(results, r#"[[{"col":"name","value":"ann"},{"col":"id","value":1}],[{"col":"name","value":"bob"},{"col":"id","value":2}],[{"col":"name","value":null},{"col":"id","value":3}]]"#),
+        other => panic!("Unexpected result {:?}", other)
+    };
+
+    // Results of SELECT query must fit to table columns
+    assert!(matches!(run("INSERT INTO archive SELECT id, name FROM users"), Error(_)));
+
+    std::fs::remove_dir_all(&data_path).unwrap();
+}
+
+#[test]
+fn test_process_query_types() {
+    // Session connected to temporary database
+    let (data_path, _, mut sessions) = test_database(&["session"]);
+    let mut run = |query: &str| process_query(query, None, "session".to_string(), &mut sessions);
+
+    assert!(matches!(run("CREATE TABLE events (id INT, price FLOAT, day DATE, created TIMESTAMP, active BOOLEAN, notes LONGTEXT)"), Success(_)));
+    assert!(matches!(run("INSERT INTO events VALUES (1, -2.5, '2022-12-01', '2022-12-01 10:30:00', TRUE, 'first'), (2, 10, '2021-01-15', '2021-01-15T08:00:00.5', FALSE, NULL)"), Success(_)));
+    assert!(matches!(run("INSERT INTO events (id, day, created) VALUES (3, DATE '2022-06-30', '2022-06-30')"), Success(_)));
+
+    // Values which don't fit to column type are rejected
+    assert!(matches!(run("INSERT INTO events (id) VALUES (1.5)"), Error(_)));
+    assert!(matches!(run("INSERT INTO events (price) VALUES ('cheap')"), Error(_)));
+    assert!(matches!(run("INSERT INTO events (day) VALUES ('2022-13-01')"), Error(_)));
+    assert!(matches!(run("INSERT INTO events (active) VALUES (1)"), Error(_)));
+    assert!(matches!(run("UPDATE events SET created = 'yesterday' WHERE id = 1"), Error(_)));
+
+    // Values are stored in normalized form and compared according to column type
+    match run("SELECT id, created FROM events WHERE day >= '2022-01-01' AND price < 0 OR id = 3 ORDER BY created DESC") {
+        Success(Some(results)) => assert_eq!(results, r#"[[{"col":"id","value":1},{"col":"created","value":"2022-12-01 10:30:00"}],[{"col":"id","value":3},{"col":"created","value":"2022-06-30 00:00:00"}]]"#),
+        other => panic!("Unexpected result {:?}", other)
+    };
+    match run("SELECT id FROM events WHERE created < TIMESTAMP '2021-01-15 08:00:01' OR NOT active ORDER BY day") {
+        Success(Some(results)) => assert_eq!(results, r#"[[{"col":"id","value":2}]]"#),
+        other => panic!("Unexpected result {:?}", other)
+    };
+    assert!(matches!(run("UPDATE events SET price = 3, active = FALSE WHERE day = DATE '2022-12-01'"), Success(_)));
+    match run("SELECT price, active FROM events WHERE id = 1") {
+        Success(Some(results)) => assert_eq!(results, r#"[[{"col":"price","value":3.0},{"col":"active","value":false}]]"#),
+        other => panic!("Unexpected result {:?}", other)
+    };
+
+    std::fs::remove_dir_all(&data_path).unwrap();
+}
+
+#[test]
+fn test_process_query_keys() {
+    // Session connected to temporary database
+    let (data_path, db_path, mut sessions) = test_database(&["session"]);
+    let mut run = |query: &str| process_query(query, None, "session".to_string(), &mut sessions);
+
+    assert!(matches!(run("CREATE TABLE users (id INT PRIMARY KEY, email TEXT UNIQUE)"), Success(_)));
+    assert!(matches!(run("CREATE TABLE grades (student INT, course INT, grade INT, CONSTRAINT grades_key PRIMARY KEY (student, course))"), Success(_)));
+
+    // Keys must reffer to existing columns and table can have got only one PRIMARY KEY
+    assert!(matches!(run("CREATE TABLE broken (id INT, PRIMARY KEY (missing))"), Error(_)));
+    assert!(matches!(run("CREATE TABLE broken (id INT PRIMARY KEY, code INT, PRIMARY KEY (code))"), Error(_)));
+
+    // Duplicated and NULL values of PRIMARY KEY are rejected but UNIQUE key can have got many NULLs
+    assert!(matches!(run("INSERT INTO users VALUES (1, 'ann@x.pl'), (2, NULL), (3, NULL)"), Success(_)));
+    match run("INSERT INTO users VALUES (1, 'bob@x.pl')") {
+        Error(reason) => assert_eq!(reason, r#"Duplicate value (1) for key "users_pkey" (PRIMARY KEY (id))"#),
+        other => panic!("Unexpected result {:?}", other)
+    };
+    match run("UPDATE users SET email = 'ann@x.pl' WHERE id = 2") {
+        Error(reason) => assert_eq!(reason, r#"Duplicate value (ann@x.pl) for key "users_email_key" (UNIQUE (email))"#),
+        other => panic!("Unexpected result {:?}", other)
+    };
+    assert!(matches!(run("INSERT INTO users (email) VALUES ('cid@x.pl')"), Error(_)));
+
+    // Composite key is violated only when all its values are equal
+    assert!(matches!(run("INSERT INTO grades VALUES (1, 1, 5), (1, 2, 4), (2, 1, 3)"), Success(_)));
+    match run("INSERT INTO grades VALUES (2, 1, 5)") {
+        Error(reason) => assert_eq!(reason, r#"Duplicate value (2, 1) for key "grades_key" (PRIMARY KEY (student, course))"#),
+        other => panic!("Unexpected result {:?}", other)
+    };
+    match run("SELECT COUNT(*) FROM grades") {
+        Success(Some(results)) => assert_eq!(results, r#"[[{"col":"COUNT(*)","value":3}]]"#),
+        other => panic!("Unexpected result {:?}", other)
+    };
+
+    // Values of key are compared as values (1 and 1.00 are the same) and stored rows with the same values are found by index of key
+    assert!(matches!(run("CREATE TABLE prices (amount DECIMAL(10, 2) PRIMARY KEY); INSERT INTO prices VALUES (1), (2.5)"), Success(_)));
+    assert!(matches!(run("INSERT INTO prices VALUES (1.00)"), Error(_)));
+    assert!(matches!(run("INSERT INTO prices VALUES (3), (3.0)"), Error(_)));
+    let condition = sql_expr::parse_expression("amount = 2.50").unwrap();
+    assert_eq!(sql_index::candidate_locations(&sql_storage::table_path(&db_path, "prices"), "prices", &condition).map(|locations| locations.len()), Some(1));
+
+    std::fs::remove_dir_all(&data_path).unwrap();
+}
+
+#[test]
+fn test_process_query_foreign_keys() {
+    // Session connected to temporary database
+    let (data_path, _, mut sessions) = test_database(&["session"]);
+    let mut run = |query: &str| process_query(query, None, "session".to_string(), &mut sessions);
+    let rows = |outcome: Outcomes| match outcome {
+        Success(Some(results)) => results,
+        other => panic!("Unexpected result {:?}", other)
+    };
+
+    assert!(matches!(run("CREATE TABLE users (id INT PRIMARY KEY, name TEXT)"), Success(_)));
+    assert!(matches!(run("CREATE TABLE orders (id INT PRIMARY KEY, user_id INT REFERENCES users (id) ON DELETE CASCADE ON UPDATE CASCADE)"), Success(_)));
+    assert!(matches!(run("CREATE TABLE notes (id INT, user_id INT, CONSTRAINT notes_user FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE SET NULL)"), Success(_)));
+    assert!(matches!(run("CREATE TABLE items (order_id INT REFERENCES orders ON DELETE RESTRICT)"), Success(_)));
+
+    // Referenced table must exist and referenced columns must be its key
+    assert!(matches!(run("CREATE TABLE broken (user_id INT REFERENCES missing (id))"), Error(_)));
+    assert!(matches!(run("CREATE TABLE broken (name TEXT REFERENCES users (name))"), Error(_)));
+
+    // Inserted and updated rows must reffer to existing rows
+    assert!(matches!(run("INSERT INTO users VALUES (1, 'ann'), (2, 'bob'), (3, 'cid')"), Success(_)));
+    assert!(matches!(run("INSERT INTO orders VALUES (10, 1), (11, 2), (12, NULL)"), Success(_)));
+    assert!(matches!(run("INSERT INTO notes VALUES (1, 3), (2, 2)"), Success(_)));
+    match run("INSERT INTO orders VALUES (13, 4)") {
+        Error(reason) => assert_eq!(reason, r#"Value (4) of foreign key "orders_user_id_fkey" doesn't reffer to existing row of table "users""#),
+        other => panic!("Unexpected result {:?}", other)
+    };
+    assert!(matches!(run("UPDATE notes SET user_id = 5 WHERE id = 1"), Error(_)));
+
+    // Update of referenced key is cascaded and deletion sets NULL or deletes referencing rows
+    assert!(matches!(run("UPDATE users SET id = 5 WHERE id = 1"), Success(_)));
+    assert_eq!(rows(run("SELECT id, user_id FROM orders WHERE id = 10")), r#"[[{"col":"id","value":10},{"col":"user_id","value":5}]]"#);
+    match run("UPDATE users SET id = 6 WHERE id = 2") {
+        Error(reason) => assert!(reason.contains("notes_user")),
+        other => panic!("Unexpected result {:?}", other)
+    };
+    assert!(matches!(run("DELETE FROM users WHERE id = 2"), Success(_)));
+    assert_eq!(rows(run("SELECT id FROM orders ORDER BY id")), r#"[[{"col":"id","value":10}],[{"col":"id","value":12}]]"#);
+    assert_eq!(rows(run("SELECT id, user_id FROM notes WHERE id = 2")), r#"[[{"col":"id","value":2},{"col":"user_id","value":null}]]"#);
+
+    // Deletion is rejected by RESTRICT also when it is cascaded and then no row is deleted
+    assert!(matches!(run("INSERT INTO items VALUES (10)"), Success(_)));
+    match run("DELETE FROM users WHERE id = 5") {
+        Error(reason) => assert_eq!(reason, r#"Row of table "orders" can't be deleted because it is referenced by foreign key "items_order_id_fkey" of table "items""#),
+        other => panic!("Unexpected result {:?}", other)
+    };
+    assert_eq!(rows(run("SELECT COUNT(*) FROM users")), r#"[[{"col":"COUNT(*)","value":2}]]"#);
+    assert!(matches!(run("DROP TABLE orders"), Error(_)));
+
+    // Only inserted rows are checked (referenced rows are found by index of referenced key, also rows inserted by the same statement) and referenced key can't be removed
+    assert!(matches!(run("CREATE TABLE staff (id INT PRIMARY KEY, boss INT REFERENCES staff (id)); INSERT INTO staff VALUES (1, NULL), (2, 1), (3, 2)"), Success(_)));
+    match run("INSERT INTO staff VALUES (4, 3), (5, 7)") {
+        Error(reason) => assert_eq!(reason, r#"Value (7) of foreign key "staff_boss_fkey" doesn't reffer to existing row of table "staff""#),
+        other => panic!("Unexpected result {:?}", other)
+    };
+    assert!(matches!(run("ALTER TABLE users DROP CONSTRAINT users_pkey"), Error(_)));
+
+    std::fs::remove_dir_all(&data_path).unwrap();
+}
+
+#[test]
+fn test_process_query_defaults() {
+    // Session connected to temporary database
+    let (data_path, _, mut sessions) = test_database(&["session"]);
+    let mut run = |query: &str| process_query(query, None, "session".to_string(), &mut sessions);
+    let rows = |outcome: Outcomes| match outcome {
+        Success(Some(results)) => results,
+        other => panic!("Unexpected result {:?}", other)
+    };
+
+    assert!(matches!(run("CREATE TABLE tasks (id INT NOT NULL, title TEXT NOT NULL DEFAULT 'untitled', done BOOLEAN DEFAULT false, created TIMESTAMP DEFAULT CURRENT_TIMESTAMP)"), Success(_)));
+
+    // NULL can't be inserted or assigned to NOT NULL column
+    match run("INSERT INTO tasks VALUES (NULL, 'a', true, NULL)") {
+        Error(reason) => assert_eq!(reason, r#"Column "id" can't be NULL because of its NOT NULL constraint"#),
+        other => panic!("Unexpected result {:?}", other)
+    };
+    assert!(matches!(run("INSERT INTO tasks (title) VALUES ('b')"), Error(_)));
+
+    // Omitted columns and keyword DEFAULT get default values of columns
+    assert!(matches!(run("INSERT INTO tasks (id) VALUES (1)"), Success(_)));
+    assert!(matches!(run("INSERT INTO tasks VALUES (2, DEFAULT, true, DEFAULT)"), Success(_)));
+    assert_eq!(rows(run("SELECT id, title, done FROM tasks ORDER BY id")), r#"[[{"col":"id","value":1},{"col":"title","value":"untitled"},{"col":"done","value":false}],[{"col":"id","value":2},{"col":"title","value":"untitled"},{"col":"done","value":true}]]"#);
+    assert_eq!(rows(run("SELECT COUNT(*) FROM tasks WHERE created IS NOT NULL")), r#"[[{"col":"COUNT(*)","value":2}]]"#);
+
+    // UPDATE respects NOT NULL and accepts keyword DEFAULT too
+    assert!(matches!(run("UPDATE tasks SET title = NULL WHERE id = 1"), Error(_)));
+    assert!(matches!(run("UPDATE tasks SET title = 'write', done = true WHERE id = 1"), Success(_)));
+    assert!(matches!(run("UPDATE tasks SET title = DEFAULT, done = DEFAULT WHERE id = 1"), Success(_)));
+    assert_eq!(rows(run("SELECT title, done FROM tasks WHERE id = 1")), r#"[[{"col":"title","value":"untitled"},{"col":"done","value":false}]]"#);
+
+    std::fs::remove_dir_all(&data_path).unwrap();
+}
+
+#[test]
+fn test_process_query_checks() {
+    // Session connected to temporary database
+    let (data_path, _, mut sessions) = test_database(&["session"]);
+    let mut run = |query: &str| process_query(query, None, "session".to_string(), &mut sessions);
+    let error = |outcome: Outcomes| match outcome {
+        Error(reason) => reason,
+        other => panic!("Unexpected result {:?}", other)
+    };
+
+    assert!(matches!(run("CREATE TABLE products (id INT PRIMARY KEY, price INT CHECK (price >= 0), start_date DATE, end_date DATE, CONSTRAINT valid_period CHECK (start_date < end_date))"), Success(_)));
+    assert!(matches!(run("CREATE TABLE broken (id INT, CHECK (COUNT(id) > 0))"), Error(_)));
+
+    // Row can't make expression false, but NULL result doesn't violate constraint
+    assert_eq!(error(run("INSERT INTO products VALUES (1, -5, NULL, NULL)")), r#"Row violates check constraint "products_price_check" (CHECK (price >= 0))"#);
+    assert!(matches!(run("INSERT INTO products VALUES (1, 150, '2024-01-01', NULL), (2, 20, '2024-01-01', '2024-02-01')"), Success(_)));
+    assert_eq!(error(run("UPDATE products SET end_date = '2023-12-01' WHERE id = 1")), r#"Row violates check constraint "valid_period" (CHECK (start_date < end_date))"#);
+
+    // Added constraint must be fulfilled by existing rows
+    assert_eq!(error(run("ALTER TABLE products ADD CONSTRAINT cheap CHECK (price < 100)")), r#"Row violates check constraint "cheap" (CHECK (price < 100))"#);
+    assert!(matches!(run("ALTER TABLE products ADD CONSTRAINT limited CHECK (price < 1000)"), Success(_)));
+    assert!(matches!(run("INSERT INTO products VALUES (3, 5000, NULL, NULL)"), Error(_)));
+
+    // Expression follows renamed column
+    assert!(matches!(run("ALTER TABLE products RENAME COLUMN price TO cost"), Success(_)));
+    assert_eq!(error(run("UPDATE products SET cost = -1 WHERE id = 2")), r#"Row violates check constraint "products_price_check" (CHECK (cost >= 0))"#);
+    assert!(matches!(run("ALTER TABLE products DROP COLUMN cost"), Error(_)));
+
+    std::fs::remove_dir_all(&data_path).unwrap();
+}
+
+#[test]
+fn test_process_query_sequences() {
+    // Session connected to temporary database
+    let (data_path, db_path, mut sessions) = test_database(&["session"]);
+    let mut run = |query: &str| process_query(query, None, "session".to_string(), &mut sessions);
+    let rows = |outcome: Outcomes| match outcome {
+        Success(Some(results)) => results,
+        other => panic!("Unexpected result {:?}", other)
+    };
+
+    // Columns get next values when their values are omitted, NULL or DEFAULT. Counter follows values given in query
+    assert!(matches!(run("CREATE TABLE users (id INT AUTO_INCREMENT PRIMARY KEY, name TEXT)"), Success(_)));
+    assert!(matches!(run("INSERT INTO users (name) VALUES ('ann'), ('bob')"), Success(_)));
+    assert!(matches!(run("INSERT INTO users VALUES (10, 'cid'), (DEFAULT, 'dan'), (NULL, 'eve')"), Success(_)));
+    assert_eq!(rows(run("SELECT id FROM users ORDER BY id")), r#"[[{"col":"id","value":1}],[{"col":"id","value":2}],[{"col":"id","value":10}],[{"col":"id","value":11}],[{"col":"id","value":12}]]"#);
+
+    // SERIAL and identity columns are auto incremented too, but only integer column can be
+    assert!(matches!(run("CREATE TABLE orders (id SERIAL, note TEXT); CREATE TABLE items (id INT GENERATED ALWAYS AS IDENTITY, label TEXT)"), Success(_)));
+    assert!(matches!(run("INSERT INTO items (label) VALUES ('a'), ('b')"), Success(_)));
+    assert_eq!(rows(run("SELECT id FROM items WHERE label = 'b'")), r#"[[{"col":"id","value":2}]]"#);
+    assert!(matches!(run("CREATE TABLE broken (name TEXT AUTO_INCREMENT)"), Error(_)));
+
+    // Sequences are stored beside database directory
+    assert!(matches!(run("CREATE SEQUENCE invoice_seq"), Success(_)));
+    assert!(matches!(run("CREATE SEQUENCE invoice_seq"), Error(_)));
+    assert!(matches!(run("CREATE SEQUENCE IF NOT EXISTS invoice_seq"), Success(_)));
+    assert!(matches!(run("SELECT currval('invoice_seq')"), Error(_)));
+    assert_eq!(rows(run("SELECT nextval('invoice_seq') AS next")), r#"[[{"col":"next","value":1}]]"#);
+    assert!(matches!(run("INSERT INTO orders VALUES (nextval('invoice_seq'), 'first')"), Success(_)));
+    assert_eq!(rows(run("SELECT id FROM orders")), r#"[[{"col":"id","value":2}]]"#);
+    assert_eq!(rows(run("SELECT currval('invoice_seq') AS current")), r#"[[{"col":"current","value":2}]]"#);
+    let sequences_path = sql_sequences::sequences_path(&db_path);
+    assert!(std::fs::read_to_string(&sequences_path).unwrap().contains(r#""invoice_seq":{"last_value":2}"#));
+
+    assert!(matches!(run("DROP SEQUENCE invoice_seq"), Success(_)));
+    assert!(matches!(run("SELECT nextval('invoice_seq')"), Error(_)));
+
+    std::fs::remove_dir_all(&data_path).unwrap();
+}
+
+#[test]
+fn test_process_query_alter_constraints() {
+    // Session connected to temporary database
+    let (data_path, _, mut sessions) = test_database(&["session"]);
+    let mut run = |query: &str| process_query(query, None, "session".to_string(), &mut sessions);
+    let error = |outcome: Outcomes| match outcome {
+        Error(reason) => reason,
+        other => panic!("Unexpected result {:?}", other)
+    };
+
+    assert!(matches!(run("CREATE TABLE users (id INT PRIMARY KEY, email TEXT, nick TEXT); INSERT INTO users VALUES (1, 'a@x.pl', NULL), (2, 'a@x.pl', 'bob')"), Success(_)));
+    assert!(matches!(run("CREATE TABLE orders (user_id INT); INSERT INTO orders VALUES (3)"), Success(_)));
+
+    // Constraint is added only when existing rows fulfil it
+    assert_eq!(error(run("ALTER TABLE users ADD CONSTRAINT users_email_key UNIQUE (email)")), r#"Duplicate value (a@x.pl) for key "users_email_key" (UNIQUE (email))"#);
+    assert!(matches!(run("ALTER TABLE orders ADD CONSTRAINT orders_user FOREIGN KEY (user_id) REFERENCES users (id)"), Error(_)));
+    assert!(matches!(run("UPDATE users SET email = 'b@x.pl' WHERE id = 2; ALTER TABLE users ADD CONSTRAINT users_email_key UNIQUE (email)"), Success(_)));
+    assert!(matches!(run("INSERT INTO users VALUES (3, 'b@x.pl', 'cid')"), Error(_)));
+
+    // Named constraints and keys attached to columns can be dropped
+    assert!(matches!(run("ALTER TABLE users DROP CONSTRAINT users_email_key"), Success(_)));
+    assert!(matches!(run("ALTER TABLE users DROP CONSTRAINT users_email_key"), Error(_)));
+    assert!(matches!(run("ALTER TABLE users DROP CONSTRAINT IF EXISTS users_email_key"), Success(_)));
+    assert!(matches!(run("ALTER TABLE users DROP CONSTRAINT users_pkey"), Success(_)));
+    assert!(matches!(run("INSERT INTO users VALUES (2, 'b@x.pl', 'cid')"), Success(_)));
+
+    // NOT NULL and default value of column
+    assert_eq!(error(run("ALTER TABLE users ALTER COLUMN nick SET NOT NULL")), r#"Column "nick" can't be NULL because of its NOT NULL constraint"#);
+    assert!(matches!(run("UPDATE users SET nick = 'ann' WHERE id = 1; ALTER TABLE users ALTER COLUMN nick SET NOT NULL"), Success(_)));
+    assert!(matches!(run("INSERT INTO users VALUES (4, 'd@x.pl', NULL)"), Error(_)));
+    assert!(matches!(run("ALTER TABLE users ALTER COLUMN nick SET DEFAULT 'guest'; INSERT INTO users (id, email) VALUES (4, 'd@x.pl')"), Success(_)));
+    assert!(matches!(run("ALTER TABLE users ALTER COLUMN nick DROP DEFAULT; INSERT INTO users (id, email) VALUES (5, 'e@x.pl')"), Error(_)));
+    assert!(matches!(run("ALTER TABLE users ALTER COLUMN nick DROP NOT NULL; INSERT INTO users (id, email) VALUES (5, 'e@x.pl')"), Success(_)));
+    match run("SELECT id, nick FROM users WHERE id >= 4 ORDER BY id") {
+        Success(Some(results)) => assert_eq!(results, r#"[[{"col":"id","value":4},{"col":"nick","value":"guest"}],[{"col":"id","value":5},{"col":"nick","value":null}]]"#),
+        other => panic!("Unexpected result {:?}", other)
+    };
+
+    std::fs::remove_dir_all(&data_path).unwrap();
+}
+
+#[test]
+fn test_process_query_many_pages() {
+    // Session connected to temporary database
+    let (data_path, db_path, mut sessions) = test_database(&["session"]);
+    let mut run = |query: &str| process_query(query, None, "session".to_string(), &mut sessions);
+    let rows = |outcome: Outcomes| match outcome {
+        Success(Some(results)) => results,
+        other => panic!("Unexpected result {:?}", other)
+    };
+
+    // Tables span more pages than are kept changed in memory (last user has got the same email as first one)
+    let users = (1..=4000).map(|id| f!("({}, 'u{}@x.pl', {}, '{}')", id, if id < 4000 { id } else { 1 }, id % 100, "n".repeat(50))).collect::<Vec<_>>().join(", ");
+    let orders = (1..=4000).map(|id| f!("({}, {})", id, id)).collect::<Vec<_>>().join(", ");
+    assert!(matches!(run(&f!("CREATE TABLE users (id INT PRIMARY KEY, email TEXT, age INT, note TEXT); INSERT INTO users VALUES {}", users)), Success(_)));
+    assert!(matches!(run(&f!("CREATE TABLE orders (id INT PRIMARY KEY, user_id INT REFERENCES users (id) ON DELETE CASCADE ON UPDATE CASCADE); INSERT INTO orders VALUES {}", orders)), Success(_)));
+    let table_size = std::fs::metadata(sql_storage::table_path(&db_path, "users")).unwrap().len();
+    assert!(table_size > (sql_storage::CHANGED_PAGES_LIMIT * sql_storage::PAGE_SIZE) as u64);
+
+    // Rows are changed by ALTER TABLE and checked against new constraints page after page
+    match run("ALTER TABLE users ADD CONSTRAINT users_email_key UNIQUE (email)") {
+        Error(reason) => assert_eq!(reason, r#"Duplicate value (u1@x.pl) for key "users_email_key" (UNIQUE (email))"#),
+        other => panic!("Unexpected result {:?}", other)
+    };
+    assert!(matches!(run("ALTER TABLE users RENAME COLUMN note TO remark"), Success(_)));
+    assert!(matches!(run("ALTER TABLE users ALTER COLUMN age SET DATA TYPE DECIMAL(2, 1)"), Error(_)));
+    assert!(matches!(run("ALTER TABLE users ALTER COLUMN age SET DATA TYPE DECIMAL(3, 1)"), Success(_)));
+    assert!(matches!(run("ALTER TABLE orders ADD CONSTRAINT orders_positive CHECK (id > 0)"), Success(_)));
+
+    // Indexes are built from all rows and rows are read by DataFusion in batches
+    assert!(matches!(run("CREATE UNIQUE INDEX users_email ON users (email)"), Error(_)));
+    assert!(matches!(run("CREATE INDEX users_remark ON users (remark)"), Success(_)));
+    assert_eq!(rows(run("WITH named AS (SELECT id, age, remark FROM users) SELECT COUNT(*) AS count FROM named WHERE remark LIKE 'n%' AND age > 98.5")), r#"[[{"col":"count","value":40}]]"#);
+
+    // Actions of foreign keys are performed for rows read from referencing table page after page
+    assert!(matches!(run("UPDATE users SET id = id + 10000 WHERE id > 3000"), Success(_)));
+    assert_eq!(rows(run("SELECT COUNT(*) FROM orders WHERE user_id > 10000")), r#"[[{"col":"COUNT(*)","value":1000}]]"#);
+    assert!(matches!(run("DELETE FROM users WHERE id <= 1000"), Success(_)));
+    assert_eq!(rows(run("SELECT COUNT(*) FROM orders")), r#"[[{"col":"COUNT(*)","value":3000}]]"#);
+
+    // Truncated rows are removed page after page and their referencing rows are deleted too
+    assert!(matches!(run("TRUNCATE TABLE users"), Success(_)));
+    assert_eq!(rows(run("SELECT COUNT(*) FROM users")), r#"[[{"col":"COUNT(*)","value":0}]]"#);
+    assert_eq!(rows(run("SELECT COUNT(*) FROM orders")), r#"[[{"col":"COUNT(*)","value":0}]]"#);
+
+    std::fs::remove_dir_all(&data_path).unwrap();
+}
+
+#[test]
+fn test_process_query_decimals() {
+    // Session connected to temporary database
+    let (data_path, _, mut sessions) = test_database(&["session"]);
+    let mut run = |query: &str| process_query(query, None, "session".to_string(), &mut sessions);
+
+    // Values are rounded to scale of column and values with more digits than precision are rejected
+    assert!(matches!(run("CREATE TABLE accounts (id INT, balance DECIMAL(10, 2)); INSERT INTO accounts VALUES (1, 20.205), (2, 10.1)"), Success(_)));
+    assert!(matches!(run("INSERT INTO accounts VALUES (3, 123456789.00)"), Error(_)));
+    assert!(matches!(run("UPDATE accounts SET balance = 100000000 WHERE id = 2"), Error(_)));
+
+    // Assigned expressions are computed for values of row before update
+    assert!(matches!(run("UPDATE accounts SET balance = balance * 2, id = id + 10 WHERE id = 2; UPDATE accounts SET balance = balance - 10.1, id = id - 10 WHERE balance = 20.2"), Success(_)));
+    assert!(matches!(run("UPDATE accounts SET balance = balance + name WHERE id = 2"), Error(_)));
+
+    // Arithmetic is exact and decimals are returned with all digits after decimal point
+    match run("SELECT SUM(balance), MAX(balance) - 0.1 FROM accounts") {
+        Success(Some(results)) => assert_eq!(results, r#"[[{"col":"SUM(balance)","value":"30.31"},{"col":"MAX(balance) - 0.1","value":"20.11"}]]"#),
+        other => panic!("Unexpected result {:?}", other)
+    };
+    match run("SELECT id FROM accounts WHERE balance > 10.1 AND balance = '20.21'") {
+        Success(Some(results)) => assert_eq!(results, r#"[[{"col":"id","value":1}]]"#),
+        other => panic!("Unexpected result {:?}", other)
+    };
+
+    // Column type is changed only when all values fit into new type
+    assert!(matches!(run("ALTER TABLE accounts ALTER COLUMN balance SET DATA TYPE DECIMAL(2, 1)"), Error(_)));
+    assert!(matches!(run("ALTER TABLE accounts ALTER COLUMN balance SET DATA TYPE DECIMAL(3, 1)"), Success(_)));
+    match run("SELECT balance FROM accounts ORDER BY id") {
+        Success(Some(results)) => assert_eq!(results, r#"[[{"col":"balance","value":"20.2"}],[{"col":"balance","value":"10.1"}]]"#),
+        other => panic!("Unexpected result {:?}", other)
+    };
+
+    std::fs::remove_dir_all(&data_path).unwrap();
+}
+
+#[test]
+fn test_process_query_indexes() {
+    // Session connected to temporary database
+    let (data_path, db_path, mut sessions) = test_database(&["session"]);
+    let mut run = |query: &str| process_query(query, None, "session".to_string(), &mut sessions);
+
+    let values = (1..=300).map(|id| f!("({}, 'user{}', {})", id, id % 7, id % 50)).collect::<Vec<_>>().join(", ");
+    assert!(matches!(run(&f!("CREATE TABLE people (id INT, name TEXT, age INT); INSERT INTO people VALUES {}", values)), Success(_)));
+    assert!(matches!(run("CREATE UNIQUE INDEX people_id ON people (id); CREATE INDEX people_name_age ON people (name, age)"), Success(_)));
+    assert!(matches!(run("CREATE INDEX people_id ON people (age)"), Error(_)));
+
+    // Index is used for equality of leading columns and range of next column
+    let table_path = sql_storage::table_path(&db_path, "people");
+    let condition = sql_expr::parse_expression("name = 'user3' AND age BETWEEN 10 AND 20 AND id > 0").unwrap();
+    assert_eq!(sql_index::candidate_locations(&table_path, "people", &condition).map(|locations| locations.len()), Some(9));
+    match run("SELECT id FROM people WHERE name = 'user3' AND age BETWEEN 10 AND 20 AND id > 0") {
+        Success(Some(results)) => assert_eq!(results, r#"[[{"col":"id","value":10}],[{"col":"id","value":17}],[{"col":"id","value":66}],[{"col":"id","value":115}],[{"col":"id","value":164}],[{"col":"id","value":213}],[{"col":"id","value":220}],[{"col":"id","value":262}],[{"col":"id","value":269}]]"#),
+        other => panic!("Unexpected result {:?}", other)
+    };
+
+    // Indexes are maintained by UPDATE and DELETE and unique index rejects duplicated values
+    assert!(matches!(run("UPDATE people SET age = 99 WHERE id = 17"), Success(_)));
+    assert!(matches!(run("DELETE FROM people WHERE id >= 250"), Success(_)));
+    assert!(matches!(run("INSERT INTO people VALUES (5, 'copy', 1)"), Error(_)));
+    assert!(matches!(run("UPDATE people SET id = 1 WHERE id = 2"), Error(_)));
+    match run("SELECT id FROM people WHERE name = 'user3' AND age = 99 OR id = 262") {
+        Success(Some(results)) => assert_eq!(results, r#"[[{"col":"id","value":17}]]"#),
+        other => panic!("Unexpected result {:?}", other)
+    };
+    match run("SELECT COUNT(*) FROM people WHERE id > 240") {
+        Success(Some(results)) => assert_eq!(results, r#"[[{"col":"COUNT(*)","value":9}]]"#),
+        other => panic!("Unexpected result {:?}", other)
+    };
+
+    // Indexes are used also for aliased tables of join (rows without pair are still joined with NULL)
+    assert!(sql_index::candidate_locations(&table_path, "p", &sql_expr::parse_expression("p.id > 235").unwrap()).is_some());
+    match run("SELECT COUNT(*), COUNT(q.id) FROM people p LEFT JOIN people q ON q.id = p.id + 10 WHERE p.id > 235 AND q.age < 100") {
+        Success(Some(results)) => assert_eq!(results, r#"[[{"col":"COUNT(*)","value":4},{"col":"COUNT(q.id)","value":4}]]"#),
+        other => panic!("Unexpected result {:?}", other)
+    };
+    match run("SELECT COUNT(*), COUNT(q.id) FROM people p LEFT JOIN people q ON q.id = p.id + 10 WHERE p.id > 235") {
+        Success(Some(results)) => assert_eq!(results, r#"[[{"col":"COUNT(*)","value":14},{"col":"COUNT(q.id)","value":4}]]"#),
+        other => panic!("Unexpected result {:?}", other)
+    };
+
+    // Indexes follow renamed column and are built again after change of column type
+    assert!(matches!(run("ALTER TABLE people RENAME COLUMN id TO pid"), Success(_)));
+    assert!(matches!(run("INSERT INTO people VALUES (5, 'copy', 1)"), Error(_)));
+    assert!(matches!(run("ALTER TABLE people ALTER COLUMN age SET DATA TYPE DECIMAL(5, 1)"), Success(_)));
+    match run("SELECT pid FROM people WHERE name = 'user3' AND age = 99.0") {
+        Success(Some(results)) => assert_eq!(results, r#"[[{"col":"pid","value":17}]]"#),
+        other => panic!("Unexpected result {:?}", other)
+    };
+
+    // Indexes follow renamed table and are removed with it
+    assert!(matches!(run("DROP INDEX people_name_age"), Success(_)));
+    assert!(matches!(run("ALTER TABLE people RENAME TO persons"), Success(_)));
+    assert!(matches!(run("INSERT INTO persons VALUES (1, 'copy', 1)"), Error(_)));
+    assert!(matches!(run("DROP TABLE persons"), Success(_)));
+    assert!(std::fs::read_dir(&db_path).unwrap().next().is_none());
+
+    std::fs::remove_dir_all(&data_path).unwrap();
+}
+
+#[test]
+fn test_process_query_transactions() {
+    // Two sessions connected to temporary database
+    let (data_path, _, mut sessions) = test_database(&["first", "second"]);
+    let mut run = |session: &str, query: &str| process_query(query, None, session.to_string(), &mut sessions);
+    let results = |outcome: Outcomes| match outcome {
+        Success(Some(results)) => results,
+        other => panic!("Unexpected result {:?}", other)
+    };
+
+    assert!(matches!(run("first", "CREATE TABLE accounts (id INT PRIMARY KEY, balance INT); INSERT INTO accounts VALUES (1, 100), (2, 50)"), Success(_)));
+
+    // Changes are kept until commit: another session sees committed rows and can't change rows changed by transaction
+    assert!(matches!(run("first", "BEGIN; UPDATE accounts SET balance = 70 WHERE id = 1; SAVEPOINT transfer; UPDATE accounts SET balance = 80 WHERE id = 2"), Success(_)));
+    assert_eq!(results(run("second", "SELECT balance FROM accounts")), r#"[[{"col":"balance","value":100}],[{"col":"balance","value":50}]]"#);
+    assert!(matches!(run("second", "UPDATE accounts SET balance = 0 WHERE id = 2"), Error(reason) if reason == "Row has been changed by transaction of another session"));
+    assert!(matches!(run("first", "ROLLBACK TO SAVEPOINT transfer"), Success(_)));
+    assert_eq!(results(run("first", "SELECT balance FROM accounts ORDER BY id")), r#"[[{"col":"balance","value":70}],[{"col":"balance","value":50}]]"#);
+    assert!(matches!(run("first", "COMMIT"), Success(_)));
+    assert_eq!(results(run("second", "SELECT balance FROM accounts ORDER BY id")), r#"[[{"col":"balance","value":70}],[{"col":"balance","value":50}]]"#);
+
+    // Failed statement is reverted alone, rollback discards whole transaction
+    assert!(matches!(run("second", "BEGIN; INSERT INTO accounts VALUES (3, 10); DELETE FROM accounts WHERE id = 1"), Success(_)));
+    assert!(matches!(run("second", "INSERT INTO accounts VALUES (4, 0), (3, 0)"), Error(_)));
+    assert_eq!(results(run("second", "SELECT id FROM accounts ORDER BY id")), r#"[[{"col":"id","value":2}],[{"col":"id","value":3}]]"#);
+    assert!(matches!(run("second", "SAVEPOINT first; RELEASE SAVEPOINT first"), Success(_)));
+    assert!(matches!(run("second", "ROLLBACK TO first"), Error(_)));
+    assert!(matches!(run("second", "BEGIN"), Error(_)));
+    assert!(matches!(run("second", "ROLLBACK"), Success(_)));
+    assert!(matches!(run("second", "ROLLBACK"), Error(_)));
+    assert_eq!(results(run("first", "SELECT id FROM accounts ORDER BY id")), r#"[[{"col":"id","value":1}],[{"col":"id","value":2}]]"#);
+
+    // Versions of many finished transactions are resolved by checkpoint, so commit log keeps only ids of later transactions
+    for balance in 0..300 {
+        assert!(matches!(run("second", &f!("UPDATE accounts SET balance = {} WHERE id = 2", balance)), Success(_)));
+        if balance % 10 == 0 {
+            assert!(matches!(run("first", "BEGIN; UPDATE accounts SET balance = 0 WHERE id = 1; ROLLBACK"), Success(_)));
+        };
+    }
+    let commit_log = std::fs::metadata(data_path.join("wastledb.clog")).map_or(0, |metadata| metadata.len());
+    assert!(commit_log < 100 * 8, "Commit log hasn't been compacted ({} bytes)", commit_log);
+    assert_eq!(results(run("first", "SELECT balance FROM accounts ORDER BY id")), r#"[[{"col":"balance","value":70}],[{"col":"balance","value":299}]]"#);
+
+    // Transaction of expired session is rolled back
+    assert!(matches!(run("first", "START TRANSACTION; DROP TABLE accounts"), Success(_)));
+    let session_data = serde_json::from_str::<SessionData>(sessions.get("first").unwrap()).unwrap();
+    sql_transactions::abort(session_data.transaction.as_ref().unwrap()).unwrap();
+    sessions.remove("first");
+    let mut run = |session: &str, query: &str| process_query(query, None, session.to_string(), &mut sessions);
+    assert!(matches!(run("second", "SELECT COUNT(*) FROM accounts"), Success(Some(results)) if results == r#"[[{"col":"COUNT(*)","value":2}]]"#));
+
+    std::fs::remove_dir_all(&data_path).unwrap();
+}
+
+#[test]
+fn test_process_query_isolation_levels() {
+    // Three sessions connected to temporary database
+    let (data_path, _, mut sessions) = test_database(&["first", "second", "third"]);
+    let mut run = |session: &str, query: &str| process_query(query, None, session.to_string(), &mut sessions);
+    let conflict = |outcome: Outcomes| matches!(outcome, Error(reason) if reason == "Row has been changed by transaction of another session");
+
+    assert!(matches!(run("first", "CREATE TABLE items (id INT PRIMARY KEY, amount INT); INSERT INTO items VALUES (1, 10), (2, 20)"), Success(_)));
+
+    // Repeatable read transaction keeps snapshot of its first statement, read committed transaction sees changes committed before each statement
+    assert!(matches!(run("first", "BEGIN ISOLATION LEVEL REPEATABLE READ"), Success(_)));
+    assert!(matches!(run("first", "SELECT amount FROM items WHERE id = 1"), Success(Some(results)) if results == r#"[[{"col":"amount","value":10}]]"#));
+    assert!(matches!(run("second", "BEGIN; SELECT amount FROM items WHERE id = 1"), Success(_)));
+    assert!(matches!(run("third", "UPDATE items SET amount = 11 WHERE id = 1"), Success(_)));
+    assert!(matches!(run("first", "SELECT amount FROM items WHERE id = 1"), Success(Some(results)) if results == r#"[[{"col":"amount","value":10}]]"#));
+    assert!(matches!(run("second", "SELECT amount FROM items WHERE id = 1"), Success(Some(results)) if results == r#"[[{"col":"amount","value":11}]]"#));
+
+    // Row changed after snapshot or by transaction in progress can't be changed (write-write conflict)
+    assert!(conflict(run("first", "UPDATE items SET amount = 12 WHERE id = 1")));
+    assert!(matches!(run("first", "ROLLBACK"), Success(_)));
+    assert!(matches!(run("second", "UPDATE items SET amount = 21 WHERE id = 2"), Success(_)));
+    assert!(conflict(run("third", "DELETE FROM items WHERE id = 2")));
+    assert!(matches!(run("third", "SELECT amount FROM items WHERE id = 2"), Success(Some(results)) if results == r#"[[{"col":"amount","value":20}]]"#));
+    assert!(matches!(run("second", "COMMIT"), Success(_)));
+    assert!(matches!(run("third", "UPDATE items SET amount = 22 WHERE id = 2"), Success(_)));
+
+    // Isolation level is set before first statement of transaction (or for all next transactions of session, also when it is set outside of transaction)
+    assert!(matches!(run("first", "SET TRANSACTION ISOLATION LEVEL SERIALIZABLE; BEGIN; SELECT COUNT(*) FROM items"), Success(_)));
+    assert!(matches!(run("third", "SET SESSION CHARACTERISTICS AS TRANSACTION ISOLATION LEVEL READ COMMITTED"), Success(_)));
+    assert!(matches!(run("first", "SET TRANSACTION ISOLATION LEVEL READ COMMITTED"), Error(_)));
+    assert!(matches!(run("second", "BEGIN; SET TRANSACTION ISOLATION LEVEL SERIALIZABLE; SELECT COUNT(*) FROM items"), Success(_)));
+
+    // Serializable transaction which read table changed by transaction committed in the meantime is rolled back on commit
+    assert!(matches!(run("third", "INSERT INTO items VALUES (3, 30)"), Success(_)));
+    assert!(matches!(run("first", "INSERT INTO items VALUES (4, 40); COMMIT"), Error(_)));
+    assert!(matches!(run("second", "COMMIT"), Success(_)));
+    assert!(matches!(run("third", "SELECT id, amount FROM items ORDER BY id"), Success(Some(results)) if results == r#"[[{"col":"id","value":1},{"col":"amount","value":11}],[{"col":"id","value":2},{"col":"amount","value":22}],[{"col":"id","value":3},{"col":"amount","value":30}]]"#));
+
+    // Query computed by DataFusion sees rows like statement of transaction (also tables created by transaction)
+    assert!(matches!(run("first", "BEGIN; CREATE TABLE notes (id INT); INSERT INTO notes VALUES (1); DELETE FROM items WHERE id = 3"), Success(_)));
+    let ranked = |run: &mut dyn FnMut(&str, &str) -> Outcomes, session: &str, table: &str| match run(session, &f!("SELECT id, RANK() OVER (ORDER BY id) AS position FROM {} ORDER BY id", table)) {
+        Success(Some(results)) => results,
+        other => panic!("Unexpected result {:?}", other)
+    };
+    assert_eq!(ranked(&mut run, "first", "notes"), r#"[[{"col":"id","value":1},{"col":"position","value":1}]]"#);
+    assert_eq!(ranked(&mut run, "first", "items"), r#"[[{"col":"id","value":1},{"col":"position","value":1}],[{"col":"id","value":2},{"col":"position","value":2}]]"#);
+    assert_eq!(ranked(&mut run, "second", "items"), r#"[[{"col":"id","value":1},{"col":"position","value":1}],[{"col":"id","value":2},{"col":"position","value":2}],[{"col":"id","value":3},{"col":"position","value":3}]]"#);
+    assert!(matches!(run("first", "ROLLBACK"), Success(_)));
+
+    // Keys are checked also against rows of transactions in progress (not seen by statement)
+    assert!(matches!(run("first", "BEGIN; INSERT INTO items VALUES (5, 50)"), Success(_)));
+    assert!(conflict(run("second", "INSERT INTO items VALUES (5, 55)")));
+    assert!(matches!(run("first", "COMMIT"), Success(_)));
+    assert!(matches!(run("second", "INSERT INTO items VALUES (5, 55)"), Error(reason) if reason.contains("PRIMARY KEY")));
+    assert!(matches!(run("first", "BEGIN; DELETE FROM items WHERE id = 5"), Success(_)));
+    assert!(conflict(run("second", "INSERT INTO items VALUES (5, 55)")));
+    assert!(matches!(run("first", "ROLLBACK"), Success(_)));
+
+    // Foreign keys can't rely on rows created or removed by transaction in progress
+    assert!(matches!(run("first", "CREATE TABLE parents (id INT PRIMARY KEY); CREATE TABLE children (id INT, parent_id INT REFERENCES parents (id)); INSERT INTO parents VALUES (1), (2)"), Success(_)));
+    assert!(matches!(run("first", "BEGIN; DELETE FROM parents WHERE id = 1"), Success(_)));
+    assert!(conflict(run("second", "INSERT INTO children VALUES (1, 1)")));
+    assert!(matches!(run("first", "ROLLBACK"), Success(_)));
+    assert!(matches!(run("first", "BEGIN; INSERT INTO children VALUES (2, 2)"), Success(_)));
+    assert!(conflict(run("second", "DELETE FROM parents WHERE id = 2")));
+    assert!(matches!(run("first", "COMMIT"), Success(_)));
+    assert!(matches!(run("second", "INSERT INTO children VALUES (1, 1); DELETE FROM parents WHERE id = 2"), Error(reason) if !reason.contains("transaction")));
+    assert!(matches!(run("second", "SELECT COUNT(*) FROM children"), Success(Some(results)) if results == r#"[[{"col":"COUNT(*)","value":2}]]"#));
+
+    std::fs::remove_dir_all(&data_path).unwrap();
+}
@@ -0,0 +1,357 @@
+/* Run read-only queries with DataFusion planner and executor. Tables of database (table files with rows pages) are exposed to DataFusion by "JsonSQLTableProvider" */
+use std::{any::Any, fs, io, path::{Path, PathBuf}, collections::BTreeSet, sync::{Arc, Mutex}};
+
+use async_trait::async_trait;
+use chrono::Datelike;
+use datafusion::{
+    arrow::{
+        array::{ArrayRef, BooleanArray, Date32Array, Decimal128Array, Float64Array, Int64Array, StringArray, TimestampMicrosecondArray},
+        datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit},
+        error::ArrowError,
+        record_batch::{RecordBatch, RecordBatchOptions},
+        util::display::array_value_to_string,
+    },
+    datasource::{TableProvider, TableType},
+    error::{DataFusionError, Result as DataFusionResult},
+    execution::context::{SessionState, TaskContext},
+    logical_plan::Expr,
+    physical_plan::{expressions::PhysicalSortExpr, memory::MemoryExec, project_schema, stream::RecordBatchStreamAdapter, ExecutionPlan, Partitioning, SendableRecordBatchStream, Statistics},
+    prelude::SessionContext,
+};
+
+use super::sql_json::{JsonSQLTableColumn, JsonSQLTableColumnRow, SupportedSQLDataTypes};
+use super::sql_expr::{RowValue, DECIMAL_MAX_PRECISION, value_text};
+use super::{sql_storage, sql_transactions, sql_wal};
+
+/// Count of days from 1st January of year 1 to 1st January of 1970 (arrow dates are counted from the latter)
+const UNIX_EPOCH_DAYS_FROM_CE: i32 = 719_163;
+
+/// Convert column data type to type of arrow array in which column values are stored. Types without arrow equivalent are stored as text
+fn arrow_type(d_type: &SupportedSQLDataTypes) -> DataType {
+    match d_type {
+        SupportedSQLDataTypes::INT => DataType::Int64,
+        SupportedSQLDataTypes::FLOAT => DataType::Float64,
+        SupportedSQLDataTypes::DECIMAL(precision, scale) => DataType::Decimal128((*precision).clamp(1, DECIMAL_MAX_PRECISION), *scale),
+        SupportedSQLDataTypes::BOOLEAN => DataType::Boolean,
+        SupportedSQLDataTypes::DATE => DataType::Date32,
+        SupportedSQLDataTypes::DATETIMESTAMP => DataType::Timestamp(TimeUnit::Microsecond, None),
+        _ => DataType::Utf8
+    }
+}
+
+/// Convert type of arrow array from query results to column data type
+fn column_type(d_type: &DataType) -> SupportedSQLDataTypes {
+    match d_type {
+        DataType::Int8 | DataType::Int16 | DataType::Int32 | DataType::Int64 | DataType::UInt8 | DataType::UInt16 | DataType::UInt32 | DataType::UInt64 => SupportedSQLDataTypes::INT,
+        DataType::Float16 | DataType::Float32 | DataType::Float64 => SupportedSQLDataTypes::FLOAT,
+        DataType::Decimal128(precision, scale) => SupportedSQLDataTypes::DECIMAL(*precision, *scale),
+        DataType::Boolean => SupportedSQLDataTypes::BOOLEAN,
+        DataType::Date32 | DataType::Date64 => SupportedSQLDataTypes::DATE,
+        DataType::Timestamp(_, _) => SupportedSQLDataTypes::DATETIMESTAMP,
+        DataType::Null => SupportedSQLDataTypes::NULL,
+        _ => SupportedSQLDataTypes::TEXT
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Statement for which query is computed: versions of rows seen by it and files which it can read. It is captured on thread which performs statement, because query is computed on separate thread
+pub struct QueryStatement {
+    visibility: sql_transactions::Visibility,
+    access: sql_wal::Access,
+    /// tables read by query (remembered by statement after query)
+    read: Arc<Mutex<BTreeSet<PathBuf>>>,
+}
+
+impl QueryStatement {
+    /// Capture statement performed by thread
+    pub fn capture() -> QueryStatement {
+        QueryStatement { visibility: sql_transactions::visibility(), access: sql_wal::access(), read: Arc::default() }
+    }
+
+    /// Rows of table seen by statement (table is remembered as read by query)
+    fn table_rows(&self, path: &Path) -> io::Result<sql_storage::TableRows> {
+        let rows = sql_storage::table_rows_as(path, self.visibility.clone(), self.access)?;
+        self.read.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).insert(path.to_path_buf());
+        Ok(rows)
+    }
+
+    /// Remember tables read by query in statement performed by thread
+    fn record_reads(&self) {
+        for path in self.read.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).iter() {
+            sql_transactions::record_read(path);
+        }
+    }
+}
+
+#[derive(Debug)]
+/// DataFusion table backed by table file from database. Rows are read from file durning each scan so query always sees current table content (versions of rows seen by statement for which query is computed)
+pub struct JsonSQLTableProvider {
+    /// path to table file
+    path: PathBuf,
+    /// table columns converted to arrow schema
+    schema: SchemaRef,
+    statement: QueryStatement,
+}
+
+impl JsonSQLTableProvider {
+    /// Create provider for table file read for statement. Schema is taken from table columns
+    pub fn try_new(path: &Path, statement: QueryStatement) -> DataFusionResult<Self> {
+        let table = sql_storage::table_rows_as(path, statement.visibility.clone(), statement.access)?;
+        let fields = table.definition().columns
+            .iter()
+            .map(|column| Field::new(&column.name, arrow_type(&column.d_type), true))
+            .collect::<Vec<_>>();
+
+        Ok(Self {
+            path: path.to_owned(),
+            schema: Arc::new(Schema::new(fields)),
+            statement,
+        })
+    }
+}
+
+/// Convert table rows to record batch with columns of schema. Values of other type than column (e.g.: text stored before column type was checked) are treated as NULL,
+/// but values of column type which don't fit into arrow array (e.g.: INT value outside of 64-bit range) are reported as error
+fn record_batch(schema: &SchemaRef, rows: &[Vec<JsonSQLTableColumnRow>]) -> DataFusionResult<RecordBatch> {
+    let cell = |row: &Vec<JsonSQLTableColumnRow>, name: &String| row.iter().find(|cell| &cell.col == name).map_or(RowValue::Null, |cell| cell.value.clone());
+    let out_of_range = |name: &String, value: &dyn std::fmt::Display| DataFusionError::Execution(format!("Value {} of column \"{}\" is out of range of DataFusion type", value, name));
+
+    let mut arrays = vec![] as Vec<ArrayRef>;
+    for field in schema.fields() {
+        let values = rows.iter().map(|row| cell(row, field.name()));
+        let array: ArrayRef = match field.data_type() {
+            DataType::Int64 => Arc::new(values.map(|value| match value {
+                RowValue::Int(int) => i64::try_from(int).map(Some).map_err(|_| out_of_range(field.name(), &int)),
+                _ => Ok(None)
+            }).collect::<DataFusionResult<Int64Array>>()?),
+            DataType::Float64 => Arc::new(values.map(|value| match value {
+                RowValue::Float(float) => Some(float),
+                RowValue::Int(int) => Some(int as f64),
+                _ => None
+            }).collect::<Float64Array>()),
+            DataType::Decimal128(precision, scale) => Arc::new(values.map(|value| match value.of_type(&SupportedSQLDataTypes::DECIMAL(*precision, *scale)) {
+                RowValue::Decimal(digits, digits_scale) if digits_scale == *scale as u32 => Some(digits),
+                _ => None
+            }).collect::<Decimal128Array>().with_precision_and_scale(*precision, *scale)?),
+            DataType::Boolean => Arc::new(values.map(|value| match value {
+                RowValue::Boolean(boolval) => Some(boolval),
+                _ => None
+            }).collect::<BooleanArray>()),
+            DataType::Date32 => Arc::new(values.map(|value| match value {
+                RowValue::Date(date) => Some(date.num_days_from_ce() - UNIX_EPOCH_DAYS_FROM_CE),
+                _ => None
+            }).collect::<Date32Array>()),
+            DataType::Timestamp(TimeUnit::Microsecond, None) => Arc::new(values.map(|value| match value {
+                RowValue::Timestamp(timestamp) => Some(timestamp.timestamp_micros()),
+                _ => None
+            }).collect::<TimestampMicrosecondArray>()),
+            _ => Arc::new(values.map(value_text).collect::<StringArray>())
+        };
+        arrays.push(array);
+    }
+
+    Ok(RecordBatch::try_new_with_options(schema.clone(), arrays, &RecordBatchOptions::new().with_row_count(Some(rows.len())))?)
+}
+
+#[derive(Debug)]
+/// Scan of table file. Rows seen by statement are read page after page durning execution and converted to record batches of "sql_storage::ROWS_BATCH" rows, so whole table isn't kept in memory
+struct TableScanExec {
+    path: PathBuf,
+    /// schema of scanned columns (projection of table schema)
+    schema: SchemaRef,
+    statement: QueryStatement,
+}
+
+impl ExecutionPlan for TableScanExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(1)
+    }
+
+    fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
+        None
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![]
+    }
+
+    fn with_new_children(self: Arc<Self>, _: Vec<Arc<dyn ExecutionPlan>>) -> DataFusionResult<Arc<dyn ExecutionPlan>> {
+        Ok(self)
+    }
+
+    fn execute(&self, _partition: usize, _context: Arc<TaskContext>) -> DataFusionResult<SendableRecordBatchStream> {
+        let mut rows = self.statement.table_rows(&self.path)?;
+        let schema = self.schema.clone();
+        let batches = std::iter::from_fn(move || match rows.next_rows(sql_storage::ROWS_BATCH) {
+            Ok(rows) if rows.is_empty() => None,
+            Ok(rows) => Some(record_batch(&schema, &rows).map_err(|err| ArrowError::ExternalError(Box::new(err)))),
+            Err(err) => Some(Err(ArrowError::from(err)))
+        });
+
+        Ok(Box::pin(RecordBatchStreamAdapter::new(self.schema.clone(), futures::stream::iter(batches))))
+    }
+
+    fn statistics(&self) -> Statistics {
+        Statistics::default()
+    }
+}
+
+#[async_trait]
+impl TableProvider for JsonSQLTableProvider {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    async fn scan(&self, _ctx: &SessionState, projection: &Option<Vec<usize>>, _filters: &[Expr], _limit: Option<usize>) -> DataFusionResult<Arc<dyn ExecutionPlan>> {
+        // Table without columns is scanned as table without rows
+        if self.schema.fields().is_empty() {
+            return Ok(Arc::new(MemoryExec::try_new(&[], self.schema.clone(), None)?));
+        };
+
+        Ok(Arc::new(TableScanExec { path: self.path.clone(), schema: project_schema(&self.schema, projection.as_ref())?, statement: self.statement.clone() }))
+    }
+}
+
+/// Create DataFusion context with all tables from database registered under their names. Tables which can't be read by statement because they're changed by another statement in progress (e.g: created by transaction of another session) aren't registered
+pub fn database_context(database: &Path, statement: &QueryStatement) -> DataFusionResult<SessionContext> {
+    let context = SessionContext::new();
+
+    for entry in fs::read_dir(database)? {
+        let path = entry?.path();
+
+        if path.extension().is_some_and(|extension| extension == sql_storage::TABLE_EXTENSION) {
+            if let Some(name) = path.file_stem().and_then(|name| name.to_str()) {
+                match JsonSQLTableProvider::try_new(&path, statement.clone()) {
+                    Ok(provider) => context.register_table(name, Arc::new(provider))?,
+                    Err(DataFusionError::IoError(err)) if err.kind() == io::ErrorKind::WouldBlock => None,
+                    Err(err) => return Err(err)
+                };
+            }
+        }
+    }
+
+    Ok(context)
+}
+
+/// Run query over database tables for statement and return results in the same form as results from "sql_select::select"
+pub async fn select(database: &Path, sql: &str, statement: &QueryStatement) -> DataFusionResult<(Vec<JsonSQLTableColumn>, Vec<Vec<JsonSQLTableColumnRow>>)> {
+    let context = database_context(database, statement)?;
+    let data_frame = context.sql(sql).await?;
+    let batches = data_frame.collect().await?;
+
+    let columns = data_frame.schema()
+        .fields()
+        .iter()
+        .map(|field| JsonSQLTableColumn {
+            name: field.name().clone(),
+            d_type: column_type(field.data_type()),
+            constraints: None,
+        })
+        .collect::<Vec<_>>();
+
+    let mut rows = vec![];
+    for batch in batches {
+        for row in 0..batch.num_rows() {
+            let mut cells = vec![];
+            for (column, array) in columns.iter().zip(batch.columns()) {
+                let value = if array.is_null(row) {
+                    RowValue::Null
+                }
+                else {
+                    RowValue::Text(array_value_to_string(array, row)?).of_type(&column.d_type)
+                };
+                cells.push(JsonSQLTableColumnRow { col: column.name.clone(), value });
+            }
+            rows.push(cells);
+        }
+    }
+
+    Ok((columns, rows))
+}
+
+/// Run "select" from synchronous code for statement performed by thread. Query is executed on separate thread with its own runtime so it can be called also inside of tokio runtime (e.g.: when TCP requests are handled)
+pub fn select_blocking(database: &Path, sql: &str) -> DataFusionResult<(Vec<JsonSQLTableColumn>, Vec<Vec<JsonSQLTableColumnRow>>)> {
+    // Thread uses the same data directory and reads rows for the same statement as caller
+    let (data_path, statement) = (sql_wal::data_path(), QueryStatement::capture());
+    let results = std::thread::scope(|scope| {
+        scope.spawn(|| {
+            sql_wal::set_data_path(&data_path);
+            let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+            runtime.block_on(select(database, sql, &statement))
+        })
+        .join()
+        .map_err(|_| DataFusionError::Execution("Query execution has been interrupted".to_string()))?
+    });
+
+    statement.record_reads();
+    results
+}
+
+#[test]
+fn test_datafusion_select() {
+    use super::sql_json::{process_sql, ProcessSQLSupportedQueries, ProcessSQLRowField as Field, InsertOperations};
+
+    // Create tables "users" and "orders" in temporary database
+    let dir = std::env::temp_dir().join(format!("wastledb_datafusion_{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&dir).unwrap();
+    sql_wal::set_data_path(&dir);
+    let create = |name: &str, columns: Vec<(&str, SupportedSQLDataTypes)>, rows: Vec<Vec<&str>>| {
+        let path = sql_storage::table_path(&dir, name);
+        let table = process_sql(ProcessSQLSupportedQueries::CreateTable(name.to_string(), columns.iter().map(|(n, t)| (n.to_string(), t.clone(), None)).collect(), vec![])).unwrap();
+        sql_storage::write_table(&path, &table).unwrap();
+        let rows = rows.into_iter().map(|row| row.into_iter().zip(columns.iter()).map(|(val, (_, t))| Field(val.to_string(), t.clone())).collect::<Vec<_>>()).collect::<Vec<_>>();
+        let table = process_sql(ProcessSQLSupportedQueries::Insert(&path, None, rows, InsertOperations::Into)).unwrap();
+        sql_storage::write_table(&path, &table).unwrap();
+        path
+    };
+    create("users", vec![("id", SupportedSQLDataTypes::INT), ("name", SupportedSQLDataTypes::TEXT)], vec![vec!["1", "ann"], vec!["2", "bob"], vec!["3", "cid"]]);
+    create("orders", vec![("user_id", SupportedSQLDataTypes::INT), ("amount", SupportedSQLDataTypes::INT)], vec![vec!["1", "10"], vec!["1", "5"], vec!["3", "7"]]);
+
+    let values = |rows: Vec<Vec<JsonSQLTableColumnRow>>| rows.into_iter().map(|row| row.into_iter().map(|cell| value_text(cell.value).unwrap_or_default()).collect::<Vec<_>>()).collect::<Vec<_>>();
+
+    // Join with aggregate
+    let (columns, rows) = select_blocking(&dir, "SELECT u.name, SUM(o.amount) AS total FROM users u JOIN orders o ON o.user_id = u.id GROUP BY u.name ORDER BY total DESC").unwrap();
+    assert_eq!(columns.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(), vec!["name", "total"]);
+    assert_eq!(columns[1].d_type, SupportedSQLDataTypes::INT);
+    assert_eq!(values(rows), vec![vec!["ann", "15"], vec!["cid", "7"]]);
+
+    // Window function
+    let (_, rows) = select_blocking(&dir, "SELECT id, ROW_NUMBER() OVER (ORDER BY id DESC) AS position FROM users ORDER BY id").unwrap();
+    assert_eq!(values(rows), vec![vec!["1", "3"], vec!["2", "2"], vec!["3", "1"]]);
+
+    // Decimals, dates and timestamps keep their types
+    create("payments", vec![("amount", SupportedSQLDataTypes::DECIMAL(6, 2)), ("day", SupportedSQLDataTypes::DATE), ("at", SupportedSQLDataTypes::DATETIMESTAMP)], vec![vec!["10.50", "2022-12-01", "2022-12-01 10:00:00"], vec!["2.25", "2022-12-02", "2022-12-02 08:30:00"]]);
+    let (columns, rows) = select_blocking(&dir, "SELECT SUM(amount) AS total, MAX(day) AS day, MIN(at) AS at FROM payments").unwrap();
+    assert!(matches!(columns[0].d_type, SupportedSQLDataTypes::DECIMAL(_, 2)));
+    assert_eq!(columns[1].d_type, SupportedSQLDataTypes::DATE);
+    assert_eq!(columns[2].d_type, SupportedSQLDataTypes::DATETIMESTAMP);
+    assert_eq!(values(rows), vec![vec!["12.75", "2022-12-02", "2022-12-01 10:00:00"]]);
+
+    // Integer which doesn't fit into arrow array isn't turned into NULL
+    let path = create("counters", vec![("value", SupportedSQLDataTypes::INT)], vec![vec!["1"]]);
+    let mut table = sql_storage::read_table(&path).unwrap();
+    table.rows.as_mut().unwrap()[0][0].value = RowValue::Int(i64::MAX as i128 + 1);
+    sql_storage::write_table(&path, &table).unwrap();
+    assert!(select_blocking(&dir, "SELECT value FROM counters").is_err());
+
+    // Unknown table
+    assert!(select_blocking(&dir, "SELECT * FROM products").is_err());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,1006 @@
+/* Compute values of SQL expressions (conditions from "WHERE", "ON", "HAVING" and computed columns) for rows. Shared by SELECT, UPDATE and DELETE queries */
+use std::{cmp::Ordering, path::Path};
+
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{de::{self, Visitor}, Deserialize, Deserializer, Serialize, Serializer};
+
+use sqlparser::ast::{Expr, DataType, BinaryOperator, UnaryOperator, Function, FunctionArg, FunctionArgExpr, Query, Value as SQLParserValue};
+
+use super::sql_json::SupportedSQLDataTypes;
+use super::sql_select::{self, SelectQuery};
+use super::sql_sequences;
+
+#[derive(Debug, Clone)]
+/// Column of rows set for which expressions are computed (SELECT results or table rows in UPDATE and DELETE)
+pub struct RowsSetColumn {
+    /// name (or alias) of table from which column comes from. None for column merged by "JOIN .. USING (..)"
+    pub table: Option<String>,
+    /// column name
+    pub name: String,
+    /// column data type
+    pub d_type: SupportedSQLDataTypes,
+    /// hidden column can be reffered only by qualified name ("table.column") and isn't returned for "*". In that way are marked columns merged by "JOIN .. USING (..)"
+    pub hidden: bool,
+}
+
+/// Get position of column reffered by name (and optionaly by table name).
+/// Ok(None) is returned when column doesn't exists and Err(()) when column name without table name is ambiguous
+pub fn column_position(columns: &[RowsSetColumn], table: Option<&str>, name: &str) -> Result<Option<usize>, ()> {
+    let mut found = columns
+        .iter()
+        .enumerate()
+        .filter(|(_, column)| {
+            column.name == name && match table {
+                Some(table) => column.table.as_deref() == Some(table),
+                None => !column.hidden
+            }
+        })
+        .map(|(position, _)| position);
+
+    let first = found.next();
+    if found.next().is_some() {
+        return Err(());
+    };
+
+    Ok(first)
+}
+
+#[derive(Debug, Clone, Copy)]
+/// Row of outer query to which correlated subquery can reffer
+pub struct OuterRow<'a> {
+    pub columns: &'a [RowsSetColumn],
+    pub row: &'a [RowValue],
+    /// row of query in which outer query is nested
+    pub outer: Option<&'a OuterRow<'a>>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+/// Environment in which expressions are computed
+pub struct ExprContext<'a> {
+    /// directory of database in which subqueries are computed. Subqueries aren't allowed without it
+    pub database: Option<&'a Path>,
+    /// rows aggregated into group. Aggregate functions aren't allowed without them
+    pub group: Option<&'a [Vec<RowValue>]>,
+    /// row of query for which subquery is computed
+    pub outer: Option<&'a OuterRow<'a>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Typed value of row cell. Values are stored in table files, computed from expressions and returned to user in this form
+pub enum RowValue {
+    Null,
+    Int(i128),
+    Float(f64),
+    Decimal(i128, u32), // 1. digits without decimal point (e.g: 1234 for 12.34), 2. scale (count of digits after decimal point)
+    Boolean(bool),
+    Date(NaiveDate),
+    Timestamp(NaiveDateTime),
+    Text(String),
+}
+
+impl RowValue {
+    /// Convert value to data type of column. Value which can't be converted is returned unchanged (e.g: text from "INT" column of table saved before cell values were typed)
+    pub fn of_type(self, d_type: &SupportedSQLDataTypes) -> RowValue {
+        use SupportedSQLDataTypes as sup;
+        match (d_type, self) {
+            // Tables saved before cell values were typed have got all values stored as text (and NULL as "null" text)
+            (sup::INT | sup::FLOAT | sup::DECIMAL(..) | sup::BOOLEAN | sup::DATE | sup::DATETIMESTAMP, RowValue::Text(text)) if text == "null" => RowValue::Null,
+            (sup::INT, RowValue::Text(text)) => match text.trim().parse::<i128>() {
+                Ok(int) => RowValue::Int(int),
+                Err(_) => RowValue::Text(text)
+            },
+            (sup::INT, RowValue::Decimal(digits, scale)) => rescale_decimal(digits, scale, 0).map_or(RowValue::Decimal(digits, scale), RowValue::Int),
+            (sup::FLOAT, RowValue::Int(int)) => RowValue::Float(int as f64),
+            (sup::FLOAT, RowValue::Decimal(digits, scale)) => RowValue::Float(decimal_float(digits, scale)),
+            (sup::FLOAT, RowValue::Text(text)) => match text.trim().parse::<f64>() {
+                Ok(float) => RowValue::Float(float),
+                Err(_) => RowValue::Text(text)
+            },
+            // Decimal is rounded to scale of column (value with more digits than precision of column is returned unchanged)
+            (sup::DECIMAL(precision, scale), value @ (RowValue::Int(_) | RowValue::Float(_) | RowValue::Decimal(..) | RowValue::Text(_))) => {
+                exact_number(&value)
+                    .and_then(|(digits, digits_scale)| fit_decimal(digits, digits_scale, *precision, *scale))
+                    .unwrap_or(value)
+            },
+            (sup::BOOLEAN, RowValue::Text(text)) => match text.trim().to_lowercase().parse::<bool>() {
+                Ok(boolval) => RowValue::Boolean(boolval),
+                Err(_) => RowValue::Text(text)
+            },
+            (sup::DATE, RowValue::Text(text)) => parse_date(&text).map_or(RowValue::Text(text), RowValue::Date),
+            (sup::DATE, RowValue::Timestamp(timestamp)) => RowValue::Date(timestamp.date()),
+            (sup::DATETIMESTAMP, RowValue::Text(text)) => parse_timestamp(&text).map_or(RowValue::Text(text), RowValue::Timestamp),
+            (sup::DATETIMESTAMP, RowValue::Date(date)) => date.and_hms_opt(0, 0, 0).map_or(RowValue::Date(date), RowValue::Timestamp),
+            (sup::TEXT | sup::VARCHAR(_) | sup::LONGTEXT, value) => value_text(value).map_or(RowValue::Null, RowValue::Text),
+            (_, value) => value
+        }
+    }
+}
+
+/// In JSON values are represented by JSON types: NULL as null, numbers as numbers, BOOLEAN as boolean and other values (also dates and decimals, to keep all their digits) as strings
+impl Serialize for RowValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            RowValue::Null => serializer.serialize_none(),
+            RowValue::Int(int) => serializer.serialize_i128(*int),
+            RowValue::Float(float) => serializer.serialize_f64(*float),
+            RowValue::Boolean(boolval) => serializer.serialize_bool(*boolval),
+            value => serializer.serialize_str(&value_text(value.clone()).unwrap_or_default())
+        }
+    }
+}
+
+/// Value read from JSON has got type of JSON value (so date is read as text). To obtain value of column type use "RowValue::of_type"
+impl<'de> Deserialize<'de> for RowValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct RowValueVisitor;
+
+        impl<'de> Visitor<'de> for RowValueVisitor {
+            type Value = RowValue;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("null, number, boolean or string")
+            }
+
+            fn visit_unit<E: de::Error>(self) -> Result<RowValue, E> {
+                Ok(RowValue::Null)
+            }
+
+            fn visit_none<E: de::Error>(self) -> Result<RowValue, E> {
+                Ok(RowValue::Null)
+            }
+
+            fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<RowValue, D::Error> {
+                deserializer.deserialize_any(RowValueVisitor)
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<RowValue, E> {
+                Ok(RowValue::Int(value as i128))
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<RowValue, E> {
+                Ok(RowValue::Int(value as i128))
+            }
+
+            fn visit_i128<E: de::Error>(self, value: i128) -> Result<RowValue, E> {
+                Ok(RowValue::Int(value))
+            }
+
+            fn visit_f64<E: de::Error>(self, value: f64) -> Result<RowValue, E> {
+                Ok(RowValue::Float(value))
+            }
+
+            fn visit_bool<E: de::Error>(self, value: bool) -> Result<RowValue, E> {
+                Ok(RowValue::Boolean(value))
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<RowValue, E> {
+                Ok(RowValue::Text(value.to_string()))
+            }
+
+            fn visit_string<E: de::Error>(self, value: String) -> Result<RowValue, E> {
+                Ok(RowValue::Text(value))
+            }
+        }
+
+        deserializer.deserialize_any(RowValueVisitor)
+    }
+}
+
+/// Format in which "DATE" values are stored
+pub const DATE_FORMAT: &str = "%Y-%m-%d";
+/// Format in which "DATETIMESTAMP" values are stored (fraction of second is stored only when it isn't 0)
+pub const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.f";
+
+/// Parse date in format "YYYY-MM-DD"
+pub fn parse_date(text: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(text.trim(), DATE_FORMAT).ok()
+}
+
+/// Parse timestamp in format "YYYY-MM-DD HH:MM:SS[.fraction]" (also with "T" between date and time). Date without time is treated as midnight
+pub fn parse_timestamp(text: &str) -> Option<NaiveDateTime> {
+    let text = text.trim();
+    NaiveDateTime::parse_from_str(text, TIMESTAMP_FORMAT)
+        .or_else(|_| NaiveDateTime::parse_from_str(text, "%Y-%m-%dT%H:%M:%S%.f"))
+        .or_else(|_| NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M"))
+        .ok()
+        .or_else(|| parse_date(text).and_then(|date| date.and_hms_opt(0, 0, 0)))
+}
+
+/// Compare 2 values. None is returned when values can't be compared (e.g: one of them is NULL)
+pub fn compare(left: &RowValue, right: &RowValue) -> Option<Ordering> {
+    use RowValue::*;
+    match (left, right) {
+        (Null, _) | (_, Null) => None,
+        (Int(l), Int(r)) => Some(l.cmp(r)),
+        (Float(l), Float(r)) => l.partial_cmp(r),
+        (Boolean(l), Boolean(r)) => Some(l.cmp(r)),
+        (Text(l), Text(r)) => Some(l.cmp(r)),
+        // Numbers of different types (e.g: INT column compared with 9.99) are promoted to common type. Number compared with text (e.g: INT column compared with '5') is compared as number only when text is a number
+        (Int(_) | Float(_) | Decimal(..), Int(_) | Float(_) | Decimal(..) | Text(_)) | (Text(_), Int(_) | Float(_) | Decimal(..)) => compare_numbers(left, right),
+        // Dates are compared chronologically. Date compared with timestamp is treated as its midnight and text compared with date must be a date
+        (Date(l), Date(r)) => Some(l.cmp(r)),
+        (Timestamp(l), Timestamp(r)) => Some(l.cmp(r)),
+        (Date(date), Timestamp(_)) => compare(&Timestamp(date.and_hms_opt(0, 0, 0)?), right),
+        (Timestamp(_), Date(date)) => compare(left, &Timestamp(date.and_hms_opt(0, 0, 0)?)),
+        (Date(_), Text(text)) => compare(left, &Timestamp(parse_timestamp(text)?)),
+        (Text(text), Date(_)) => compare(&Timestamp(parse_timestamp(text)?), right),
+        (Timestamp(_), Text(text)) => compare(left, &Timestamp(parse_timestamp(text)?)),
+        (Text(text), Timestamp(_)) => compare(&Timestamp(parse_timestamp(text)?), right),
+        _ => None
+    }
+}
+
+/// Compare numbers (or text which is a number) of any types. Numbers are compared exactly as decimals so e.g: large integer isn't rounded as when it is converted to float.
+/// Numbers which haven't got exact form (e.g: infinity or text "1e3") or have got too many digits are compared as floats
+fn compare_numbers(left: &RowValue, right: &RowValue) -> Option<Ordering> {
+    let as_float = |value: &RowValue| match value {
+        RowValue::Int(int) => Some(*int as f64),
+        RowValue::Float(float) => Some(*float),
+        RowValue::Decimal(digits, scale) => Some(decimal_float(*digits, *scale)),
+        RowValue::Text(text) => text.trim().parse::<f64>().ok(),
+        _ => None
+    };
+
+    if let (Some((l_digits, l_scale)), Some((r_digits, r_scale))) = (exact_number(left), exact_number(right)) {
+        let scale = l_scale.max(r_scale);
+        if let (Some(l), Some(r)) = (rescale_decimal(l_digits, l_scale, scale), rescale_decimal(r_digits, r_scale, scale)) {
+            return Some(l.cmp(&r));
+        };
+    };
+
+    as_float(left)?.partial_cmp(&as_float(right)?)
+}
+
+/// Text form of value (e.g: used for concatenation). None for NULL
+pub fn value_text(value: RowValue) -> Option<String> {
+    match value {
+        RowValue::Null => None,
+        RowValue::Int(int) => Some(int.to_string()),
+        RowValue::Float(float) => Some(float.to_string()),
+        RowValue::Decimal(digits, scale) => Some(decimal_text(digits, scale)),
+        RowValue::Boolean(boolval) => Some(boolval.to_string()),
+        RowValue::Date(date) => Some(date.format(DATE_FORMAT).to_string()),
+        RowValue::Timestamp(timestamp) => Some(timestamp.format(TIMESTAMP_FORMAT).to_string()),
+        RowValue::Text(text) => Some(text)
+    }
+}
+
+/// Maximal count of digits of "DECIMAL" value (all of them must fit into i128)
+pub const DECIMAL_MAX_PRECISION: u8 = 38;
+/// Count of digits after decimal point which result of decimal division has got more than its more precise argument
+const DECIMAL_DIVISION_SCALE: u32 = 6;
+
+/// Parse exact number in format "[-]digits[.digits]" into digits without decimal point and scale (e.g: "-12.340" gives (-12340, 3)). None when text isn't such number or has got too many digits
+pub fn parse_decimal(text: &str) -> Option<(i128, u32)> {
+    let text = text.trim();
+    let (negative, number) = match text.strip_prefix('-') {
+        Some(number) => (true, number),
+        None => (false, text.strip_prefix('+').unwrap_or(text))
+    };
+    let (integer, fraction) = number.split_once('.').unwrap_or((number, ""));
+
+    if integer.is_empty() && fraction.is_empty() {
+        return None;
+    };
+    let mut digits = 0_i128;
+    for digit in integer.chars().chain(fraction.chars()) {
+        digits = digits.checked_mul(10)?.checked_add(digit.to_digit(10)? as i128)?;
+    }
+
+    Some((if negative { -digits } else { digits }, fraction.len() as u32))
+}
+
+/// Change scale of decimal digits. When scale is decreased then value is rounded half away from zero. None when value doesn't fit into i128 after change
+fn rescale_decimal(digits: i128, scale: u32, new_scale: u32) -> Option<i128> {
+    if new_scale >= scale {
+        return digits.checked_mul(10_i128.checked_pow(new_scale - scale)?);
+    };
+
+    match 10_i128.checked_pow(scale - new_scale) {
+        Some(divisor) => {
+            let (quotient, remainder) = (digits / divisor, digits % divisor);
+            Some(if remainder.abs() >= divisor - remainder.abs() { quotient + digits.signum() } else { quotient })
+        },
+        None => Some(0) // divisor is greater than any i128 value
+    }
+}
+
+/// Round decimal to scale of "DECIMAL(precision, scale)" column. None when value has got more digits than precision of column
+fn fit_decimal(digits: i128, digits_scale: u32, precision: u8, scale: u8) -> Option<RowValue> {
+    let digits = rescale_decimal(digits, digits_scale, scale as u32)?;
+
+    if digits.unsigned_abs() >= 10_u128.pow(precision.min(DECIMAL_MAX_PRECISION) as u32) {
+        return None;
+    };
+    Some(RowValue::Decimal(digits, scale as u32))
+}
+
+/// Approximate value of decimal as float
+fn decimal_float(digits: i128, scale: u32) -> f64 {
+    digits as f64 / 10_f64.powi(scale as i32)
+}
+
+/// Text form of decimal with all digits after decimal point (e.g: "12.30" for DECIMAL(4, 2))
+fn decimal_text(digits: i128, scale: u32) -> String {
+    let sign = if digits < 0 { "-" } else { "" };
+    let digits = format!("{:0>width$}", digits.unsigned_abs(), width = scale as usize + 1);
+    let (integer, fraction) = digits.split_at(digits.len() - scale as usize);
+
+    if fraction.is_empty() { format!("{}{}", sign, integer) } else { format!("{}{}.{}", sign, integer, fraction) }
+}
+
+/// Value as exact decimal (digits and scale). Float is taken with digits from its shortest text form (e.g: 0.1 as 1 with scale 1). None for values which aren't numbers
+pub fn exact_number(value: &RowValue) -> Option<(i128, u32)> {
+    match value {
+        RowValue::Int(int) => Some((*int, 0)),
+        RowValue::Decimal(digits, scale) => Some((*digits, *scale)),
+        RowValue::Float(float) => parse_decimal(&float.to_string()),
+        RowValue::Text(text) => parse_decimal(text),
+        _ => None
+    }
+}
+
+/// Key of value which is the same for all values equal when compared (e.g: 1, 1.0 and 1.00 or date and timestamp of its midnight). Used to group rows
+pub fn value_key(value: &RowValue) -> String {
+    match value {
+        RowValue::Int(_) | RowValue::Float(_) | RowValue::Decimal(..) => match exact_number(value) {
+            Some((mut digits, mut scale)) => {
+                while scale > 0 && digits % 10 == 0 {
+                    digits /= 10;
+                    scale -= 1;
+                }
+                format!("{:?}", RowValue::Decimal(digits, scale))
+            },
+            None => format!("{:?}", value)
+        },
+        RowValue::Date(date) => format!("{:?}", date.and_hms_opt(0, 0, 0).map_or(RowValue::Null, RowValue::Timestamp)),
+        _ => format!("{:?}", value)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AggregateFunction {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max
+}
+
+/// Obtain aggregate function when expression is call of it
+pub fn aggregate_function(expr: &Expr) -> Option<(AggregateFunction, &Function)> {
+    match expr {
+        Expr::Function(function) if function.name.0.len() == 1 && function.over.is_none() => {
+            let kind = match function.name.0[0].value.to_uppercase().as_str() {
+                "COUNT" => AggregateFunction::Count,
+                "SUM" => AggregateFunction::Sum,
+                "AVG" => AggregateFunction::Avg,
+                "MIN" => AggregateFunction::Min,
+                "MAX" => AggregateFunction::Max,
+                _ => return None
+            };
+            Some((kind, function))
+        },
+        _ => None
+    }
+}
+
+/// Whether expression contains call of aggregate function
+pub fn contains_aggregate(expr: &Expr) -> bool {
+    match expr {
+        _ if aggregate_function(expr).is_some() => true,
+        Expr::Nested(inside) => contains_aggregate(inside),
+        Expr::BinaryOp { left, right, .. } => contains_aggregate(left) || contains_aggregate(right),
+        Expr::UnaryOp { expr, .. } => contains_aggregate(expr),
+        _ => false
+    }
+}
+
+/// Compute aggregate function over all rows from group. NULLs are skipped, and for empty group only COUNT returns value other than NULL
+fn evaluate_aggregate(kind: AggregateFunction, function: &Function, columns: &[RowsSetColumn], group: &[Vec<RowValue>], context: &ExprContext) -> Result<RowValue, ()> {
+    let arg = match function.args.as_slice() {
+        [FunctionArg::Unnamed(FunctionArgExpr::Wildcard)] if kind == AggregateFunction::Count && !function.distinct => {
+            return Ok(RowValue::Int(group.len() as i128)) // COUNT(*)
+        },
+        [FunctionArg::Unnamed(FunctionArgExpr::Expr(arg))] => arg,
+        _ => return Err(())
+    };
+
+    // Argument values for all rows (aggregate functions can't be nested so argument is computed outside of group)
+    let mut values = vec![] as Vec<RowValue>;
+    let row_context = ExprContext { group: None, ..*context };
+    for row in group {
+        let value = evaluate(arg, columns, row, &row_context)?;
+        if value != RowValue::Null && !(function.distinct && values.contains(&value)) {
+            values.push(value);
+        }
+    }
+
+    // Numeric value (text is accepted only when it is a number)
+    let as_number = |value: &RowValue| match value {
+        RowValue::Int(_) | RowValue::Float(_) | RowValue::Decimal(..) => Ok(value.clone()),
+        RowValue::Text(text) => text.parse::<f64>().map(RowValue::Float).map_err(|_| ()),
+        _ => Err(())
+    };
+    // Sum of integers is integer, sum with any decimal is exact decimal and otherwise it is float
+    let sum = || -> Result<RowValue, ()> {
+        let mut sum = RowValue::Int(0);
+        for value in &values {
+            sum = arithmetic(&BinaryOperator::Plus, sum, as_number(value)?)?;
+        }
+        Ok(sum)
+    };
+
+    match kind {
+        AggregateFunction::Count => Ok(RowValue::Int(values.len() as i128)),
+        _ if values.is_empty() => Ok(RowValue::Null),
+        AggregateFunction::Sum => sum(),
+        AggregateFunction::Avg => {
+            match sum()? {
+                RowValue::Int(sum) => Ok(RowValue::Float(sum as f64 / values.len() as f64)),
+                RowValue::Float(sum) => Ok(RowValue::Float(sum / values.len() as f64)),
+                sum @ RowValue::Decimal(..) => arithmetic(&BinaryOperator::Divide, sum, RowValue::Int(values.len() as i128)),
+                _ => Err(())
+            }
+        },
+        AggregateFunction::Min | AggregateFunction::Max => {
+            let mut result = values[0].clone();
+            for value in &values[1..] {
+                let ordering = compare(value, &result).ok_or(())?;
+                if (kind == AggregateFunction::Min && ordering == Ordering::Less) || (kind == AggregateFunction::Max && ordering == Ordering::Greater) {
+                    result = value.clone();
+                }
+            }
+            Ok(result)
+        }
+    }
+}
+
+/// Current date or time when function is one of functions which return it (e.g: CURRENT_TIMESTAMP). Time is taken in local timezone of server
+pub fn current_time_value(function: &Function) -> Option<RowValue> {
+    if function.name.0.len() != 1 || !function.args.is_empty() || function.over.is_some() {
+        return None;
+    };
+
+    let now = chrono::Local::now().naive_local();
+    match function.name.0[0].value.to_uppercase().as_str() {
+        "CURRENT_TIMESTAMP" | "LOCALTIMESTAMP" | "NOW" => Some(RowValue::Timestamp(now)),
+        "CURRENT_DATE" => Some(RowValue::Date(now.date())),
+        _ => None
+    }
+}
+
+/// Sequence name and whether sequence is moved forward when function is "nextval('sequence')" or "currval('sequence')"
+pub fn sequence_function(function: &Function) -> Option<(String, bool)> {
+    if function.name.0.len() != 1 || function.over.is_some() {
+        return None;
+    };
+
+    let next = match function.name.0[0].value.to_uppercase().as_str() {
+        "NEXTVAL" => true,
+        "CURRVAL" => false,
+        _ => return None
+    };
+    match function.args.as_slice() {
+        [FunctionArg::Unnamed(FunctionArgExpr::Expr(Expr::Value(SQLParserValue::SingleQuotedString(name))))] => Some((name.clone(), next)),
+        _ => None
+    }
+}
+
+/// Data type of value computed from expression
+pub fn expression_type(expr: &Expr, columns: &[RowsSetColumn]) -> SupportedSQLDataTypes {
+    let column_type = |position: Result<Option<usize>, ()>| match position {
+        Ok(Some(position)) => columns[position].d_type.clone(),
+        _ => SupportedSQLDataTypes::TEXT
+    };
+
+    match expr {
+        Expr::Identifier(ident) => column_type(column_position(columns, None, &ident.value)),
+        Expr::CompoundIdentifier(idents) if idents.len() == 2 => column_type(column_position(columns, Some(&idents[0].value), &idents[1].value)),
+        Expr::Value(SQLParserValue::Number(num, _)) if num.parse::<i128>().is_ok() => SupportedSQLDataTypes::INT,
+        Expr::Value(SQLParserValue::Number(_, _)) => SupportedSQLDataTypes::FLOAT,
+        Expr::Value(SQLParserValue::Boolean(_)) => SupportedSQLDataTypes::BOOLEAN,
+        Expr::Value(SQLParserValue::Null) => SupportedSQLDataTypes::NULL,
+        Expr::TypedString { data_type: DataType::Date, .. } => SupportedSQLDataTypes::DATE,
+        Expr::TypedString { data_type: DataType::Timestamp(_) | DataType::Datetime, .. } => SupportedSQLDataTypes::DATETIMESTAMP,
+        Expr::Nested(inside) => expression_type(inside, columns),
+        Expr::BinaryOp { left, op: op @ (BinaryOperator::Plus | BinaryOperator::Minus | BinaryOperator::Multiply | BinaryOperator::Divide | BinaryOperator::Modulo), right } => {
+            // Only operation on integers gives integer and operation with decimal gives decimal (with scale of its result)
+            let (left_type, right_type) = (expression_type(left, columns), expression_type(right, columns));
+            let decimal_scale = |d_type: &SupportedSQLDataTypes| match d_type {
+                SupportedSQLDataTypes::DECIMAL(_, scale) => Some(*scale),
+                _ => None
+            };
+            match (&left_type, &right_type) {
+                (SupportedSQLDataTypes::INT, SupportedSQLDataTypes::INT) => SupportedSQLDataTypes::INT,
+                _ if decimal_scale(&left_type).or(decimal_scale(&right_type)).is_some() => {
+                    let (l_scale, r_scale) = (decimal_scale(&left_type).unwrap_or(0), decimal_scale(&right_type).unwrap_or(0));
+                    let scale = match op {
+                        BinaryOperator::Multiply => l_scale.saturating_add(r_scale),
+                        BinaryOperator::Divide => l_scale.max(r_scale).saturating_add(DECIMAL_DIVISION_SCALE as u8),
+                        _ => l_scale.max(r_scale)
+                    };
+                    SupportedSQLDataTypes::DECIMAL(DECIMAL_MAX_PRECISION, scale.min(DECIMAL_MAX_PRECISION))
+                },
+                _ => SupportedSQLDataTypes::FLOAT
+            }
+        },
+        Expr::BinaryOp { op: BinaryOperator::StringConcat, .. } => SupportedSQLDataTypes::TEXT,
+        Expr::UnaryOp { op: UnaryOperator::Minus | UnaryOperator::Plus, expr } => expression_type(expr, columns),
+        Expr::Function(function) if sequence_function(function).is_some() => SupportedSQLDataTypes::INT,
+        Expr::Function(function) if current_time_value(function).is_some() => {
+            match current_time_value(function) {
+                Some(RowValue::Date(_)) => SupportedSQLDataTypes::DATE,
+                _ => SupportedSQLDataTypes::DATETIMESTAMP
+            }
+        },
+        Expr::BinaryOp { .. } | Expr::UnaryOp { .. } | Expr::IsNull(_) | Expr::IsNotNull(_) | Expr::InList { .. } | Expr::Between { .. } | Expr::Like { .. } | Expr::ILike { .. } | Expr::InSubquery { .. } | Expr::Exists { .. } => SupportedSQLDataTypes::BOOLEAN,
+        _ => {
+            match aggregate_function(expr) {
+                Some((AggregateFunction::Count, _)) => SupportedSQLDataTypes::INT,
+                Some((kind, function)) => {
+                    let arg_type = match function.args.as_slice() {
+                        [FunctionArg::Unnamed(FunctionArgExpr::Expr(arg))] => expression_type(arg, columns),
+                        _ => SupportedSQLDataTypes::TEXT
+                    };
+                    match (kind, arg_type) {
+                        (AggregateFunction::Sum, SupportedSQLDataTypes::DECIMAL(_, scale)) => SupportedSQLDataTypes::DECIMAL(DECIMAL_MAX_PRECISION, scale),
+                        (AggregateFunction::Avg, SupportedSQLDataTypes::DECIMAL(_, scale)) => SupportedSQLDataTypes::DECIMAL(DECIMAL_MAX_PRECISION, (scale + DECIMAL_DIVISION_SCALE as u8).min(DECIMAL_MAX_PRECISION)),
+                        (AggregateFunction::Avg, _) => SupportedSQLDataTypes::FLOAT,
+                        (AggregateFunction::Sum, arg_type) if arg_type != SupportedSQLDataTypes::INT => SupportedSQLDataTypes::FLOAT,
+                        (_, arg_type) => arg_type
+                    }
+                },
+                None => SupportedSQLDataTypes::TEXT
+            }
+        }
+    }
+}
+
+/// Obtain value of column reffered by name (and optionaly by table name). When column doesn't exists in row then it is searched in outer query rows
+fn column_value(columns: &[RowsSetColumn], row: &[RowValue], table: Option<&str>, name: &str, outer: Option<&OuterRow>) -> Result<Option<RowValue>, ()> {
+    match (column_position(columns, table, name)?, outer) {
+        (Some(position), _) => Ok(Some(row[position].clone())),
+        (None, Some(outer)) => column_value(outer.columns, outer.row, table, name, outer.outer),
+        (None, None) => Ok(None)
+    }
+}
+
+/// Compute subquery for row and return values of its results. Subquery can reffer to columns of row (correlated subquery).
+/// When "single_column" is set then subquery must return single column
+fn subquery_rows(subquery: &Query, single_column: bool, columns: &[RowsSetColumn], row: &[RowValue], context: &ExprContext) -> Result<Vec<Vec<RowValue>>, ()> {
+    let database = context.database.ok_or(())?;
+    let outer = OuterRow { columns, row, outer: context.outer };
+    let select_query = SelectQuery::from_query(database, subquery).map_err(|_| ())?;
+    let (result_columns, result_rows) = sql_select::select(&select_query, Some(&outer))?;
+
+    if single_column && result_columns.len() != 1 {
+        return Err(());
+    };
+
+    Ok(result_rows
+        .into_iter()
+        .map(|result_row| result_row.into_iter().map(|cell| cell.value).collect::<Vec<_>>())
+        .collect::<Vec<_>>())
+}
+
+/// Parse expression stored as SQL text in table schema (e.g: expression of CHECK constraint)
+pub fn parse_expression(sql: &str) -> Result<Expr, ()> {
+    use sqlparser::{dialect::AnsiDialect, parser::Parser as SqlParser, tokenizer::Tokenizer};
+
+    let tokens = Tokenizer::new(&AnsiDialect {}, sql).tokenize().map_err(|_| ())?;
+    SqlParser::new(tokens, &AnsiDialect {}).parse_expr().map_err(|_| ())
+}
+
+/// Compute value of expression for row. Aggregate functions are computed over context group rows and aren't allowed when group isn't attached
+pub fn evaluate(expr: &Expr, columns: &[RowsSetColumn], row: &[RowValue], context: &ExprContext) -> Result<RowValue, ()> {
+    match expr {
+        Expr::Identifier(ident) => {
+            column_value(columns, row, None, &ident.value, context.outer)?.ok_or(())
+        },
+        Expr::CompoundIdentifier(idents) if idents.len() == 2 => column_value(columns, row, Some(&idents[0].value), &idents[1].value, context.outer)?.ok_or(()),
+        Expr::Value(value) => {
+            match value {
+                SQLParserValue::Number(num, _) => {
+                    if let Ok(int) = num.parse::<i128>() {
+                        Ok(RowValue::Int(int))
+                    }
+                    else {
+                        num.parse::<f64>().map(RowValue::Float).map_err(|_| ())
+                    }
+                },
+                SQLParserValue::SingleQuotedString(stri) | SQLParserValue::DoubleQuotedString(stri) => Ok(RowValue::Text(stri.clone())),
+                SQLParserValue::Boolean(boolval) => Ok(RowValue::Boolean(*boolval)),
+                SQLParserValue::Null => Ok(RowValue::Null),
+                _ => Err(())
+            }
+        },
+        Expr::TypedString { data_type: DataType::Date, value } => parse_date(value).map(RowValue::Date).ok_or(()), // DATE '2022-12-01'
+        Expr::TypedString { data_type: DataType::Timestamp(_) | DataType::Datetime, value } => parse_timestamp(value).map(RowValue::Timestamp).ok_or(()), // TIMESTAMP '2022-12-01 10:00:00'
+        Expr::Nested(inside) => evaluate(inside, columns, row, context),
+        Expr::BinaryOp { left, op, right } => {
+            let left = evaluate(left, columns, row, context)?;
+            let right = match (op, &**right) {
+                // Word which isn't column name on right side of comparison is treated as text (e.g: WHERE name = kika)
+                (BinaryOperator::Eq | BinaryOperator::NotEq | BinaryOperator::Gt | BinaryOperator::GtEq | BinaryOperator::Lt | BinaryOperator::LtEq, Expr::Identifier(ident)) => {
+                    column_value(columns, row, None, &ident.value, context.outer)?.unwrap_or_else(|| RowValue::Text(ident.value.clone()))
+                },
+                _ => evaluate(right, columns, row, context)?
+            };
+
+            match op {
+                BinaryOperator::And => Ok(logical_and(as_bool(&left)?, as_bool(&right)?).map_or(RowValue::Null, RowValue::Boolean)),
+                BinaryOperator::Or => Ok(logical_or(as_bool(&left)?, as_bool(&right)?).map_or(RowValue::Null, RowValue::Boolean)),
+                BinaryOperator::Plus | BinaryOperator::Minus | BinaryOperator::Multiply | BinaryOperator::Divide | BinaryOperator::Modulo => arithmetic(op, left, right),
+                BinaryOperator::StringConcat => {
+                    match (value_text(left), value_text(right)) {
+                        (Some(left), Some(right)) => Ok(RowValue::Text(left + &right)),
+                        _ => Ok(RowValue::Null)
+                    }
+                },
+                _ => {
+                    let ordering = compare(&left, &right);
+                    let result = ordering.map(|ordering| match op {
+                        BinaryOperator::Eq => Ok(ordering == Ordering::Equal),
+                        BinaryOperator::NotEq => Ok(ordering != Ordering::Equal),
+                        BinaryOperator::Gt => Ok(ordering == Ordering::Greater),
+                        BinaryOperator::GtEq => Ok(ordering != Ordering::Less),
+                        BinaryOperator::Lt => Ok(ordering == Ordering::Less),
+                        BinaryOperator::LtEq => Ok(ordering != Ordering::Greater),
+                        _ => Err(())
+                    });
+
+                    match result {
+                        Some(Ok(boolval)) => Ok(RowValue::Boolean(boolval)),
+                        Some(Err(_)) => Err(()),
+                        None => Ok(RowValue::Null)
+                    }
+                }
+            }
+        },
+        Expr::UnaryOp { op, expr } => {
+            let value = evaluate(expr, columns, row, context)?;
+
+            match (op, value) {
+                (_, RowValue::Null) => Ok(RowValue::Null),
+                (UnaryOperator::Not, value) => Ok(RowValue::Boolean(!as_bool(&value)?.ok_or(())?)),
+                (UnaryOperator::Minus, RowValue::Int(int)) => Ok(RowValue::Int(-int)),
+                (UnaryOperator::Minus, RowValue::Float(float)) => Ok(RowValue::Float(-float)),
+                (UnaryOperator::Minus, RowValue::Decimal(digits, scale)) => Ok(RowValue::Decimal(-digits, scale)),
+                (UnaryOperator::Plus, value @ (RowValue::Int(_) | RowValue::Float(_) | RowValue::Decimal(..))) => Ok(value),
+                _ => Err(())
+            }
+        },
+        Expr::IsNull(expr) => Ok(RowValue::Boolean(evaluate(expr, columns, row, context)? == RowValue::Null)),
+        Expr::IsNotNull(expr) => Ok(RowValue::Boolean(evaluate(expr, columns, row, context)? != RowValue::Null)),
+        Expr::InList { expr, list, negated } => {
+            let value = evaluate(expr, columns, row, context)?;
+
+            // Value is in list when is equal to one of list values. When it isn't equal to any of them but some of them is NULL then result is unknown
+            let mut result = Some(false);
+            for list_expr in list {
+                match compare(&value, &evaluate(list_expr, columns, row, context)?) {
+                    Some(Ordering::Equal) => {
+                        result = Some(true);
+                        break;
+                    },
+                    Some(_) => (),
+                    None => result = None
+                }
+            }
+
+            Ok(result.map_or(RowValue::Null, |in_list| RowValue::Boolean(in_list != *negated)))
+        },
+        Expr::Between { expr, negated, low, high } => {
+            let value = evaluate(expr, columns, row, context)?;
+            let low = evaluate(low, columns, row, context)?;
+            let high = evaluate(high, columns, row, context)?;
+
+            // Same as "value >= low AND value <= high"
+            let above_low = compare(&value, &low).map(|ordering| ordering != Ordering::Less);
+            let below_high = compare(&value, &high).map(|ordering| ordering != Ordering::Greater);
+            Ok(logical_and(above_low, below_high).map_or(RowValue::Null, |between| RowValue::Boolean(between != *negated)))
+        },
+        Expr::Like { negated, expr: value_expr, pattern, escape_char } | Expr::ILike { negated, expr: value_expr, pattern, escape_char } => {
+            let value = evaluate(value_expr, columns, row, context)?;
+            let pattern = evaluate(pattern, columns, row, context)?;
+
+            match (value_text(value), value_text(pattern)) {
+                (Some(value), Some(pattern)) => {
+                    // ILIKE compares text without taking care about letters size
+                    let like = if matches!(expr, Expr::ILike { .. }) {
+                        like_matches(&value.to_lowercase(), &pattern.to_lowercase(), *escape_char)
+                    }
+                    else {
+                        like_matches(&value, &pattern, *escape_char)
+                    };
+                    Ok(RowValue::Boolean(like != *negated))
+                },
+                _ => Ok(RowValue::Null)
+            }
+        },
+        Expr::InSubquery { expr, subquery, negated } => {
+            let value = evaluate(expr, columns, row, context)?;
+
+            // Same as "IN (...)" for list of values returned by subquery
+            let mut result = Some(false);
+            for subquery_row in subquery_rows(subquery, true, columns, row, context)? {
+                match compare(&value, &subquery_row[0]) {
+                    Some(Ordering::Equal) => {
+                        result = Some(true);
+                        break;
+                    },
+                    Some(_) => (),
+                    None => result = None
+                }
+            }
+
+            Ok(result.map_or(RowValue::Null, |in_list| RowValue::Boolean(in_list != *negated)))
+        },
+        Expr::Exists { subquery, negated } => Ok(RowValue::Boolean(subquery_rows(subquery, false, columns, row, context)?.is_empty() == *negated)),
+        Expr::Subquery(subquery) => {
+            // Scalar subquery returns single value or NULL when it hasn't got any results
+            let mut subquery_rows = subquery_rows(subquery, true, columns, row, context)?;
+            match subquery_rows.len() {
+                0 => Ok(RowValue::Null),
+                1 => Ok(subquery_rows.remove(0).remove(0)),
+                _ => Err(())
+            }
+        },
+        Expr::Function(function) if current_time_value(function).is_some() => current_time_value(function).ok_or(()),
+        Expr::Function(function) if sequence_function(function).is_some() => {
+            // Sequences belong to database so they can be used only when database is known
+            let (name, next) = sequence_function(function).ok_or(())?;
+            let database = context.database.ok_or(())?;
+            let value = if next { sql_sequences::next_value(database, &name) } else { sql_sequences::current_value(database, &name) };
+            value.map(RowValue::Int).map_err(|_| ())
+        },
+        _ => {
+            match (aggregate_function(expr), context.group) {
+                (Some((kind, function)), Some(group)) => evaluate_aggregate(kind, function, columns, group, context),
+                _ => Err(()) // unsupported expression or aggregate function outside of group (e.g: in "WHERE")
+            }
+        }
+    }
+}
+
+/// Compute arithmetic operation on 2 numbers. Text is accepted only when it is a number.
+/// Operation on integers gives integer (division is truncated), operation with decimal gives exact decimal and on other numbers gives float
+fn arithmetic(op: &BinaryOperator, left: RowValue, right: RowValue) -> Result<RowValue, ()> {
+    let as_number = |value: RowValue| match value {
+        RowValue::Text(text) => {
+            match text.parse::<i128>() {
+                Ok(int) => Ok(RowValue::Int(int)),
+                Err(_) => text.parse::<f64>().map(RowValue::Float).map_err(|_| ())
+            }
+        },
+        RowValue::Null | RowValue::Int(_) | RowValue::Float(_) | RowValue::Decimal(..) => Ok(value),
+        RowValue::Boolean(_) | RowValue::Date(_) | RowValue::Timestamp(_) => Err(())
+    };
+
+    match (as_number(left)?, as_number(right)?) {
+        (RowValue::Null, _) | (_, RowValue::Null) => Ok(RowValue::Null),
+        (RowValue::Int(left), RowValue::Int(right)) => {
+            let result = match op {
+                BinaryOperator::Plus => left.checked_add(right),
+                BinaryOperator::Minus => left.checked_sub(right),
+                BinaryOperator::Multiply => left.checked_mul(right),
+                BinaryOperator::Divide => left.checked_div(right), // None for division by zero
+                BinaryOperator::Modulo => left.checked_rem(right),
+                _ => None
+            };
+            result.map(RowValue::Int).ok_or(())
+        },
+        (left, right) if matches!(left, RowValue::Decimal(..)) || matches!(right, RowValue::Decimal(..)) => decimal_arithmetic(op, &left, &right),
+        (left, right) => {
+            let as_float = |value: RowValue| match value {
+                RowValue::Int(int) => int as f64,
+                RowValue::Float(float) => float,
+                _ => f64::NAN
+            };
+            let (left, right) = (as_float(left), as_float(right));
+
+            if matches!(op, BinaryOperator::Divide | BinaryOperator::Modulo) && right == 0.0 {
+                return Err(());
+            };
+            match op {
+                BinaryOperator::Plus => Ok(RowValue::Float(left + right)),
+                BinaryOperator::Minus => Ok(RowValue::Float(left - right)),
+                BinaryOperator::Multiply => Ok(RowValue::Float(left * right)),
+                BinaryOperator::Divide => Ok(RowValue::Float(left / right)),
+                BinaryOperator::Modulo => Ok(RowValue::Float(left % right)),
+                _ => Err(())
+            }
+        }
+    }
+}
+
+/// Compute arithmetic operation exactly on decimals (integer and float are taken as decimals).
+/// Result of addition, subtraction and modulo has got scale of more precise argument, result of multiplication sum of arguments scales and result of division 6 digits more than more precise argument (it is rounded)
+fn decimal_arithmetic(op: &BinaryOperator, left: &RowValue, right: &RowValue) -> Result<RowValue, ()> {
+    let ((l_digits, l_scale), (r_digits, r_scale)) = (exact_number(left).ok_or(())?, exact_number(right).ok_or(())?);
+    let scale = l_scale.max(r_scale);
+    let (left, right) = (rescale_decimal(l_digits, l_scale, scale).ok_or(())?, rescale_decimal(r_digits, r_scale, scale).ok_or(())?);
+
+    let result = match op {
+        BinaryOperator::Plus => left.checked_add(right).map(|digits| (digits, scale)),
+        BinaryOperator::Minus => left.checked_sub(right).map(|digits| (digits, scale)),
+        BinaryOperator::Multiply => l_digits.checked_mul(r_digits).map(|digits| (digits, l_scale + r_scale)),
+        BinaryOperator::Modulo => left.checked_rem(right).map(|digits| (digits, scale)), // None for division by zero
+        BinaryOperator::Divide if right != 0 => {
+            // Both arguments have got the same scale so quotient of digits extended by result scale is result digits
+            let result_scale = scale + DECIMAL_DIVISION_SCALE;
+            10_i128.checked_pow(result_scale).and_then(|multiplier| left.checked_mul(multiplier)).map(|dividend| {
+                let (quotient, remainder) = (dividend / right, dividend % right);
+                let round = if remainder.abs() >= right.abs() - remainder.abs() { dividend.signum() * right.signum() } else { 0 };
+                (quotient + round, result_scale)
+            })
+        },
+        _ => None
+    };
+
+    result.map(|(digits, scale)| RowValue::Decimal(digits, scale)).ok_or(())
+}
+
+/// Interpret value as logical value. NULL is unknown logical value
+fn as_bool(value: &RowValue) -> Result<Option<bool>, ()> {
+    match value {
+        RowValue::Boolean(boolval) => Ok(Some(*boolval)),
+        RowValue::Null => Ok(None),
+        _ => Err(())
+    }
+}
+
+/// Logical "AND" in three-valued logic where None means "unknown"
+fn logical_and(left: Option<bool>, right: Option<bool>) -> Option<bool> {
+    match (left, right) {
+        (Some(false), _) | (_, Some(false)) => Some(false),
+        (Some(true), Some(true)) => Some(true),
+        _ => None
+    }
+}
+
+/// Logical "OR" in three-valued logic where None means "unknown"
+fn logical_or(left: Option<bool>, right: Option<bool>) -> Option<bool> {
+    match (left, right) {
+        (Some(true), _) | (_, Some(true)) => Some(true),
+        (Some(false), Some(false)) => Some(false),
+        _ => None
+    }
+}
+
+/// Whether text matches to "LIKE" pattern, where "%" matches any sequence of characters and "_" matches single character.
+/// Character after escape character is matched literally
+fn like_matches(text: &str, pattern: &str, escape_char: Option<char>) -> bool {
+    #[derive(PartialEq)]
+    enum PatternToken {
+        Any,
+        Single,
+        Char(char)
+    }
+
+    let mut tokens = vec![] as Vec<PatternToken>;
+    let mut pattern_chars = pattern.chars();
+    while let Some(pattern_char) = pattern_chars.next() {
+        tokens.push(match pattern_char {
+            _ if Some(pattern_char) == escape_char => PatternToken::Char(pattern_chars.next().unwrap_or(pattern_char)),
+            '%' => PatternToken::Any,
+            '_' => PatternToken::Single,
+            _ => PatternToken::Char(pattern_char)
+        });
+    }
+
+    // matched[j] = whether text prefix matches to first j pattern tokens
+    let text = text.chars().collect::<Vec<_>>();
+    let mut matched = vec![false; tokens.len() + 1];
+    matched[0] = true;
+    for (token_id, token) in tokens.iter().enumerate() {
+        if *token != PatternToken::Any {
+            break;
+        };
+        matched[token_id + 1] = true;
+    }
+
+    for text_char in text {
+        let mut next = vec![false; tokens.len() + 1];
+        for (token_id, token) in tokens.iter().enumerate() {
+            next[token_id + 1] = match token {
+                PatternToken::Any => next[token_id] || matched[token_id + 1],
+                PatternToken::Single => matched[token_id],
+                PatternToken::Char(pattern_char) => matched[token_id] && *pattern_char == text_char
+            };
+        }
+        matched = next;
+    }
+
+    matched[tokens.len()]
+}
+
+/// Whether row fullfils condition. Row matches only when condition is TRUE (NULL is treated as not matched)
+pub fn matches(condition: &Expr, columns: &[RowsSetColumn], row: &[RowValue], context: &ExprContext) -> Result<bool, ()> {
+    value_matches(evaluate(condition, columns, row, context)?)
+}
+
+/// Whether computed condition value means that condition is fullfiled
+pub fn value_matches(value: RowValue) -> Result<bool, ()> {
+    match value {
+        RowValue::Boolean(boolval) => Ok(boolval),
+        RowValue::Null => Ok(false),
+        _ => Err(())
+    }
+}
+
+
+#[test]
+fn test_evaluate_conditions() {
+    use sqlparser::{dialect::AnsiDialect, parser::Parser as SqlParser, ast::{Statement, SetExpr}};
+
+    let column = |name: &str, d_type: SupportedSQLDataTypes| RowsSetColumn { table: Some("t".to_string()), name: name.to_string(), d_type, hidden: false };
+    let columns = vec![column("name", SupportedSQLDataTypes::VARCHAR(None)), column("age", SupportedSQLDataTypes::INT), column("limit_age", SupportedSQLDataTypes::INT), column("email", SupportedSQLDataTypes::TEXT)];
+    let row = vec![RowValue::Text("Anna_K".to_string()), RowValue::Int(30), RowValue::Int(25), RowValue::Null];
+
+    // Whether row matches to condition from 'WHERE'
+    let result = |condition: &str| {
+        match SqlParser::parse_sql(&AnsiDialect {}, &format!("SELECT * FROM t WHERE {}", condition)).unwrap().remove(0) {
+            Statement::Query(query) => match *query.body {
+                SetExpr::Select(select) => matches(&select.selection.unwrap(), &columns, &row, &ExprContext::default()),
+                _ => unreachable!()
+            },
+            _ => unreachable!()
+        }
+    };
+    let check = |condition: &str| result(condition).unwrap();
+
+    // Precedence and parentheses
+    assert!(check("age = 1 AND name = 'x' OR age = 30"));
+    assert!(!check("age = 1 AND (name = 'x' OR age = 30)"));
+    assert!(check("NOT (age = 1 OR age > 40)"));
+
+    // Column to column comparision
+    assert!(check("age > limit_age"));
+    assert!(check("t.limit_age <= t.age"));
+
+    assert!(check("age IN (10, 20, 30)"));
+    assert!(check("name NOT IN ('Bob', 'Cid')"));
+    assert!(!check("age NOT IN (10, NULL)")); // unknown
+    assert!(check("age BETWEEN 25 AND 30"));
+    assert!(!check("age NOT BETWEEN limit_age AND 40"));
+    assert!(check("age > -5"));
+
+    assert!(check("name LIKE 'An%'"));
+    assert!(check("name LIKE '_nna!_K' ESCAPE '!'"));
+    assert!(!check("name LIKE 'an%'"));
+    assert!(check("name ILIKE 'an%k'"));
+    assert!(check("name NOT LIKE '%x%'"));
+
+    assert!(check("email IS NULL"));
+    assert!(check("name IS NOT NULL"));
+    // Comparision with NULL is unknown so neither condition nor its negation is fullfiled
+    assert!(!check("email = 'a'"));
+    assert!(!check("NOT email = 'a'"));
+
+    // Word which isn't column name is text only on right side of comparison
+    assert!(check("name = Anna_K"));
+    assert!(result("nme = 'Anna_K'").is_err());
+    assert!(result("name IN (Anna_K)").is_err());
+}
+
+#[test]
+fn test_compare_values() {
+    use RowValue::*;
+
+    // Numbers of different types are promoted to common type
+    assert_eq!(compare(&Float(10.5), &Float(9.99)), Some(Ordering::Greater));
+    assert_eq!(compare(&Int(10), &Float(9.99)), Some(Ordering::Greater));
+    assert_eq!(compare(&Float(9.99), &Decimal(999, 2)), Some(Ordering::Equal));
+    assert_eq!(compare(&Int(9_007_199_254_740_993), &Float(9_007_199_254_740_992.0)), Some(Ordering::Greater)); // not equal after conversion to float
+    assert_eq!(compare(&Text("9.99".to_string()), &Int(10)), Some(Ordering::Less));
+    assert_eq!(compare(&Text("1e3".to_string()), &Int(999)), Some(Ordering::Greater));
+    assert_eq!(compare(&Text("abc".to_string()), &Int(1)), None);
+
+    // Text is ordered lexicographically and dates chronologically
+    assert_eq!(compare(&Text("apple".to_string()), &Text("banana".to_string())), Some(Ordering::Less));
+    let date = Date(parse_date("2022-12-01").unwrap());
+    assert_eq!(compare(&date, &Text("2022-11-30".to_string())), Some(Ordering::Greater));
+    assert_eq!(compare(&date, &Timestamp(parse_timestamp("2022-12-01 10:00").unwrap())), Some(Ordering::Less));
+}
@@ -0,0 +1,921 @@
+/* Secondary indexes created by "CREATE INDEX". Each index is B-tree stored in own paged file ("<index>.idx") placed in directory of database beside tables. Header of file holds definition of index (indexed table and columns, uniqueness) and other pages are nodes of tree.
+   Key of index entry is made of values of indexed columns (encoded so that order of bytes is same as order of values) followed by location of row in table file, so keys of all entries are different also in not unique index.
+   Entries are changed by "sql_storage" (by table writer and by "write_table") for each removed and saved row (each stored version of row has got own entry, so rows found by index are filtered by visibility). Entries are only removed from leaves (nodes aren't merged), so tree stays balanced and space is reused by next inserted entries */
+use std::{fs::{self, File}, io::{self, Read, Seek, SeekFrom}, path::{Path, PathBuf}, collections::{BTreeMap, HashMap, HashSet}, cmp::Ordering};
+
+use serde::{Deserialize, Serialize};
+use sqlparser::ast::{Expr, BinaryOperator, UnaryOperator};
+
+use super::sql_expr::{self, RowValue, ExprContext};
+use super::sql_json::{self, JsonSQLTable, JsonSQLTableColumnRow, SupportedSQLDataTypes};
+use super::sql_select::RowsSet;
+use super::sql_storage::{self, RowLocation, PAGE_SIZE};
+use super::{sql_transactions, sql_wal};
+
+/// Extension of index files
+pub const INDEX_EXTENSION: &str = "idx";
+
+const MAGIC: &[u8; 8] = b"WASTLIDX";
+/// Header page: 1. magic, 2. root page (u32), 3. count of pages (u32), 4. length of definition (u16) and 5. definition of index (JSON)
+const HEADER_DEFINITION: usize = 18;
+
+/// Kinds of node pages
+const LEAF_PAGE: u8 = 1;
+const INNER_PAGE: u8 = 2;
+/// Node page: 1. kind, 2. unused byte, 3. count of keys (u16), 4. next leaf or first child (u32) and then keys (each with length u16 and child u32 in inner node)
+const NODE_HEADER: usize = 8;
+/// Longest key which can be stored (each node has to hold few keys)
+const MAX_KEY: usize = (PAGE_SIZE - NODE_HEADER) / 4 - 6;
+/// Row location placed at end of key: page (u32) and slot (u16)
+const LOCATION_SIZE: usize = 6;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+/// Indexed column with its type at time when index was built (values of column are encoded by type)
+pub struct IndexColumn {
+    pub name: String,
+    pub d_type: SupportedSQLDataTypes,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+/// Represent definition of index
+pub struct IndexDefinition {
+    pub name: String,
+    pub table: String,
+    pub columns: Vec<IndexColumn>,
+    /// two rows can't have got same values of indexed columns (when none of them is NULL)
+    pub unique: bool,
+    /// index of PRIMARY KEY or UNIQUE key of table (it is created and removed together with key, rows with same values of key are found by it)
+    #[serde(default)]
+    pub key: bool,
+    /// index of key is index of PRIMARY KEY (otherwise index of UNIQUE key)
+    #[serde(default)]
+    pub primary: bool,
+}
+
+fn corrupted() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "Index file is corrupted")
+}
+
+/// Path to file of index placed in database
+pub fn index_path(database: &Path, index_name: &str) -> PathBuf {
+    database.join(format!("{}.{}", index_name, INDEX_EXTENSION))
+}
+
+/// Append value encoded so that encoded values are ordered in same way as values of their type (NULL is placed before all values). Each encoded value has got known end, so encoded values can be joined
+fn encode_value(value: &RowValue, key: &mut Vec<u8>) {
+    let signed = |bytes: &mut [u8]| bytes[0] ^= 0x80;
+    match value {
+        RowValue::Null => key.push(0),
+        value => {
+            key.push(1);
+            match value {
+                RowValue::Int(int) | RowValue::Decimal(int, _) => {
+                    let mut bytes = int.to_be_bytes();
+                    signed(&mut bytes);
+                    key.extend_from_slice(&bytes);
+                },
+                RowValue::Float(float) => {
+                    // Negative numbers have got all bits inverted (so bigger absolute value is placed earlier). Negative zero is equal to zero
+                    let bits = if *float == 0.0 { 0 } else { float.to_bits() };
+                    let bits = if bits >> 63 == 1 { !bits } else { bits | 1 << 63 };
+                    key.extend_from_slice(&bits.to_be_bytes());
+                },
+                RowValue::Boolean(boolean) => key.push(*boolean as u8),
+                RowValue::Date(date) => {
+                    let mut bytes = chrono::Datelike::num_days_from_ce(date).to_be_bytes();
+                    signed(&mut bytes);
+                    key.extend_from_slice(&bytes);
+                },
+                RowValue::Timestamp(timestamp) => {
+                    let mut bytes = timestamp.timestamp().to_be_bytes();
+                    signed(&mut bytes);
+                    key.extend_from_slice(&bytes);
+                    key.extend_from_slice(&timestamp.timestamp_subsec_nanos().to_be_bytes());
+                },
+                RowValue::Text(text) => {
+                    // Zero bytes of text are escaped and text is ended by 2 zero bytes (so shorter text is placed before text which it begins)
+                    for byte in text.as_bytes() {
+                        key.push(*byte);
+                        if *byte == 0 {
+                            key.push(0xFF);
+                        };
+                    }
+                    key.extend_from_slice(&[0, 0]);
+                },
+                RowValue::Null => unreachable!()
+            }
+        }
+    }
+}
+
+fn values_key(values: &[RowValue]) -> Vec<u8> {
+    let mut key = vec![];
+    for value in values {
+        encode_value(value, &mut key);
+    }
+    key
+}
+
+fn entry_key(values: &[RowValue], (page, slot): RowLocation) -> Vec<u8> {
+    let mut key = values_key(values);
+    key.extend_from_slice(&page.to_be_bytes());
+    key.extend_from_slice(&(slot as u16).to_be_bytes());
+    key
+}
+
+fn entry_location(key: &[u8]) -> RowLocation {
+    let location = &key[key.len() - LOCATION_SIZE..];
+    (u32::from_be_bytes(location[..4].try_into().unwrap()), u16::from_be_bytes(location[4..].try_into().unwrap()) as usize)
+}
+
+impl IndexDefinition {
+    /// Values of indexed columns of row (converted to types of columns)
+    fn row_values(&self, row: &[JsonSQLTableColumnRow]) -> Vec<RowValue> {
+        self.columns
+            .iter()
+            .map(|column| {
+                row.iter()
+                    .find(|cell| cell.col == column.name)
+                    .map_or(RowValue::Null, |cell| cell.value.clone().of_type(&column.d_type))
+            })
+            .collect::<Vec<_>>()
+    }
+
+    /// Whether index can be kept for table (all indexed columns exist) and whether its entries have to be built again (type of some column has been changed)
+    fn state_for(&self, table: &JsonSQLTable) -> Option<bool> {
+        let mut rebuild = false;
+        for column in &self.columns {
+            let table_column = table.columns.iter().find(|table_column| table_column.name == column.name)?;
+            rebuild |= table_column.d_type != column.d_type;
+        }
+        Some(rebuild)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Node of tree. Key of inner node is first key of subtree placed after it (children has got one item more than keys)
+enum Node {
+    Leaf { keys: Vec<Vec<u8>>, next: u32 },
+    Inner { keys: Vec<Vec<u8>>, children: Vec<u32> },
+}
+
+impl Node {
+    fn decode(page: &[u8]) -> io::Result<Node> {
+        let count = u16::from_le_bytes([page[2], page[3]]) as usize;
+        let first = u32::from_le_bytes(page[4..8].try_into().unwrap());
+        let inner = match page[0] {
+            LEAF_PAGE => false,
+            INNER_PAGE => true,
+            _ => return Err(corrupted())
+        };
+
+        let mut keys = Vec::with_capacity(count);
+        let mut children = vec![first];
+        let mut offset = NODE_HEADER;
+        for _ in 0..count {
+            let length = u16::from_le_bytes(page.get(offset..offset + 2).ok_or_else(corrupted)?.try_into().unwrap()) as usize;
+            keys.push(page.get(offset + 2..offset + 2 + length).ok_or_else(corrupted)?.to_vec());
+            offset += 2 + length;
+            if inner {
+                children.push(u32::from_le_bytes(page.get(offset..offset + 4).ok_or_else(corrupted)?.try_into().unwrap()));
+                offset += 4;
+            };
+        }
+
+        Ok(if inner { Node::Inner { keys, children } } else { Node::Leaf { keys, next: first } })
+    }
+
+    fn keys(&self) -> &Vec<Vec<u8>> {
+        match self {
+            Node::Leaf { keys, .. } | Node::Inner { keys, .. } => keys
+        }
+    }
+
+    fn size(&self) -> usize {
+        let child_size = if matches!(self, Node::Inner { .. }) { 4 } else { 0 };
+        NODE_HEADER + self.keys().iter().map(|key| 2 + key.len() + child_size).sum::<usize>()
+    }
+
+    /// Position from which keys are moved to new node when node is split (keys before it take about half of node)
+    fn split_position(&self) -> usize {
+        let child_size = if matches!(self, Node::Inner { .. }) { 4 } else { 0 };
+        let keys = self.keys();
+        let half = (self.size() - NODE_HEADER) / 2;
+        let mut size = 0;
+        let position = keys.iter().position(|key| {
+            size += 2 + key.len() + child_size;
+            size > half
+        }).unwrap_or(keys.len());
+        // Both nodes get at least one key (inner node gives one key to its parent)
+        position.clamp(1, keys.len() - if matches!(self, Node::Inner { .. }) { 2 } else { 1 })
+    }
+
+    /// Node which doesn't fit into page isn't encoded (it would be truncated)
+    fn encode(&self) -> io::Result<Vec<u8>> {
+        if self.size() > PAGE_SIZE {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Node of index is larger than page"));
+        };
+
+        let mut page = vec![0; NODE_HEADER];
+        let (kind, first) = match self {
+            Node::Leaf { next, .. } => (LEAF_PAGE, *next),
+            Node::Inner { children, .. } => (INNER_PAGE, children[0])
+        };
+        page[0] = kind;
+        page[2..4].copy_from_slice(&(self.keys().len() as u16).to_le_bytes());
+        page[4..8].copy_from_slice(&first.to_le_bytes());
+
+        for (position, key) in self.keys().iter().enumerate() {
+            page.extend_from_slice(&(key.len() as u16).to_le_bytes());
+            page.extend_from_slice(key);
+            if let Node::Inner { children, .. } = self {
+                page.extend_from_slice(&children[position + 1].to_le_bytes());
+            };
+        }
+
+        page.resize(PAGE_SIZE, 0);
+        Ok(page)
+    }
+}
+
+/// Position of child of inner node in which key is placed
+fn child_position(keys: &[Vec<u8>], key: &[u8]) -> usize {
+    keys.partition_point(|separator| separator.as_slice() <= key)
+}
+
+/// Opened index file. Changed pages are kept in memory until they are written to file by "flush"
+struct IndexFile {
+    path: PathBuf,
+    /// None for file which hasn't been saved yet
+    file: Option<File>,
+    definition: IndexDefinition,
+    root: u32,
+    page_count: u32,
+    header_changed: bool,
+    changed_pages: BTreeMap<u32, Vec<u8>>,
+}
+
+impl IndexFile {
+    fn open(path: &Path) -> io::Result<IndexFile> {
+        let mut file = File::open(path)?;
+        let mut header = vec![0; PAGE_SIZE];
+        file.read_exact(&mut header)?;
+        if &header[..8] != MAGIC {
+            return Err(corrupted());
+        };
+
+        let definition_length = u16::from_le_bytes([header[16], header[17]]) as usize;
+        let definition = serde_json::from_slice(header.get(HEADER_DEFINITION..HEADER_DEFINITION + definition_length).ok_or_else(corrupted)?)?;
+        Ok(IndexFile {
+            path: path.to_path_buf(),
+            file: Some(file),
+            definition,
+            root: u32::from_le_bytes(header[8..12].try_into().unwrap()),
+            page_count: u32::from_le_bytes(header[12..16].try_into().unwrap()),
+            header_changed: false,
+            changed_pages: BTreeMap::new()
+        })
+    }
+
+    /// Index without entries (with empty leaf as root). Existing file is replaced when index is saved
+    fn create(path: &Path, definition: IndexDefinition) -> io::Result<IndexFile> {
+        let mut index = IndexFile { path: path.to_path_buf(), file: None, definition, root: 1, page_count: 2, header_changed: true, changed_pages: BTreeMap::new() };
+        index.write_node(1, &Node::Leaf { keys: vec![], next: 0 })?;
+        Ok(index)
+    }
+
+    fn read_node(&mut self, number: u32) -> io::Result<Node> {
+        if let Some(page) = self.changed_pages.get(&number) {
+            return Node::decode(page);
+        };
+        let file = match self.file.as_mut() {
+            Some(file) if number != 0 && number < self.page_count => file,
+            _ => return Err(corrupted())
+        };
+
+        let mut page = vec![0; PAGE_SIZE];
+        file.seek(SeekFrom::Start(number as u64 * PAGE_SIZE as u64))?;
+        file.read_exact(&mut page)?;
+        Node::decode(&page)
+    }
+
+    fn write_node(&mut self, number: u32, node: &Node) -> io::Result<()> {
+        self.changed_pages.insert(number, node.encode()?);
+        Ok(())
+    }
+
+    fn allocate_page(&mut self) -> u32 {
+        self.header_changed = true;
+        self.page_count += 1;
+        self.page_count - 1
+    }
+
+    /// Add key to subtree. When node has to be split then first key of new node and its page are returned
+    fn insert_into(&mut self, number: u32, key: Vec<u8>) -> io::Result<Option<(Vec<u8>, u32)>> {
+        let mut node = self.read_node(number)?;
+        match &mut node {
+            Node::Leaf { keys, .. } => {
+                let position = keys.partition_point(|stored| *stored < key);
+                keys.insert(position, key);
+            },
+            Node::Inner { keys, children } => {
+                let position = child_position(keys, &key);
+                if let Some((separator, page)) = self.insert_into(children[position], key)? {
+                    keys.insert(position, separator);
+                    children.insert(position + 1, page);
+                };
+            }
+        };
+
+        if node.size() <= PAGE_SIZE {
+            self.write_node(number, &node)?;
+            return Ok(None);
+        };
+
+        // Node is split by size of keys (so that both halves fit into page also when lengths of keys differ) and second half is moved to new page
+        let new_page = self.allocate_page();
+        let middle = node.split_position();
+        let (separator, new_node) = match &mut node {
+            Node::Leaf { keys, next } => {
+                let new_keys = keys.split_off(middle);
+                let new_node = Node::Leaf { keys: new_keys, next: *next };
+                *next = new_page;
+                (new_node.keys()[0].clone(), new_node)
+            },
+            Node::Inner { keys, children } => {
+                let new_keys = keys.split_off(middle + 1);
+                let separator = keys.pop().unwrap();
+                (separator, Node::Inner { keys: new_keys, children: children.split_off(middle + 1) })
+            }
+        };
+        self.write_node(number, &node)?;
+        self.write_node(new_page, &new_node)?;
+
+        Ok(Some((separator, new_page)))
+    }
+
+    fn insert(&mut self, key: Vec<u8>) -> io::Result<()> {
+        if key.len() > MAX_KEY {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Values are too long to be stored in index \"{}\"", self.definition.name)));
+        };
+
+        if let Some((separator, page)) = self.insert_into(self.root, key)? {
+            // Split root is placed under new root
+            let new_root = self.allocate_page();
+            self.write_node(new_root, &Node::Inner { keys: vec![separator], children: vec![self.root, page] })?;
+            self.root = new_root;
+        };
+        Ok(())
+    }
+
+    /// Leaf in which key is (or would be) placed
+    fn leaf(&mut self, key: &[u8]) -> io::Result<(u32, Node)> {
+        let mut number = self.root;
+        for _ in 0..self.page_count {
+            match self.read_node(number)? {
+                Node::Inner { keys, children } => number = children[child_position(&keys, key)],
+                leaf => return Ok((number, leaf))
+            }
+        }
+
+        Err(corrupted())
+    }
+
+    fn remove(&mut self, key: &[u8]) -> io::Result<()> {
+        if let (number, Node::Leaf { mut keys, next }) = self.leaf(key)? {
+            if let Ok(position) = keys.binary_search_by(|stored| stored.as_slice().cmp(key)) {
+                keys.remove(position);
+                self.write_node(number, &Node::Leaf { keys, next })?;
+            };
+        };
+        Ok(())
+    }
+
+    /// Keys not smaller than "lower" in order. Keys are visited until function returns false
+    fn scan(&mut self, lower: &[u8], mut visit: impl FnMut(&[u8]) -> bool) -> io::Result<()> {
+        let (_, mut node) = self.leaf(lower)?;
+        let mut visited_leaves = 0;
+        loop {
+            let next = match &node {
+                Node::Leaf { keys, next } => {
+                    for key in keys.iter().filter(|key| key.as_slice() >= lower) {
+                        if !visit(key) {
+                            return Ok(());
+                        };
+                    }
+                    *next
+                },
+                Node::Inner { .. } => return Err(corrupted())
+            };
+
+            visited_leaves += 1;
+            if next == 0 || visited_leaves > self.page_count {
+                return Ok(());
+            };
+            node = self.read_node(next)?;
+        }
+    }
+
+    /// Locations of rows whose entries have got given values
+    fn values_locations(&mut self, values_key: &[u8]) -> io::Result<Vec<RowLocation>> {
+        let mut locations = vec![];
+        self.scan(values_key, |key| {
+            let found = key.len() == values_key.len() + LOCATION_SIZE && key.starts_with(values_key);
+            if found {
+                locations.push(entry_location(key));
+            };
+            found
+        })?;
+        Ok(locations)
+    }
+
+    /// Add entry of row. Entry with same values as entry of live row (row which hasn't been removed, according to "live") can't be added to unique index (index of key describes duplicated values of key)
+    fn insert_row(&mut self, location: RowLocation, row: &[JsonSQLTableColumnRow], live: &mut dyn FnMut(RowLocation) -> io::Result<bool>) -> io::Result<()> {
+        let values = self.definition.row_values(row);
+        if self.definition.unique && !values.contains(&RowValue::Null) {
+            for same_location in self.values_locations(&values_key(&values))? {
+                if live(same_location)? {
+                    let definition = &self.definition;
+                    let reason = match definition.key {
+                        true => sql_json::duplicate_key(values, &sql_json::key_description(&definition.name, definition.primary, &definition.columns.iter().map(|column| column.name.clone()).collect::<Vec<_>>())),
+                        false => format!("Rows have got duplicated values of unique index \"{}\"", definition.name)
+                    };
+                    return Err(io::Error::new(io::ErrorKind::InvalidInput, reason));
+                };
+            }
+        };
+
+        self.insert(entry_key(&values, location))
+    }
+
+    /// Add entry of stored version of row (e.g: when index is built from all versions). Only version which holds its values is checked against entries of live rows with the same values
+    fn insert_version(&mut self, location: RowLocation, row: &[JsonSQLTableColumnRow], holds_values: bool, live: &mut dyn FnMut(RowLocation) -> io::Result<bool>) -> io::Result<()> {
+        match holds_values {
+            true => self.insert_row(location, row, live),
+            false => self.insert(entry_key(&self.definition.row_values(row), location))
+        }
+    }
+
+    fn encode_header(&self) -> io::Result<Vec<u8>> {
+        let definition = serde_json::to_vec(&self.definition)?;
+        if HEADER_DEFINITION + definition.len() > PAGE_SIZE {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Definition of index is too long"));
+        };
+
+        let mut header = vec![0; PAGE_SIZE];
+        header[..8].copy_from_slice(MAGIC);
+        header[8..12].copy_from_slice(&self.root.to_le_bytes());
+        header[12..16].copy_from_slice(&self.page_count.to_le_bytes());
+        header[16..18].copy_from_slice(&(definition.len() as u16).to_le_bytes());
+        header[HEADER_DEFINITION..HEADER_DEFINITION + definition.len()].copy_from_slice(&definition);
+        Ok(header)
+    }
+
+    /// Write changed pages to file (through write-ahead log). Not saved index replaces existing file
+    fn flush(&mut self) -> io::Result<()> {
+        if self.header_changed {
+            self.changed_pages.insert(0, self.encode_header()?);
+            self.header_changed = false;
+        };
+
+        let pages = std::mem::take(&mut self.changed_pages)
+            .into_iter()
+            .map(|(number, page)| (number as u64 * PAGE_SIZE as u64, page))
+            .collect::<Vec<_>>();
+        if self.file.is_none() {
+            // File created by first flush is read when next pages are changed
+            let content = pages.into_iter().flat_map(|(_, page)| page).collect::<Vec<_>>();
+            sql_wal::write_file(&self.path, &content)?;
+            self.file = Some(File::open(&self.path)?);
+            return Ok(());
+        };
+        sql_wal::write_at(&self.path, pages)
+    }
+
+    /// Write changed pages to file when many pages have been changed (e.g: when index of big table is built), so only part of index is kept in memory
+    fn write_changes(&mut self) -> io::Result<()> {
+        if self.changed_pages.len() >= sql_storage::CHANGED_PAGES_LIMIT {
+            self.flush()?;
+        };
+        Ok(())
+    }
+}
+
+/// All indexes of database (also when some index file can't be read)
+fn database_indexes(database: &Path) -> io::Result<Vec<IndexFile>> {
+    let mut indexes = vec![];
+    for entry in fs::read_dir(database)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|extension| extension == INDEX_EXTENSION) {
+            indexes.push(IndexFile::open(&path)?);
+        };
+    }
+
+    Ok(indexes)
+}
+
+/// Indexes of table placed in database
+fn table_indexes(database: &Path, table_name: &str) -> io::Result<Vec<IndexFile>> {
+    Ok(database_indexes(database)?.into_iter().filter(|index| index.definition.table == table_name).collect::<Vec<_>>())
+}
+
+/// Database and table name of table file
+fn table_location(table_path: &Path) -> io::Result<(&Path, &str)> {
+    match (table_path.parent(), table_path.file_stem().and_then(|name| name.to_str())) {
+        (Some(database), Some(table_name)) => Ok((database, table_name)),
+        _ => Err(io::Error::from(io::ErrorKind::InvalidInput))
+    }
+}
+
+/// Indexes of table which are changed together with table. Index is built again when its column has been changed (e.g: renamed or its type has been changed) and it is removed when its column has been removed.
+/// Each key of table (PRIMARY KEY or UNIQUE) has got own unique index, which is created when key is added and removed when key is removed
+pub struct TableIndexes {
+    /// index with information whether it is built again
+    indexes: Vec<(IndexFile, bool)>,
+}
+
+impl TableIndexes {
+    /// Open indexes of table whose stored definition is replaced by new definition
+    pub fn open(table_path: &Path, stored_definition: Option<&JsonSQLTable>, definition: &JsonSQLTable) -> io::Result<TableIndexes> {
+        let (database, table_name) = table_location(table_path)?;
+        if !database.exists() {
+            return Ok(TableIndexes { indexes: vec![] });
+        };
+
+        // Indexes of keys are found by columns of keys (name of key is changed e.g: with name of table)
+        let mut keys = definition.keys();
+        let mut indexes = vec![];
+        for index in table_indexes(database, table_name)? {
+            if index.definition.key {
+                match keys.iter().position(|(_, _, columns)| index.definition.columns.iter().map(|column| &column.name).eq(columns.iter())) {
+                    Some(position) => {
+                        let key_columns = keys[position].2.clone();
+                        keys.retain(|(_, _, columns)| *columns != key_columns);
+                    },
+                    None => {
+                        sql_wal::remove_file(&index.path)?;
+                        continue;
+                    }
+                };
+            };
+
+            // Entries of stored rows can't be found by index which doesn't match stored table
+            let stored_state = stored_definition.map_or(Some(false), |stored_definition| index.definition.state_for(stored_definition));
+            match (index.definition.state_for(definition), stored_state) {
+                (Some(false), Some(false)) => indexes.push((index, false)),
+                (Some(_), _) => {
+                    let columns = index.definition.columns
+                        .iter()
+                        .map(|column| IndexColumn { name: column.name.clone(), d_type: definition.get_column_type(&column.name).unwrap_or_else(|| column.d_type.clone()) })
+                        .collect::<Vec<_>>();
+                    indexes.push((IndexFile::create(&index.path, IndexDefinition { columns, ..index.definition })?, true));
+                },
+                (None, _) => sql_wal::remove_file(&index.path)?
+            };
+        }
+
+        // Index of added key is built from all rows of table (file name of index is unique, so it doesn't collide with indexes created by "CREATE INDEX")
+        for (name, primary, columns) in keys {
+            if indexes.iter().any(|(index, _)| index.definition.key && index.definition.columns.iter().map(|column| &column.name).eq(columns.iter())) {
+                continue;
+            };
+            let columns = match columns.iter().map(|column| definition.get_column_type(column).map(|d_type| IndexColumn { name: column.clone(), d_type })).collect::<Option<Vec<_>>>() {
+                Some(columns) => columns,
+                None => continue
+            };
+            let path = index_path(database, &format!("{}.{}", name, uuid::Uuid::new_v4().simple()));
+            indexes.push((IndexFile::create(&path, IndexDefinition { name, table: table_name.to_string(), columns, unique: true, key: true, primary })?, true));
+        }
+
+        Ok(TableIndexes { indexes })
+    }
+
+    /// Whether table has got indexes whose entries are changed with rows
+    pub fn is_maintained(&self) -> bool {
+        !self.indexes.is_empty()
+    }
+
+    /// Whether some index has to be built from all rows of table
+    pub fn is_rebuilt(&self) -> bool {
+        self.indexes.iter().any(|(_, rebuilt)| *rebuilt)
+    }
+
+    /// Add entry of row of table to indexes which are built again (entries of all stored versions of rows are added)
+    pub fn rebuild(&mut self, location: RowLocation, row: &[JsonSQLTableColumnRow], live: &mut dyn FnMut(RowLocation) -> io::Result<bool>) -> io::Result<()> {
+        for (index, _) in self.indexes.iter_mut().filter(|(_, rebuilt)| *rebuilt) {
+            let holds_values = live(location)?;
+            index.insert_version(location, row, holds_values, live)?;
+        }
+        Ok(())
+    }
+
+    /// Change entries for rows removed from locations and rows saved under locations (entries of all removed rows are removed before entries are added)
+    pub fn update<'a>(&mut self, removed: &[(RowLocation, Vec<JsonSQLTableColumnRow>)], saved: impl Iterator<Item = (RowLocation, &'a [JsonSQLTableColumnRow])> + Clone, live: &mut dyn FnMut(RowLocation) -> io::Result<bool>) -> io::Result<()> {
+        for (index, _) in self.indexes.iter_mut().filter(|(_, rebuilt)| !*rebuilt) {
+            for (location, row) in removed {
+                index.remove(&entry_key(&index.definition.row_values(row), *location))?;
+            }
+            for (location, row) in saved.clone() {
+                index.insert_row(location, row, live)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        for (index, _) in &mut self.indexes {
+            index.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Write changed pages of indexes in which many pages have been changed (see "IndexFile::write_changes")
+    pub fn write_changes(&mut self) -> io::Result<()> {
+        for (index, _) in &mut self.indexes {
+            index.write_changes()?;
+        }
+        Ok(())
+    }
+}
+
+/// Create index of table from database. Index is built from all rows of table (from all stored versions of rows, read page after page)
+pub fn create_index(database: &Path, definition: IndexDefinition, if_not_exists: bool) -> Result<(), String> {
+    let path = index_path(database, &definition.name);
+    if path.exists() {
+        return if if_not_exists { Ok(()) } else { Err(format!("Index \"{}\" already exists", definition.name)) };
+    };
+
+    let table_path = sql_storage::table_path(database, &definition.table);
+    let read_error = |_| format!("Couldn't read table \"{}\"", definition.table);
+    let mut versions = sql_storage::table_rows(&table_path).map_err(read_error)?;
+    if let Some(column) = definition.columns.iter().find(|column| versions.definition().get_column_type(&column.name).is_none()) {
+        return Err(format!("Column \"{}\" doesn't exist in table \"{}\"", column.name, definition.table));
+    };
+
+    // Version with the same values is found by index and read again only when index is unique
+    let visibility = sql_transactions::visibility();
+    let mut live = |location| sql_storage::read_versions_at(&table_path, &[location]).map(|versions| versions.first().is_some_and(|(_, version, _)| visibility.is_live(*version)));
+    let mut index = IndexFile::create(&path, definition.clone()).map_err(|err| err.to_string())?;
+    while let Some(version) = versions.next_version() {
+        let (location, version, row) = version.map_err(read_error)?;
+        index.insert_version(location, &row, visibility.is_live(version), &mut live).map_err(|err| err.to_string())?;
+        index.write_changes().map_err(|_| "Couldn't save index".to_string())?;
+    }
+    index.flush().map_err(|_| "Couldn't save index".to_string())
+}
+
+/// Remove index from database. Removing of not existing index is ignored only when "if_exists" is set
+pub fn drop_index(database: &Path, name: &str, if_exists: bool) -> Result<(), String> {
+    let path = index_path(database, name);
+    if !path.exists() {
+        return if if_exists { Ok(()) } else { Err(format!("Index \"{}\" doesn't exist", name)) };
+    };
+
+    sql_wal::remove_file(&path).map_err(|_| format!("Couldn't remove index \"{}\"", name))
+}
+
+/// Remove all indexes of removed table
+pub fn drop_table_indexes(database: &Path, table_name: &str) -> io::Result<()> {
+    for index in table_indexes(database, table_name)? {
+        sql_wal::remove_file(&index.path)?;
+    }
+    Ok(())
+}
+
+/// Change definitions of all indexes of table
+fn change_definitions(database: &Path, table_name: &str, change: impl Fn(&mut IndexDefinition)) -> io::Result<()> {
+    for mut index in table_indexes(database, table_name)? {
+        change(&mut index.definition);
+        index.header_changed = true;
+        index.flush()?;
+    }
+    Ok(())
+}
+
+/// Attach indexes of renamed table to its new name
+pub fn rename_table(database: &Path, table_name: &str, new_table_name: &str) -> io::Result<()> {
+    change_definitions(database, table_name, |definition| definition.table = new_table_name.to_string())
+}
+
+/// Reffer by indexes to renamed column by its new name
+pub fn rename_column(database: &Path, table_name: &str, column_name: &str, new_column_name: &str) -> io::Result<()> {
+    change_definitions(database, table_name, |definition| {
+        for column in definition.columns.iter_mut().filter(|column| column.name == column_name) {
+            column.name = new_column_name.to_string();
+        }
+    })
+}
+
+/// Check whether rows of table which would be saved have got different values of each unique index of table (rows with NULL value of indexed column are omitted)
+pub fn check_unique(table_path: &Path, table: &JsonSQLTable) -> Result<(), String> {
+    let (database, table_name) = table_location(table_path).map_err(|_| "Couldn't read indexes of table".to_string())?;
+    let indexes = table_indexes(database, table_name).map_err(|_| "Couldn't read indexes of table".to_string())?;
+
+    for index in indexes.iter().filter(|index| index.definition.unique) {
+        let mut keys = HashSet::new();
+        for row in table.rows.iter().flatten() {
+            let values = index.definition.row_values(row);
+            if !values.contains(&RowValue::Null) && !keys.insert(values_key(&values)) {
+                return Err(format!("Rows have got duplicated values of unique index \"{}\"", index.definition.name));
+            };
+        }
+    }
+
+    Ok(())
+}
+
+/// Locations of entries of index of key (with given columns) which have got the same values of key as rows (list for each row, empty for row with NULL value of key).
+/// Each stored version of row has got own entry, so versions found under locations still have to be checked by visibility
+pub fn key_locations(table_path: &Path, columns: &[String], rows: &[Vec<JsonSQLTableColumnRow>]) -> io::Result<Vec<Vec<RowLocation>>> {
+    let (database, table_name) = table_location(table_path)?;
+    let mut index = table_indexes(database, table_name)?
+        .into_iter()
+        .find(|index| index.definition.key && index.definition.columns.iter().map(|column| &column.name).eq(columns.iter()))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Index of key doesn't exist"))?;
+
+    rows.iter()
+        .map(|row| {
+            let values = index.definition.row_values(row);
+            if values.contains(&RowValue::Null) { Ok(vec![]) } else { index.values_locations(&values_key(&values)) }
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Default)]
+/// Values to which column is compared by condition
+struct ColumnBounds {
+    equal: Option<RowValue>,
+    lower: Option<RowValue>,
+    upper: Option<RowValue>,
+}
+
+/// Value of literal converted to type of column. None when value of column type isn't compared with column values in same way as literal (e.g: 1.5 with INT column)
+fn literal_value(expr: &Expr, d_type: &SupportedSQLDataTypes) -> Option<RowValue> {
+    let is_literal = match expr {
+        Expr::Value(_) | Expr::TypedString { .. } => true,
+        Expr::UnaryOp { op: UnaryOperator::Minus | UnaryOperator::Plus, expr } => matches!(**expr, Expr::Value(_)),
+        _ => false
+    };
+    if !is_literal {
+        return None;
+    };
+
+    let value = sql_expr::evaluate(expr, &[], &[], &ExprContext::default()).ok()?;
+    let column_value = value.clone().of_type(d_type);
+    let same = match (&value, &column_value) {
+        (RowValue::Null, _) | (_, RowValue::Null) => false,
+        (RowValue::Int(_) | RowValue::Float(_) | RowValue::Decimal(..), RowValue::Int(_) | RowValue::Float(_) | RowValue::Decimal(..)) | (RowValue::Text(_), RowValue::Date(_) | RowValue::Timestamp(_)) => sql_expr::compare(&value, &column_value) == Some(Ordering::Equal),
+        _ => value == column_value
+    };
+
+    if same { Some(column_value) } else { None }
+}
+
+/// Collect values to which columns are compared by condition (only comparisons joined by "AND" are taken)
+fn collect_bounds(condition: &Expr, rows_set: &RowsSet, bounds: &mut HashMap<String, ColumnBounds>) {
+    let column = |expr: &Expr| {
+        let position = match expr {
+            Expr::Identifier(ident) => sql_expr::column_position(&rows_set.columns, None, &ident.value),
+            Expr::CompoundIdentifier(idents) if idents.len() == 2 => sql_expr::column_position(&rows_set.columns, Some(&idents[0].value), &idents[1].value),
+            _ => Ok(None)
+        };
+        position.ok().flatten().map(|position| &rows_set.columns[position])
+    };
+
+    match condition {
+        Expr::Nested(expr) => collect_bounds(expr, rows_set, bounds),
+        Expr::BinaryOp { left, op: BinaryOperator::And, right } => {
+            collect_bounds(left, rows_set, bounds);
+            collect_bounds(right, rows_set, bounds);
+        },
+        Expr::BinaryOp { left, op, right } => {
+            // Literal can be placed on both sides of comparison
+            let (column, value, op) = match (column(left), column(right)) {
+                (Some(column), None) => (column, literal_value(right, &column.d_type), op.clone()),
+                (None, Some(column)) => (column, literal_value(left, &column.d_type), match op {
+                    BinaryOperator::Lt => BinaryOperator::Gt,
+                    BinaryOperator::LtEq => BinaryOperator::GtEq,
+                    BinaryOperator::Gt => BinaryOperator::Lt,
+                    BinaryOperator::GtEq => BinaryOperator::LtEq,
+                    op => op.clone()
+                }),
+                _ => return
+            };
+            let column_bounds = bounds.entry(column.name.clone()).or_default();
+            match op {
+                BinaryOperator::Eq => column_bounds.equal = value.or(column_bounds.equal.take()),
+                BinaryOperator::Gt | BinaryOperator::GtEq => column_bounds.lower = value.or(column_bounds.lower.take()),
+                BinaryOperator::Lt | BinaryOperator::LtEq => column_bounds.upper = value.or(column_bounds.upper.take()),
+                _ => ()
+            };
+        },
+        Expr::Between { expr, negated: false, low, high } => {
+            if let Some(column) = column(expr) {
+                let (low, high) = (literal_value(low, &column.d_type), literal_value(high, &column.d_type));
+                let column_bounds = bounds.entry(column.name.clone()).or_default();
+                column_bounds.lower = low.or(column_bounds.lower.take());
+                column_bounds.upper = high.or(column_bounds.upper.take());
+            };
+        },
+        _ => ()
+    }
+}
+
+/// Locations of rows of table which can fulfil condition from 'WHERE', found by index of table whose first columns are compared with literals (equality for leading columns and range for next column).
+/// None when no index can be used. Found rows still have to be checked by whole condition
+pub fn candidate_locations(table_path: &Path, qualifier: &str, condition: &Expr) -> Option<Vec<RowLocation>> {
+    let (database, table_name) = table_location(table_path).ok()?;
+    let indexes = table_indexes(database, table_name).ok()?;
+    if indexes.is_empty() {
+        return None;
+    };
+
+    let definition = sql_storage::read_definition(table_path).ok()?;
+    let rows_set = RowsSet::from_json_table(&definition, qualifier);
+    let mut bounds = HashMap::new();
+    collect_bounds(condition, &rows_set, &mut bounds);
+
+    // Index with the most columns compared by equality (and then with range for next column) is used
+    let (mut index, equal_count, range) = indexes
+        .into_iter()
+        .filter(|index| index.definition.state_for(&definition) == Some(false))
+        .map(|index| {
+            let column_bounds = |position: usize| index.definition.columns.get(position).and_then(|column| bounds.get(&column.name));
+            let equal_count = (0..index.definition.columns.len()).take_while(|position| column_bounds(*position).is_some_and(|bounds| bounds.equal.is_some())).count();
+            let range = column_bounds(equal_count).filter(|bounds| bounds.lower.is_some() || bounds.upper.is_some()).cloned();
+            (index, equal_count, range)
+        })
+        .filter(|(_, equal_count, range)| *equal_count > 0 || range.is_some())
+        .max_by_key(|(_, equal_count, range)| (*equal_count, range.is_some()))?;
+
+    let equal_values = index.definition.columns[..equal_count]
+        .iter()
+        .map(|column| bounds[&column.name].equal.clone().unwrap())
+        .collect::<Vec<_>>();
+    let prefix = values_key(&equal_values);
+    let (lower, upper) = match range {
+        Some(ColumnBounds { lower, upper, .. }) => (
+            lower.map_or(prefix.clone(), |lower| [prefix.clone(), values_key(&[lower])].concat()),
+            upper.map(|upper| [prefix.clone(), values_key(&[upper])].concat()).or(if equal_count > 0 { Some(prefix) } else { None })
+        ),
+        None => (prefix.clone(), Some(prefix))
+    };
+
+    // Keys placed after upper bound which don't begin with it are greater than all values of range
+    let mut locations = vec![];
+    index.scan(&lower, |key| {
+        let in_range = upper.as_ref().is_none_or(|upper| key <= upper.as_slice() || key.starts_with(upper));
+        if in_range {
+            locations.push(entry_location(key));
+        };
+        in_range
+    }).ok()?;
+
+    Some(locations)
+}
+
+#[test]
+fn test_index_tree() {
+    let dir = std::env::temp_dir().join(format!("wastledb_index_{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&dir).unwrap();
+    sql_wal::set_data_path(&dir);
+    let path = index_path(&dir, "by_value");
+    let definition = IndexDefinition { name: "by_value".to_string(), table: "t".to_string(), columns: vec![IndexColumn { name: "value".to_string(), d_type: SupportedSQLDataTypes::INT }], unique: true, key: false, primary: false };
+
+    // Entries are kept in order of values after many splits of nodes
+    let mut index = IndexFile::create(&path, definition.clone()).unwrap();
+    let row = |value: i128| vec![JsonSQLTableColumnRow { col: "value".to_string(), value: RowValue::Int(value) }];
+    for value in (0..3000).map(|value| (value * 7919) % 3000 - 1500) {
+        index.insert_row(((value + 1500) as u32 + 1, 0), &row(value), &mut |_| Ok(true)).unwrap();
+    }
+    index.flush().unwrap();
+    let mut index = IndexFile::open(&path).unwrap();
+    assert!(matches!(index.read_node(index.root).unwrap(), Node::Inner { .. }));
+    assert!(index.insert_row((1, 1), &row(-1500), &mut |_| Ok(true)).is_err());
+    // Entry of removed version of row doesn't collide with new row
+    index.insert_row((1, 1), &row(-1500), &mut |_| Ok(false)).unwrap();
+
+    index.remove(&entry_key(&[RowValue::Int(-10)], (1491, 0))).unwrap();
+    let mut values = vec![];
+    index.scan(&values_key(&[RowValue::Int(-12)]), |key| {
+        values.push(entry_location(key).0 as i128 - 1501);
+        values.len() < 4
+    }).unwrap();
+    assert_eq!(values, vec![-12, -11, -9, -8]);
+
+    // Nodes with keys of different lengths are split by their size, node larger than page isn't encoded
+    let path = index_path(&dir, "by_name");
+    let definition = IndexDefinition { name: "by_name".to_string(), columns: vec![IndexColumn { name: "value".to_string(), d_type: SupportedSQLDataTypes::VARCHAR(None) }], unique: false, ..definition };
+    let mut index = IndexFile::create(&path, definition).unwrap();
+    for number in 0..400u32 {
+        let text = if number % 8 == 0 { "x".repeat(MAX_KEY - 20) } else { number.to_string() };
+        index.insert_row((number + 1, 0), &[JsonSQLTableColumnRow { col: "value".to_string(), value: RowValue::Text(text) }], &mut |_| Ok(true)).unwrap();
+    }
+    index.flush().unwrap();
+    let mut count = 0;
+    IndexFile::open(&path).unwrap().scan(&[], |_| { count += 1; true }).unwrap();
+    assert_eq!(count, 400);
+    assert!(Node::Leaf { keys: vec![vec![0; MAX_KEY]; 5], next: 0 }.encode().is_err());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
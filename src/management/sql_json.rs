@@ -1,13 +1,19 @@
 #![allow(unused)]
-use std::{fs, path::{Path, PathBuf}, collections::{HashMap, HashSet}, borrow::{BorrowMut, Borrow}};
+use std::{fs, path::{Path, PathBuf}, collections::{HashMap, HashSet}, borrow::{BorrowMut, Borrow}, cmp::Ordering};
 
 use serde::{self, Deserialize, Serialize};
 use sqlparser::{
     self,
-    ast::{ColumnOption, ColumnOptionDef, DataType, Statement, Expr, Value as SQLParserValue, BinaryOperator, Assignment, AlterTableOperation, AlterColumnOperation},
+    ast::{ColumnOption, ColumnOptionDef, DataType, ExactNumberInfo, Statement, Expr, Value as SQLParserValue, BinaryOperator, Assignment, AlterTableOperation, AlterColumnOperation},
 };
 use Statement::*;
 
+use super::sql_select::{self, SelectQuery, RowsSet};
+use super::sql_expr::{self, ExprContext, RowValue, RowsSetColumn};
+use super::sql_storage::{self, RowLocation};
+use super::sql_references::RowChange;
+use super::{sql_wal, sql_index, sql_transactions};
+
 /* Create table in json format */
 #[derive(Serialize, Deserialize, Debug, Clone)]
 /// Represent SQL table created from query in JSON format
@@ -18,10 +24,175 @@ pub struct JsonSQLTable {
     pub columns: Vec<JsonSQLTableColumn>,
     /// rows with columns. This value can be represented by None in moment when: table is now created or it doesn't have got any records inside
     pub rows: Option<Vec<Vec<JsonSQLTableColumnRow>>>,
+    /// constraints which concern whole table (e.g: key composed from many columns). Field is omitted in JSON file of table without such constraints
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub constraints: Option<Vec<SupportedSQLTableConstraints>>,
+}
+
+/// Value of column in row (NULL for column without cell in row)
+fn cell_value(row: &[JsonSQLTableColumnRow], column: &String) -> RowValue {
+    row.iter().find(|cell| &cell.col == column).map_or(RowValue::Null, |cell| cell.value.clone())
+}
+
+/// Key of table described in errors (with its name, kind and columns)
+pub fn key_description(name: &str, primary: bool, columns: &[String]) -> String {
+    format!("\"{}\" ({} ({}))", name, if primary { "PRIMARY KEY" } else { "UNIQUE" }, columns.join(", "))
+}
+
+/// Whether values of key are equal (compared as values, e.g: 1 and 1.0 are the same)
+fn same_values(values: &[RowValue], other_values: &[RowValue]) -> bool {
+    values.iter().zip(other_values).all(|(value, other_value)| sql_expr::compare(value, other_value) == Some(Ordering::Equal))
+}
+
+/// Error of row which has got the same values of key as other row
+pub fn duplicate_key(values: Vec<RowValue>, key: &str) -> String {
+    let values = values.into_iter().map(|value| sql_expr::value_text(value).unwrap_or_default()).collect::<Vec<_>>();
+    format!("Duplicate value ({}) for key {}", values.join(", "), key)
 }
 
 impl JsonSQLTable {
-    fn get_column_type(&self, column_name: &String) -> Option<SupportedSQLDataTypes> {
+    /// Parse table from content of its file. Each cell value is converted to type of its column, thanks to that also files in which values were saved as strings (before typed values were introduced) are readed correctly
+    pub fn from_json(json: &str) -> serde_json::Result<JsonSQLTable> {
+        let mut table = serde_json::from_str::<JsonSQLTable>(json)?;
+        let rows = table.rows.take();
+        table.rows = rows.map(|rows| rows.into_iter().map(|row| table.typed_row(row)).collect());
+
+        Ok(table)
+    }
+
+    /// Convert each cell value of row read from file to type of its column
+    pub fn typed_row(&self, mut row: Vec<JsonSQLTableColumnRow>) -> Vec<JsonSQLTableColumnRow> {
+        for cell in row.iter_mut() {
+            if let Some(column) = self.columns.iter().find(|column| column.name == cell.col) {
+                cell.value = std::mem::replace(&mut cell.value, RowValue::Null).of_type(&column.d_type);
+            }
+        }
+
+        row
+    }
+
+    /// Obtain all table keys (defined as column constraint or as table constraint) as list with: 1. key name, 2. whether key is PRIMARY KEY, 3. key columns
+    pub fn keys(&self) -> Vec<(String, bool, Vec<String>)> {
+        let mut keys = vec![] as Vec<(String, bool, Vec<String>)>;
+
+        for column in &self.columns {
+            for constraint in column.constraints.iter().flatten() {
+                match constraint {
+                    SupportedSQLColumnConstraints::PRIMARY_KEY => keys.push((SupportedSQLTableConstraints::key_name(&self.name, true, std::slice::from_ref(&column.name)), true, vec![column.name.clone()])),
+                    SupportedSQLColumnConstraints::UNIQUE => keys.push((SupportedSQLTableConstraints::key_name(&self.name, false, std::slice::from_ref(&column.name)), false, vec![column.name.clone()])),
+                    _ => ()
+                }
+            }
+        };
+
+        for constraint in self.constraints.iter().flatten() {
+            match constraint {
+                SupportedSQLTableConstraints::PRIMARY_KEY(name, columns) => keys.push((name.clone(), true, columns.clone())),
+                SupportedSQLTableConstraints::UNIQUE(name, columns) => keys.push((name.clone(), false, columns.clone())),
+                _ => ()
+            }
+        };
+
+        keys
+    }
+
+    /// Check whether table rows fullfil table constraints. When some constraint is violated then its description (with violated constraint name) is returned as Err
+    pub fn check_constraints(&self) -> Result<(), String> {
+        let rows = self.rows.as_deref().unwrap_or_default();
+
+        // Column with "NOT NULL" constraint can't have got NULL value in any row
+        for column in &self.columns {
+            let not_null = column.constraints.iter().flatten().any(|constraint| matches!(constraint, SupportedSQLColumnConstraints::NOT_NULL));
+
+            if not_null && rows.iter().any(|row| cell_value(row, &column.name) == RowValue::Null) {
+                return Err(format!("Column \"{}\" can't be NULL because of its NOT NULL constraint", column.name));
+            };
+        };
+
+        // Rows can't have got same values in key columns. Values of PRIMARY KEY can't be NULL, but UNIQUE key can have got NULL in many rows (row with NULL in key doesn't conflict with other rows)
+        for (name, primary, columns) in self.keys() {
+            let key = key_description(&name, primary, &columns);
+            let mut keys_values = vec![];
+
+            for row in rows {
+                let values = columns.iter().map(|column| cell_value(row, column)).collect::<Vec<_>>();
+
+                if let Some(position) = values.iter().position(|value| *value == RowValue::Null) {
+                    if primary {
+                        return Err(format!("Column \"{}\" of key {} can't be NULL", columns[position], key));
+                    };
+                    continue;
+                };
+                keys_values.push(values);
+            }
+
+            // Values of key are sorted, so the same values are placed next to each other
+            keys_values.sort_by(|values, other_values| values.iter().zip(other_values).map(|(value, other_value)| sql_expr::compare(value, other_value).unwrap_or(Ordering::Equal)).find(|ordering| ordering.is_ne()).unwrap_or(Ordering::Equal));
+            if let Some(position) = keys_values.windows(2).position(|pair| same_values(&pair[0], &pair[1])) {
+                return Err(duplicate_key(keys_values.swap_remove(position), &key));
+            };
+        };
+
+        // Expression of CHECK constraint can't be false for any row (NULL result doesn't violate constraint)
+        let rows_set = RowsSet::from_json_table(self, &self.name);
+        for constraint in self.constraints.iter().flatten() {
+            if let SupportedSQLTableConstraints::CHECK(name, expression, _) = constraint {
+                let check = format!("\"{}\" (CHECK ({}))", name, expression);
+                let expr = sql_expr::parse_expression(expression).map_err(|_| format!("Couldn't read expression of check constraint {}", check))?;
+
+                for row in &rows_set.rows {
+                    match sql_expr::evaluate(&expr, &rows_set.columns, row, &ExprContext::default()) {
+                        Ok(RowValue::Boolean(true) | RowValue::Null) => (),
+                        Ok(RowValue::Boolean(false)) => return Err(format!("Row violates check constraint {}", check)),
+                        _ => return Err(format!("Couldn't compute check constraint {} for row", check))
+                    }
+                }
+            }
+        };
+
+        Ok(())
+    }
+
+    /// Check whether table constraints are correct: keys (and foreign keys) must be created from existing columns, table can have got only one PRIMARY KEY,
+    /// constraints names must be unique, CHECK expressions must be computable for table rows (e.g: they can't reffer to not existing columns) and AUTO_INCREMENT can be attached only to INT column
+    pub fn check_schema(&self) -> Result<(), ()> {
+        let auto_increment = |column: &JsonSQLTableColumn| column.constraints.iter().flatten().any(|constraint| matches!(constraint, SupportedSQLColumnConstraints::AUTO_INCREMENT(_)));
+        if self.columns.iter().any(|column| auto_increment(column) && column.d_type != SupportedSQLDataTypes::INT) {
+            return Err(());
+        };
+
+        let keys = self.keys();
+        let foreign_keys = self.constraints
+            .iter()
+            .flatten()
+            .filter(|constraint| matches!(constraint, SupportedSQLTableConstraints::FOREIGN_KEY(..)))
+            .map(|constraint| (constraint.name(), constraint.columns()));
+        let mut constraints_names = HashSet::new();
+        for (name, columns) in keys.iter().map(|(name, _, columns)| (name, columns)).chain(foreign_keys) {
+            if columns.is_empty() || columns.iter().any(|column| self.get_column_type(column).is_none()) || !constraints_names.insert(name) {
+                return Err(());
+            }
+        };
+        if keys.iter().filter(|(_, primary, _)| *primary).count() > 1 {
+            return Err(());
+        };
+
+        // Expression is computed for row with NULL in each column
+        let rows_set = RowsSet::from_json_table(self, &self.name);
+        let null_row = vec![RowValue::Null; rows_set.columns.len()];
+        for constraint in self.constraints.iter().flatten() {
+            if let SupportedSQLTableConstraints::CHECK(name, expression, _) = constraint {
+                let expr = sql_expr::parse_expression(expression)?;
+                if !constraints_names.insert(name) || sql_expr::evaluate(&expr, &rows_set.columns, &null_row, &ExprContext::default()).is_err() {
+                    return Err(());
+                }
+            }
+        };
+
+        Ok(())
+    }
+
+    pub fn get_column_type(&self, column_name: &String) -> Option<SupportedSQLDataTypes> {
         let mut col_type = None as Option<SupportedSQLDataTypes>;
         
         for column in &self.columns {
@@ -35,10 +206,12 @@ impl JsonSQLTable {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[allow(clippy::upper_case_acronyms)]
 /// Represent all supported SQL collumn types in this database
 pub enum SupportedSQLDataTypes {
     INT,
     FLOAT,
+    DECIMAL(u8, u8), // 1. precision (count of all digits, maximum 38), 2. scale (count of digits after decimal point)
     TEXT,
     VARCHAR(Option<u16>), // can store maximum 65_535 bytes
     LONGTEXT,
@@ -54,9 +227,10 @@ pub trait ConvertFromSupportedDataTypeToSqlParserDataType {
     fn convert_to(supported_data_type: &SupportedSQLDataTypes) -> Option<DataType> {
         use SupportedSQLDataTypes::*;
         match supported_data_type {
-            // Currently no support in backward convertion for "LONGTEXT" and for "NULL" datatypes
+            // Currently no support in backward convertion for "NULL" datatype
             INT => Some(DataType::Int(Some(u64::MAX))),
             FLOAT => Some(DataType::Float(Some(u64::MAX))),
+            DECIMAL(precision, scale) => Some(DataType::Decimal(sqlparser::ast::ExactNumberInfo::PrecisionAndScale(*precision as u64, *scale as u64))),
             TEXT => Some(DataType::Text),
             VARCHAR(len) => 
                 Some(
@@ -71,6 +245,7 @@ pub trait ConvertFromSupportedDataTypeToSqlParserDataType {
             DATE => Some(DataType::Date),
             DATETIMESTAMP => Some(DataType::Timestamp(sqlparser::ast::TimezoneInfo::None)),
             BOOLEAN => Some(DataType::Boolean),
+            LONGTEXT => Some(DataType::Custom(sqlparser::ast::ObjectName(vec![sqlparser::ast::Ident::new("LONGTEXT")]))),
             _ => None
         }
     }
@@ -98,9 +273,30 @@ pub trait ConvertSQLParserTypesToSupported {
             Int(_width) => { // TODO: add support for Int width
                 Some(SupportedSQLDataTypes::INT)
             },
+            Integer(_) | BigInt(_) | SmallInt(_) => Some(SupportedSQLDataTypes::INT),
             Text => {
                 Some(SupportedSQLDataTypes::TEXT)
             },
+            Float(_) | Real | Double | DoublePrecision => Some(SupportedSQLDataTypes::FLOAT),
+            // "DECIMAL" is "DECIMAL(38, 0)" and "DECIMAL(p)" is "DECIMAL(p, 0)". Scale can't be greater than precision
+            Decimal(info) => {
+                let (precision, scale) = match info {
+                    ExactNumberInfo::None => (sql_expr::DECIMAL_MAX_PRECISION as u64, 0),
+                    ExactNumberInfo::Precision(precision) => (*precision, 0),
+                    ExactNumberInfo::PrecisionAndScale(precision, scale) => (*precision, *scale)
+                };
+
+                if (1..=sql_expr::DECIMAL_MAX_PRECISION as u64).contains(&precision) && scale <= precision {
+                    Some(SupportedSQLDataTypes::DECIMAL(precision as u8, scale as u8))
+                }
+                else {
+                    None
+                }
+            },
+            Boolean => Some(SupportedSQLDataTypes::BOOLEAN),
+            Date => Some(SupportedSQLDataTypes::DATE),
+            Timestamp(_) | Datetime => Some(SupportedSQLDataTypes::DATETIMESTAMP),
+            Custom(name) if name.to_string().eq_ignore_ascii_case("LONGTEXT") => Some(SupportedSQLDataTypes::LONGTEXT),
             _ => None // unsuported
         }
     }
@@ -135,7 +331,7 @@ pub trait ConvertSQLParserTypesToSupported {
                                 Err(())
                             }
                         },
-                        DataType::Int(_) | DataType::Float(_) | DataType::Boolean => Ok(()),
+                        DataType::Int(_) | DataType::Float(_) | DataType::Decimal(_) | DataType::Boolean => Ok(()),
                         _ => {
                             // Check supercases when cell value has been attached and cell value fullfils rules convertion to "to"
                             if let Some(cell_value) = cell_value {
@@ -152,7 +348,28 @@ pub trait ConvertSQLParserTypesToSupported {
                 },
                 DataType::Text => {
                     match from {
-                        DataType::Text | DataType::Int(_) | DataType::Float(_) | DataType::Varchar(_) | DataType::Boolean => Ok(()),
+                        DataType::Text | DataType::Int(_) | DataType::Float(_) | DataType::Decimal(_) | DataType::Varchar(_) | DataType::Boolean => Ok(()),
+                        _ => Err(())
+                    }
+                },
+                DataType::Decimal(_) => {
+                    // Each number (and text which is a number) can be converted to decimal when it fits into its precision after rounding to its scale
+                    match (from, cell_value, DataType::convert(&to)) {
+                        (DataType::Int(_) | DataType::Float(_) | DataType::Decimal(_) | DataType::Text | DataType::Varchar(_), None, Some(_)) => Ok(()),
+                        (DataType::Int(_) | DataType::Float(_) | DataType::Decimal(_) | DataType::Text | DataType::Varchar(_), Some(cell_value), Some(to_type)) => {
+                            match RowValue::Text(cell_value.clone()).of_type(&to_type) {
+                                RowValue::Decimal(..) => Ok(()),
+                                _ => Err(())
+                            }
+                        },
+                        _ => Err(())
+                    }
+                },
+                DataType::Int(_) => {
+                    // Decimal can be converted to integer only when it hasn't got fraction
+                    match (from, cell_value.and_then(|cell_value| sql_expr::parse_decimal(cell_value))) {
+                        (DataType::Decimal(_), None) => Ok(()),
+                        (DataType::Decimal(_), Some((digits, scale))) if 10_i128.checked_pow(scale).map_or(digits == 0, |divisor| digits % divisor == 0) => Ok(()),
                         _ => Err(())
                     }
                 },
@@ -169,10 +386,165 @@ pub enum SupportedSQLColumnConstraints {
     PRIMARY_KEY,
     FOREGIN_KEY,
     DEFAULT(String),
+    DEFAULT_EXPRESSION(String), // 1. SQL expression which is computed each time when default value is needed (e.g: CURRENT_TIMESTAMP)
+    AUTO_INCREMENT(i128), // 1. last value assigned to column (0 when any value wasn't assigned yet)
     NOT_NULL,
     UNIQUE,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+/// Represent supported constraints defined for whole table (in CREATE TABLE after columns). Each of them has got name (given in query or created from table and columns names)
+pub enum SupportedSQLTableConstraints {
+    PRIMARY_KEY(String, Vec<String>), // 1. constraint name, 2. key columns
+    UNIQUE(String, Vec<String>), // 1. constraint name, 2. key columns
+    FOREIGN_KEY(
+        String,
+        Vec<String>,
+        String,
+        Vec<String>,
+        SupportedSQLReferentialActions,
+        SupportedSQLReferentialActions
+    ), // 1. constraint name, 2. columns, 3. referenced table name, 4. referenced columns (empty when key reffers to PRIMARY KEY of referenced table), 5. action ON DELETE, 6. action ON UPDATE
+    CHECK(String, String, Vec<String>), // 1. constraint name, 2. SQL expression which can't be false for any row, 3. columns used in expression
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+/// Represent supported actions performed on rows which reffer (by FOREIGN KEY) to deleted or updated row
+pub enum SupportedSQLReferentialActions {
+    CASCADE,
+    SET_NULL,
+    RESTRICT,
+    NO_ACTION,
+}
+
+impl SupportedSQLReferentialActions {
+    /// Convert action from sqlparser (None when action isn't given in query). None for unsupported actions
+    pub fn from_referential_action(action: Option<&sqlparser::ast::ReferentialAction>) -> Option<SupportedSQLReferentialActions> {
+        use sqlparser::ast::ReferentialAction;
+        match action {
+            Some(ReferentialAction::Cascade) => Some(SupportedSQLReferentialActions::CASCADE),
+            Some(ReferentialAction::SetNull) => Some(SupportedSQLReferentialActions::SET_NULL),
+            Some(ReferentialAction::Restrict) => Some(SupportedSQLReferentialActions::RESTRICT),
+            Some(ReferentialAction::NoAction) | None => Some(SupportedSQLReferentialActions::NO_ACTION),
+            _ => None
+        }
+    }
+}
+
+impl SupportedSQLTableConstraints {
+    /// Convert table constraint from sqlparser to supported constraint for table with name "table_name" and columns "table_columns". None for unsupported constraints
+    pub fn from_table_constraint(constraint: &sqlparser::ast::TableConstraint, table_name: &str, table_columns: &[String]) -> Option<SupportedSQLTableConstraints> {
+        match constraint {
+            sqlparser::ast::TableConstraint::Unique { name, columns, is_primary } => {
+                let columns = columns.iter().map(|column| column.value.clone()).collect::<Vec<_>>();
+                let name = name.as_ref().map_or_else(|| SupportedSQLTableConstraints::key_name(table_name, *is_primary, &columns), |name| name.value.clone());
+
+                if *is_primary {
+                    Some(SupportedSQLTableConstraints::PRIMARY_KEY(name, columns))
+                }
+                else {
+                    Some(SupportedSQLTableConstraints::UNIQUE(name, columns))
+                }
+            },
+            sqlparser::ast::TableConstraint::ForeignKey { name, columns, foreign_table, referred_columns, on_delete, on_update } => {
+                let columns = columns.iter().map(|column| column.value.clone()).collect::<Vec<_>>();
+                let name = name.as_ref().map_or_else(|| format!("{}_{}_fkey", table_name, columns.join("_")), |name| name.value.clone());
+
+                Some(SupportedSQLTableConstraints::FOREIGN_KEY(
+                    name,
+                    columns,
+                    foreign_table.0.last()?.value.clone(),
+                    referred_columns.iter().map(|column| column.value.clone()).collect(),
+                    SupportedSQLReferentialActions::from_referential_action(on_delete.as_ref())?,
+                    SupportedSQLReferentialActions::from_referential_action(on_update.as_ref())?
+                ))
+            },
+            sqlparser::ast::TableConstraint::Check { name, expr } => {
+                let expression = expr.to_string();
+                let columns = SupportedSQLTableConstraints::expression_columns(&expression, table_columns);
+                // Check which uses only one column is named after it (e.g: "products_price_check")
+                let name = name.as_ref().map_or_else(|| match columns.as_slice() {
+                    [column] => format!("{}_{}_check", table_name, column),
+                    _ => format!("{}_check", table_name)
+                }, |name| name.value.clone());
+
+                Some(SupportedSQLTableConstraints::CHECK(name, expression, columns))
+            },
+            _ => None
+        }
+    }
+
+    /// Columns from "table_columns" which are used in expression
+    fn expression_columns(expression: &str, table_columns: &[String]) -> Vec<String> {
+        use sqlparser::{dialect::AnsiDialect, tokenizer::{Token, Tokenizer}};
+
+        let words = Tokenizer::new(&AnsiDialect {}, expression)
+            .tokenize()
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|token| match token {
+                Token::Word(word) => Some(word.value),
+                _ => None
+            })
+            .collect::<HashSet<_>>();
+
+        table_columns.iter().filter(|column| words.contains(*column)).cloned().collect()
+    }
+
+    /// Name of key which hasn't got name given in query (e.g: "users_pkey" for PRIMARY KEY and "users_email_key" for UNIQUE key)
+    pub fn key_name(table_name: &str, primary: bool, columns: &[String]) -> String {
+        if primary {
+            format!("{}_pkey", table_name)
+        }
+        else {
+            format!("{}_{}_key", table_name, columns.join("_"))
+        }
+    }
+
+    /// Constraint name
+    pub fn name(&self) -> &String {
+        match self {
+            SupportedSQLTableConstraints::PRIMARY_KEY(name, _) | SupportedSQLTableConstraints::UNIQUE(name, _) | SupportedSQLTableConstraints::FOREIGN_KEY(name, ..) | SupportedSQLTableConstraints::CHECK(name, ..) => name
+        }
+    }
+
+    /// Columns to which constraint reffers
+    pub fn columns(&self) -> &Vec<String> {
+        match self {
+            SupportedSQLTableConstraints::PRIMARY_KEY(_, columns) | SupportedSQLTableConstraints::UNIQUE(_, columns) | SupportedSQLTableConstraints::FOREIGN_KEY(_, columns, ..) | SupportedSQLTableConstraints::CHECK(_, _, columns) => columns
+        }
+    }
+
+    /// Change name of column to which constraint reffers (after column name has been changed)
+    pub fn rename_column(&mut self, old_name: &str, new_name: &str) {
+        match self {
+            SupportedSQLTableConstraints::PRIMARY_KEY(_, columns) | SupportedSQLTableConstraints::UNIQUE(_, columns) | SupportedSQLTableConstraints::FOREIGN_KEY(_, columns, ..) => {
+                columns.iter_mut().filter(|column| *column == old_name).for_each(|column| *column = new_name.to_string())
+            },
+            SupportedSQLTableConstraints::CHECK(_, expression, columns) => {
+                use sqlparser::{dialect::AnsiDialect, tokenizer::{Token, Tokenizer}};
+
+                // Column name is replaced in expression tokens so other parts of expression (e.g: text values) stay unchanged
+                if let Ok(tokens) = Tokenizer::new(&AnsiDialect {}, expression).tokenize() {
+                    *expression = tokens
+                        .into_iter()
+                        .map(|token| match token {
+                            Token::Word(mut word) if word.value == old_name => {
+                                word.value = new_name.to_string();
+                                Token::Word(word).to_string()
+                            },
+                            token => token.to_string()
+                        })
+                        .collect();
+                };
+                columns.iter_mut().filter(|column| *column == old_name).for_each(|column| *column = new_name.to_string())
+            }
+        }
+    }
+}
+
 /// Trait to easy and seamlessly convertion between SQLParser Options (in sqlparser and is equal concept to Constraint) stored benath "ColumnOption.option" as a "ColumnOption" enum to supported constraints by database
 pub trait ConvertSQLParserOptionsToSupportedConstraints {
     fn convert(option: sqlparser::ast::ColumnOptionDef) -> Option<SupportedSQLColumnConstraints> {
@@ -180,6 +552,20 @@ pub trait ConvertSQLParserOptionsToSupportedConstraints {
         // TODO: Add support for more constraints
         match option.option {
             NotNull => Some(SupportedSQLColumnConstraints::NOT_NULL),
+            Unique { is_primary: true } => Some(SupportedSQLColumnConstraints::PRIMARY_KEY),
+            Unique { is_primary: false } => Some(SupportedSQLColumnConstraints::UNIQUE),
+            Default(Expr::Value(value)) => { // default value is attached to columns omitted in "INSERT INTO table (columns) ..."
+                match value {
+                    SQLParserValue::SingleQuotedString(value) | SQLParserValue::DoubleQuotedString(value) | SQLParserValue::Number(value, _) => Some(SupportedSQLColumnConstraints::DEFAULT(value)),
+                    SQLParserValue::Boolean(value) => Some(SupportedSQLColumnConstraints::DEFAULT(value.to_string())),
+                    SQLParserValue::Null => Some(SupportedSQLColumnConstraints::DEFAULT_EXPRESSION("NULL".to_string())),
+                    _ => None
+                }
+            },
+            // "AUTO_INCREMENT" (and "AUTOINCREMENT") is specific for some dialects
+            DialectSpecific(tokens) if matches!(tokens.as_slice(), [sqlparser::tokenizer::Token::Word(word)] if ["AUTO_INCREMENT", "AUTOINCREMENT"].contains(&word.value.to_uppercase().as_str())) => Some(SupportedSQLColumnConstraints::AUTO_INCREMENT(0)),
+            // Other default values are expressions which don't reffer to columns (e.g: CURRENT_TIMESTAMP or -1)
+            Default(expr) if sql_expr::evaluate(&expr, &[], &[], &ExprContext::default()).is_ok() => Some(SupportedSQLColumnConstraints::DEFAULT_EXPRESSION(expr.to_string())),
             _ => None // for unsuppored options
         }
     }
@@ -196,20 +582,143 @@ pub struct JsonSQLTableColumn {
     pub constraints: Option<Vec<SupportedSQLColumnConstraints>>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+impl JsonSQLTableColumn {
+    /// Remove column constraints which match "predicate". Returns whether any constraint has been removed
+    fn remove_constraints(&mut self, predicate: impl Fn(&SupportedSQLColumnConstraints) -> bool) -> bool {
+        let constraints = self.constraints.take().unwrap_or_default();
+        let constraints_count = constraints.len();
+        let constraints = constraints.into_iter().filter(|constraint| !predicate(constraint)).collect::<Vec<_>>();
+        let removed = constraints.len() != constraints_count;
+
+        self.constraints = if !constraints.is_empty() { Some(constraints) } else { None };
+        removed
+    }
+
+    /// Assign next values of AUTO_INCREMENT column to rows which have got NULL in it. Counter of column is moved forward also by values given for column, thanks to that assigned values don't collide with them
+    fn assign_auto_increment(&mut self, rows: &mut [Vec<JsonSQLTableColumnRow>]) {
+        for constraint in self.constraints.iter_mut().flatten() {
+            if let SupportedSQLColumnConstraints::AUTO_INCREMENT(last_value) = constraint {
+                for cell in rows.iter_mut().flatten().filter(|cell| cell.col == self.name) {
+                    match cell.value {
+                        RowValue::Null => {
+                            *last_value += 1;
+                            cell.value = RowValue::Int(*last_value);
+                        },
+                        RowValue::Int(value) => *last_value = (*last_value).max(value),
+                        _ => ()
+                    }
+                }
+            }
+        };
+    }
+
+    /// Value which is attached to column when value for it isn't given in INSERT or when keyword DEFAULT is used instead of value. NULL for column without default value
+    pub fn default_value(&self) -> Result<RowValue, ()> {
+        for constraint in self.constraints.iter().flatten() {
+            match constraint {
+                SupportedSQLColumnConstraints::DEFAULT(value) => return Ok(RowValue::Text(value.clone()).of_type(&self.d_type)),
+                SupportedSQLColumnConstraints::DEFAULT_EXPRESSION(sql) => {
+                    let expr = sql_expr::parse_expression(sql)?;
+                    return Ok(sql_expr::evaluate(&expr, &[], &[], &ExprContext::default())?.of_type(&self.d_type));
+                },
+                _ => ()
+            }
+        };
+
+        Ok(RowValue::Null)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 /// Represent each row with data for "JsonSQLTable" struct
 pub struct JsonSQLTableColumnRow {
     /// To which column name (unique id) this row belongs to.
     /// Name is unuiqe id of column because only one column with same name can exists in whole table.
     /// When column name was changes the "col" value for each old name should be changed to new name 
     pub col: String,
-    /// Row value
-    pub value: Option<String>,
+    /// Row value (stored in JSON file under JSON type appropriate for it: number, boolean, string or null)
+    pub value: RowValue,
 }
 
 #[derive(Debug, PartialEq)]
 pub struct ProcessSQLRowField(pub String, pub SupportedSQLDataTypes); // 1. field value, 2. Field data type (only supported datatypes)
 
+impl ProcessSQLRowField {
+    /// Obtain field from literal value attached to query (e.g: 'text', -1.5, TRUE, NULL, DATE '2022-12-01'). None for expressions which aren't literals or for unsupported literals
+    pub fn from_literal(expr: &Expr) -> Option<ProcessSQLRowField> {
+        use SupportedSQLDataTypes as sup;
+        match expr {
+            Expr::Value(value) => {
+                match value {
+                    // strings are interpreted as "VARCHAR" (up to 65_535 characters) and longer as "TEXT"
+                    SQLParserValue::SingleQuotedString(stri) | SQLParserValue::DoubleQuotedString(stri) | SQLParserValue::EscapedStringLiteral(stri) => {
+                        let stri_data_type = if stri.len() > u16::MAX as usize { sup::TEXT } else { sup::VARCHAR(None) };
+                        Some(ProcessSQLRowField(stri.clone(), stri_data_type))
+                    },
+                    SQLParserValue::Number(num, _) => {
+                        let num_data_type = if num.parse::<i64>().is_ok() { sup::INT } else { sup::FLOAT };
+                        Some(ProcessSQLRowField(num.clone(), num_data_type))
+                    },
+                    SQLParserValue::Boolean(val) => Some(ProcessSQLRowField(val.to_string(), sup::BOOLEAN)),
+                    SQLParserValue::Null => Some(ProcessSQLRowField("null".to_string(), sup::NULL)),
+                    _ => None
+                }
+            },
+            // negative number
+            Expr::UnaryOp { op: sqlparser::ast::UnaryOperator::Minus, expr } => {
+                match ProcessSQLRowField::from_literal(expr)? {
+                    ProcessSQLRowField(num, num_data_type @ (sup::INT | sup::FLOAT)) if !num.starts_with('-') => Some(ProcessSQLRowField(format!("-{}", num), num_data_type)),
+                    _ => None
+                }
+            },
+            Expr::TypedString { data_type: DataType::Date, value } => Some(ProcessSQLRowField(value.clone(), sup::DATE)),
+            Expr::TypedString { data_type: DataType::Timestamp(_) | DataType::Datetime, value } => Some(ProcessSQLRowField(value.clone(), sup::DATETIMESTAMP)),
+            _ => None
+        }
+    }
+
+    /// Obtain field from value of column with type "d_type" (e.g: from row returned by SELECT query)
+    pub fn from_value(value: RowValue, d_type: &SupportedSQLDataTypes) -> ProcessSQLRowField {
+        match sql_expr::value_text(value) {
+            Some(value) => ProcessSQLRowField(value, d_type.clone()),
+            None => ProcessSQLRowField("null".to_string(), SupportedSQLDataTypes::NULL)
+        }
+    }
+
+    /// Convert field value to value which is stored in column with type "column_type".
+    /// Err(()) is returned when value can't be stored in column (e.g: text in "INT" column or incorrect date)
+    pub fn column_value(&self, column_type: &SupportedSQLDataTypes) -> Result<RowValue, ()> {
+        use SupportedSQLDataTypes::*;
+        let ProcessSQLRowField(value, value_type) = self;
+
+        match (column_type, value_type) {
+            (_, NULL) => Ok(RowValue::Null), // NULL can be attached to column of any type
+            (INT, INT) => value.trim().parse::<i64>().map(|int| RowValue::Int(int as i128)).map_err(|_| ()),
+            (FLOAT, INT | FLOAT | DECIMAL(..)) => {
+                match value.trim().parse::<f64>() {
+                    Ok(float) if float.is_finite() => Ok(RowValue::Float(float)),
+                    _ => Err(())
+                }
+            },
+            // Number is rounded to scale of column and must have got at most as many digits as precision of column
+            (DECIMAL(precision, scale), INT | FLOAT | DECIMAL(..)) => {
+                match RowValue::Text(value.clone()).of_type(&DECIMAL(*precision, *scale)) {
+                    decimal @ RowValue::Decimal(..) => Ok(decimal),
+                    _ => Err(())
+                }
+            },
+            (BOOLEAN, BOOLEAN | VARCHAR(_) | TEXT) => value.to_lowercase().parse::<bool>().map(RowValue::Boolean).map_err(|_| ()),
+            (DATE, DATE | VARCHAR(_) | TEXT | LONGTEXT) => sql_expr::parse_date(value).map(RowValue::Date).ok_or(()),
+            (DATETIMESTAMP, DATE | DATETIMESTAMP | VARCHAR(_) | TEXT | LONGTEXT) => sql_expr::parse_timestamp(value).map(RowValue::Timestamp).ok_or(()),
+            // Text columns accept any value which fits into them ("VARCHAR" can store up to 65_535 bytes, "TEXT" up to 16_777_215 bytes and "LONGTEXT" up to 4_294_967_295 bytes)
+            (VARCHAR(max_len), _) if value.len() <= max_len.unwrap_or(u16::MAX) as usize => Ok(RowValue::Text(value.clone())),
+            (TEXT, _) if value.len() <= 16_777_215 => Ok(RowValue::Text(value.clone())),
+            (LONGTEXT, _) if value.len() as u64 <= u32::MAX as u64 => Ok(RowValue::Text(value.clone())),
+            _ => Err(())
+        }
+    }
+}
+
 type TableName = String;
 type TablePath<'x> = &'x PathBuf;
 type ColumnName = String;
@@ -238,130 +747,15 @@ pub enum ProcessSQLSupportedQueries<'x> {
             SupportedSQLDataTypes,
             Option<Vec<SupportedSQLColumnConstraints>>,
         )>,
-    ), // 1. Table name, 2. Vector with table columns and characteristic for each column
+        Vec<SupportedSQLTableConstraints>
+    ), // 1. Table name, 2. Vector with table columns and characteristic for each column, 3. Constraints for whole table
     Truncate(TablePath<'x>),
-    Select(TablePath<'x>, ActionOnlyForTheseColumns, Option<Expr>), // path to table, 2. return results for specific record tuples can be all, 3. Select only these records
+    Select(SelectQuery), // 1. query with tables from "FROM" clause, projection, conditions, grouping, sorting and range of returned records
     Delete(TablePath<'x>, Option<Expr>), // Delete whole table records or only specific record
     Update(TablePath<'x>, Vec<Assignment>, Option<Expr>), // 1. TablePath, 2. Assigments, 3. Condition/s
     AlterTable(TablePath<'x>, AlterTableOperation)
 }
 
-#[derive(Debug, Clone)]
-/// Describe operation for row
-struct RowWhereOperation {
-    /// column name
-    column: Option<String>,
-    /// column value
-    value: Option<String>,
-    /// type is from "sqlparser" crate
-    op: BinaryOperator,
-    /// in match operation indicates whether operation has been successfullperformed
-    perf: Option<bool>
-}
-
-#[derive(Debug, Clone)]
-/// Store table row as prepared for actions with conditions
-struct RowOperationForm {
-    row: Vec<JsonSQLTableColumnRow>,
-    id: u128
-}
-
-/// Convert Conditions to more readable and oparatable form. Longer: Function which aim is convert Expr::BinOp to RowWhereOperation expression and put it into "operations_for_row" collection to facilitate performant 'WHERE' computing
-fn convert_binarop(expr: Expr, converted_list: &mut Vec<RowWhereOperation>) -> Result<(), ()> {
-    if let Expr::BinaryOp { left, op, right } = expr { // for parent
-        /// To convert expression witch doesn't rollup further to conjuction (And, Or) 
-        fn for_value_and_column(op_row_collection: &mut Vec<RowWhereOperation>, right: &Box<Expr>, left: &Box<Expr>, op: &BinaryOperator) -> Result<(), ()> {
-            let no_rollup_cond = RowWhereOperation {
-                column: { // column "name"
-                    match &**left {
-                        Expr::Identifier(d) => {
-                            Some(d.value.clone())
-                        },
-                        _ => return Err(()) // incorrect parsed condition
-                    }
-                },
-                op: op.clone(), // operation type like: Eq, NotEq, Less , ...
-                value: { // column "value"
-                    match &**right {
-                        Expr::Identifier(d) => {
-                            Some(d.value.clone())
-                        },
-                        Expr::Value(value) => {
-                            match value {
-                                SQLParserValue::SingleQuotedString(sval) | SQLParserValue::DoubleQuotedString(sval) => Some(sval.to_owned()),
-                                SQLParserValue::Number(num, _) => {
-                                    Some(num.clone())
-                                },
-                                SQLParserValue::Boolean(boolval) => Some(boolval.to_string()),
-                                SQLParserValue::Null => Some(String::from("null")),
-                                _ => return Err(())
-                            }
-                        },
-                        _ => return Err(()) // incorrect parsed condition
-                    }
-                },
-                perf: None
-            };
-
-            // Add condition part to list
-            op_row_collection.push(no_rollup_cond);
-
-            // Performed indicator result
-            Ok(())
-        }
-        
-        /// Appropriate action to appropriate outcome
-        match op {
-            BinaryOperator::And | BinaryOperator::Or => { // for multiple blocks // in that case "left" and "right" keys allways represents next "BinaryOp" struct
-                // left
-                convert_binarop(*left, converted_list)?;
-
-                // Add conjuction
-                let conjuction = RowWhereOperation {
-                    column: None,
-                    value: None,
-                    op: op.clone(),
-                    perf: None
-                };
-                converted_list.push(conjuction);
-
-                // right
-                convert_binarop(*right, converted_list)?;
-
-                // Result
-                Ok(())
-            }, 
-            _ => for_value_and_column(converted_list, &right, &left, &op) // for row operations   
-        }
-    }
-    else {
-        // No-predicted behave
-        Err(())
-    }
-}
-
-// Obtain all actions requied for match conditions such as: column data type, number from row, searched number
-type Comparision = (bool, i128);
-fn numeric_matches(json_t_data: &JsonSQLTable, col_name: &Option<String>, col_value: &Option<String>, row_vals: &JsonSQLTableColumnRow) -> (Option<SupportedSQLDataTypes>, Comparision, Comparision) {
-    let column_type = json_t_data.get_column_type(col_name.as_ref().unwrap());  // column name always should be provided
-    let number_is_checker = col_value.as_ref().map_or_else(|| (false, 0), |success| {
-        let parse_op = success.parse::<i128>();
-        match parse_op {
-            Ok(num) => (true, num),
-            Err(_) => (false, 0)
-        }
-    });
-    let number_to_check = row_vals.value.as_ref().map_or_else(|| (false, 0), |success| {
-        let parse_op = success.parse::<i128>();
-        match parse_op {
-            Ok(num) => (true, num),
-            Err(_) => (false, 0)
-        }
-    });
-
-    (column_type, number_is_checker, number_to_check)
-}
-
 /// Processing attached SQL query and returns its result as "JsonSQLTable" type ready to serialize, to json format thanks to "serde" and "serde_json" crates
 /// When something went bad durning analyze or processing sql query then Error without any description is returned
 // Note: Polish characters are not supported by sqlparser, so not use them into queries
@@ -369,7 +763,7 @@ fn numeric_matches(json_t_data: &JsonSQLTable, col_name: &Option<String>, col_va
 pub fn process_sql(sql_action: ProcessSQLSupportedQueries) -> Result<JsonSQLTable, ()> {
     use ProcessSQLSupportedQueries::*;
     match sql_action { // only operations which require changes/obtain data/mainupulate file content in any manner
-        CreateTable(table_name, columns) => {
+        CreateTable(table_name, columns, table_constraints) => {
             if columns.len() > 0 { // can be treat as boilerplate but i feel safier with this statement
                 let mut ready_columns: Vec<JsonSQLTableColumn> = vec![];
 
@@ -411,7 +805,12 @@ pub fn process_sql(sql_action: ProcessSQLSupportedQueries) -> Result<JsonSQLTabl
                     name: table_name.into(),
                     columns: ready_columns,
                     rows: None,
+                    constraints: if !table_constraints.is_empty() { Some(table_constraints) } else { None },
                 };
+
+                // Constraints of table must be correct (e.g: keys can't reffer to not existing columns)
+                json_sql_table.check_schema()?;
+
                 // attach computed json table from sql to returned value from whole function
                 Ok(json_sql_table)
             }
@@ -419,30 +818,17 @@ pub fn process_sql(sql_action: ProcessSQLSupportedQueries) -> Result<JsonSQLTabl
                 Err(())
             }
         }
-        Insert(table_path, columns, rows, op_type) => {
+        Insert(table_path, columns, rows, _) => {
             // TODO: Add support for When column type is different then this inffered for query collumn but format of value should be supported like between: "Varchar" and "TEXT" type
-            // TODO: Add support for constraints (e.g: When column has got NOT NULL then it must have got assigned value durning INSERT operation)
-            // TODO: Add support for autoindexing
             // TODO: Better system to checking types inside this method (number can't be asigned to string)
 
             // To perform operation must be minimum one row with inserted data
             if rows.len() > 0 {
-                // Obtain already existsing table data (if it exists and is benath correct json format)
-                let table_str = if let Ok(data) = fs::read_to_string(table_path) {
-                    data
-                } else {
-                    return Err(());
-                };
-                let mut table_json =
-                    if let Ok(json_table) = serde_json::from_str::<JsonSQLTable>(&table_str) {
-                        json_table
-                    } else {
-                        return Err(());
-                    };
+                // Obtain table definition (if table exists and is benath correct format). Rows stored in table aren't read
+                let mut table_json = sql_storage::read_definition(table_path).map_err(|_| ())?;
 
                 // Attach to table operation
-                let db_table_columns = &table_json.columns;
-                let db_table_rows = &mut table_json.rows;
+                let db_table_columns = &table_json.columns.clone(); // table columns are changed by assignment of AUTO_INCREMENT values
 
                 // When columns to which values should be inserted were attached then check whether addition for specific columns can be performed
                 // When columns weren't attached then ignore this code block
@@ -451,6 +837,11 @@ pub fn process_sql(sql_action: ProcessSQLSupportedQueries) -> Result<JsonSQLTabl
                 let mut columns_not_included_in_query: Vec<&JsonSQLTableColumn> = vec![];
                 if columns.is_some() {
                     let columns = columns.clone().unwrap();
+
+                    // Each column can be attached to query only once
+                    if columns.iter().collect::<HashSet<_>>().len() != columns.len() {
+                        return Err(());
+                    };
                     
                     // check whether all columns given into query exists and put this column into Vector
                     for column_perf_for in &columns {
@@ -493,56 +884,11 @@ pub fn process_sql(sql_action: ProcessSQLSupportedQueries) -> Result<JsonSQLTabl
                     // Go further only when all columns from query exists in table and below checking has been done to advantage of "perform further"
                     // When all is correct after check then perform further, else return Err(())
                     if existing_columns_to_perform.len() == columns.len() {
-                        // Check whether remained column doesn't have constraints "not null"
-                            // ... obtain all reamained columns + assign it to scope range variable
-                        columns_not_included_in_query = db_table_columns.
-                            iter()
-                            .filter(|column| {
-                                if !existing_columns_to_perform.contains(&column.name) {
-                                    true
-                                }
-                                else {
-                                    false
-                                }
-                            })
-                            .collect::<Vec<&JsonSQLTableColumn>>();
-                        
-                            // ... Check whether all remained columns so (these "not included in query") not inclueded constraint "NOT NULL" (when not includes then result is "true")
-                        let all_remained_dn_null = columns_not_included_in_query // indicate whether all columns doesn't have NOT_NULL constraint
+                        // Remained columns (these "not included in query") get their default values. NOT NULL constraint for them is checked together with other constraints of table
+                        columns_not_included_in_query = db_table_columns
                             .iter()
-                            .all(|remained_column| {
-                                if let Some(constraints_vec) = &remained_column.constraints {
-                                    match &remained_column.constraints {
-                                        Some(constraints_vec) => {
-                                            // When vector is empty that NOT_NULL constraint doesn't exists so return "false"
-                                            if constraints_vec.len() > 0 {
-                                                // Check whether in vector with constraints is any NOT_NULL constraint (when is return "false" when is that constraint (from this reason "not" operator begin statement!))
-                                                constraints_vec
-                                                    .iter()
-                                                    .any(|constraint| {
-                                                        match constraint.clone() {
-                                                            SupportedSQLColumnConstraints::NOT_NULL => false,
-                                                            _ => true
-                                                        }
-                                                    })
-                                            }
-                                            else {
-                                                true
-                                            }
-                                        },
-                                        None => true
-                                    }
-                                }
-                                else {
-                                    true
-                                }
-                            });
-
-                        // When some from remained column contains NOT_NULL constraiint then return Err(()) 
-                        if !all_remained_dn_null { 
-                            return Err(());
-                        }
-                        // else ... Go further and perform addition
+                            .filter(|column| !existing_columns_to_perform.contains(&column.name))
+                            .collect::<Vec<&JsonSQLTableColumn>>();
                     }
                     else {
                         return Err(());
@@ -589,68 +935,32 @@ pub fn process_sql(sql_action: ProcessSQLSupportedQueries) -> Result<JsonSQLTabl
                                 }
                             };
 
-                            // IMPORTANT: Check types correcteness ... type must be the same as column type // + add to match!() all datatype enum tuple memebers
-                            if column_for_row_value.d_type == row_value.1
-                                || matches!(
-                                    column_for_row_value.d_type,
-                                    SupportedSQLDataTypes::VARCHAR(_)
-                                )
-                                || (column_for_row_value.d_type == SupportedSQLDataTypes::TEXT && matches!(row_value.1, SupportedSQLDataTypes::VARCHAR(_))) // "VARCHAR" should also be added to columns with "TEXT" type (because varchat capacity is smaller then TEXT)
-                            {
-                                // Additional more sophisticated type checker for more complicated types
-                                // Initialy it is always "true" so operation can be performed but in moment when type isn't correct that is changing to "false"
-                                let mut allow_to_add = true;
-                                // ... more advance checking on column datatype constraints (not same as normal constraints)
-                                match column_for_row_value.d_type {
-                                    SupportedSQLDataTypes::VARCHAR(column_t_maxlen) => {
-                                        // ... check attached value from row to column datatype
-                                        match row_value.1 {
-                                            SupportedSQLDataTypes::VARCHAR(_) => {
-                                                // now always VARCHAR None
-                                                // In attached varchar type, value can't be heighter then column varchar length requirements (existing when "column_t_maxlen" is not "None")
-                                                if column_t_maxlen.is_some()
-                                                    && (column_t_maxlen.unwrap()
-                                                        < row_value.0.len() as u16
-                                                        || row_value.0.len() as u16 > 65_535)
-                                                {
-                                                    // TODO: attach on table creation that value must has got smaller length than 65_535 characters for VARCHAR datatype + attach to recognize type from query that after when string has got more then 65_535 charcters then it is no VARCHAR not TEXT (which can has got up to 16_777_215 characters)
-                                                    allow_to_add = false;
-                                                };
-                                            }
-                                            _ => (),
-                                        }
-                                    }
-                                    _ => (), // for non-special requirements
+                            // IMPORTANT: Check types correcteness ... value must be convertable to column type (e.g: number can't be attached to "DATE" column, text attached to "VARCHAR" column can't be longer then column maximal length)
+                            // For whole columns insert: Insert only when attached value has got type correct with column datatype
+                            // For insert for specific columns: Insert value for specific column and full fill remained columns with their default values or with NULL
+                            if let Ok(value) = row_value.column_value(&column_for_row_value.d_type) {
+                                // insert normal value
+                                let new_value = JsonSQLTableColumnRow {
+                                    col: column_for_row_value.name.clone(),
+                                    value,
                                 };
-
-                                // Create ready to insert, to table value for column
-                                // For whole columns insert: Insert only when attached value has got type correct with column datatype
-                                // For insert for specific columns: Insert value for specific column and full fill remained columns with values null
-                                if allow_to_add {
-                                    // insert normal value
-                                    let new_value = JsonSQLTableColumnRow {
-                                        col: column_for_row_value.name.clone(),
-                                        value: Some(row_value.0.clone()),
-                                    };
-                                    ready_row_values.push(new_value);
-
-                                    // Add to row values for remained columns with attached "null" as a value
-                                    // below instruction ignore type safeguards ("null" -> can be attached to all keys which doesn't have got NOT_NULL constraint)
-                                    if it_num == row_len - 1 && columns.is_some() {
-                                        let mut remained_row_values = vec![] as Vec<JsonSQLTableColumnRow>;
-
-                                        for colmn_out_from_query in &columns_not_included_in_query {
-                                            let remained_row_value = JsonSQLTableColumnRow {
-                                                col: colmn_out_from_query.name.to_owned(),
-                                                value: None
-                                            };
-                                            remained_row_values.push(remained_row_value);
+                                ready_row_values.push(new_value);
+
+                                // Add to row values for remained columns with their default value or with NULL when column hasn't got default value
+                                // below instruction ignore type safeguards (NULL -> can be attached to all keys which doesn't have got NOT_NULL constraint)
+                                if it_num == row_len - 1 && columns.is_some() {
+                                    let mut remained_row_values = vec![] as Vec<JsonSQLTableColumnRow>;
+
+                                    for colmn_out_from_query in &columns_not_included_in_query {
+                                        let default_value = colmn_out_from_query.default_value()?;
+                                        let remained_row_value = JsonSQLTableColumnRow {
+                                            col: colmn_out_from_query.name.to_owned(),
+                                            value: default_value
                                         };
-                                        ready_row_values.extend(remained_row_values);
+                                        remained_row_values.push(remained_row_value);
                                     };
-                                } else {
-                                    break;
-                                }
+                                    ready_row_values.extend(remained_row_values);
+                                };
                             } else {
                                 break;
                             };
@@ -658,773 +968,62 @@ pub fn process_sql(sql_action: ProcessSQLSupportedQueries) -> Result<JsonSQLTabl
                             it_num += 1;
                         }
 
-                        // Attach row to all rows list // Always great result
+                        // Whole operation is stopped when some value from row couldn't be attached to its column so only complete rows are inserted
+                        if ready_row_values.len() != db_table_columns.len() {
+                            return Err(());
+                        };
+
+                        // Attach row to all rows list with values in same order as table columns
+                        ready_row_values.sort_by_key(|row_value| db_table_columns.iter().position(|column| column.name == row_value.col));
                         ready_rows.push(ready_row_values);
                     } else {
-                        break;
+                        return Err(());
                     };
                 }
 
-                // Check correcteness and assign values to table "rows" key
-                if db_table_rows.is_some()
-                    && ready_rows.len() > 0
-                    && ready_rows[0].len() == db_table_columns.len()
-                    && matches!(op_type, InsertOperations::Into)
-                {
-                    // When already table has got saved rows
-                    // ... assign to table new rows
-                    let mut db_table_rows = db_table_rows.as_mut().unwrap();
-                    db_table_rows.extend(ready_rows);
-
-                    //... assign to table new rows
-                    table_json.rows = Some(db_table_rows.clone()); // .clone() becuase i would like get rid of reference without thief whole value
-
-                    //... return table in json format as a result of `INSERT` operation + stop loop
-                    return Ok(table_json);
-                } else if ready_rows.len() > 0
-                    && ready_rows[0].len() == db_table_columns.len() 
-                    && ((db_table_rows.is_none() && ready_rows.len() > 0) 
-                        || (matches!(op_type, InsertOperations::Overwrite) && ready_rows.len() > 0)) 
-                {
-                    println!("i");
-                    // When table hasn't got already any saved rows or INSERT OPERATION has been characterized as "INSERT OVERWRITE TABLE"
-                    //... assign to table new rows
-                    table_json.rows = Some(ready_rows);
-
-                    //... return table in json format as a result of `INSERT` operation + stop loop
-                    return Ok(table_json);
-                }
+                // Rows without values for AUTO_INCREMENT columns get next values of these columns
+                table_json.columns.iter_mut().for_each(|column| column.assign_auto_increment(&mut ready_rows));
 
-                return Err(()); // otherwise (is returned for example when "ready_rows.len() != db_table_columns.len()" which occurs when row type isn't equal to type specified for column)
+                // Table definition is returned only with inserted rows (they are saved beside stored rows or instead of them for "INSERT OVERWRITE TABLE" by "insert_rows")
+                table_json.rows = Some(ready_rows);
+                Ok(table_json)
             } else {
-                return Err(());
+                Err(())
             }
         },
         Truncate(table_path) => {
-            // To perform whole operation: specified table must exists, table must be in JSON format before serialization. Else "Err(())" is returned
+            // To perform whole operation: specified table must exists and its file must be correct. Else "Err(())" is returned
             // Check whether path exists isn't perform here!
-            if let Ok(table_str) = fs::read_to_string(table_path) {
-                let table_json = serde_json::from_str::<JsonSQLTable>(&table_str);
-                if table_json.is_ok() {
-                    let mut table_json = table_json.unwrap();
-                    table_json.rows = None;
-                    
-                    Ok(table_json)
-                }
-                else {
-                    Err(())
-                }
-            }
-            else {
-                Err(())
-            }
-        },
-        Select(table_path, resulting_columns, conditions) => {
-            // When results aren't find then return table rows equal to "None"
-            let table_data = fs::read_to_string(table_path).unwrap();
-            let mut json_t_data = serde_json::from_str::<JsonSQLTable>(&table_data).unwrap(); // I trust other Database functionalities to maintain correct JSON format
-            let mut t_d_rows = json_t_data.clone().rows; // WARNING: for simply access but not for assign values!!!
-
-            if t_d_rows.is_some() {
-                    // Attach to each row table unique id
-                let mut id_operation_row = 0 as u128;
-                let mut t_d_rows = t_d_rows /* prepare each table row for search match operation */
-                    .as_mut()
-                    .unwrap()
-                    .iter()
-                    .map(|each_row| {
-                        id_operation_row += 1;
-                        RowOperationForm {
-                            row: each_row.to_owned(),
-                            id: id_operation_row
-                        }
-                    })
-                    .collect::<Vec<_>>();
-                let mut matched_rows: Vec<Vec<JsonSQLTableColumnRow>> = Vec::new(); // match results are storing here
-
-                    // ... Table must have some rows to go further
-                if t_d_rows.len() > 0 {
-                    // Get whether user pass columns which are into table or pass "all" option (for return all columns)
-                    let table_col_names = json_t_data.columns.iter()
-                        .enumerate()
-                        .filter_map(|col| {
-                          Some(&col.1.name)
-                       })
-                        .collect::<Vec<&String>>();
-                    // Whether user add table column names or appropraite option
-                    let user_pass_table_cols = resulting_columns.iter()
-                        .enumerate()
-                        .all(|col_to_ret| {
-                            let col_name = col_to_ret.1;
-                        
-                            if table_col_names.contains(&col_name) || col_name == &"all".to_string() {
-                                return true;
-                            };
-
-                            false
-                        });
+            let mut table_json = sql_storage::read_definition(table_path).map_err(|_| ())?;
+            table_json.rows = None;
 
-                    //... Search results from conditions from 'WHERE'
-                    if let Some(expr_conditions) = conditions {
-                        // list with converted expressions from 'WHERE'
-                        let mut operations_for_row: Vec<RowWhereOperation> = Vec::new(); // [{ column: Some("gender"), value: Some("male"), op: Eq }, { op: And, column: None, value: None }]
-
-                        // Convert whole to expected form
-                        convert_binarop(expr_conditions, &mut operations_for_row)?;
-
-                        let mut s_rows = Vec::new() as Vec<RowOperationForm>; // matched rows storage // are storing in this scope and later are "send" to heighter scope
-                        let mut op_performed_whole = true; // when false result shoudn't be returned and search operation performed further
-                        let mut seeked_rows: Vec<u128> = Vec::new(); // Vector with ids rows which has been matched
-
-                        // Iterate over conditions and try to find appropriate columns
-                        type RowId = u128;
-                        let mut and_outcomes_for_all_conditions: Vec<Vec<(bool, RowId)>> = Vec::new(); // matches are in order in that conditions has been passed
-                        let mut or_exception_for_and_conditions: HashSet<RowId> = HashSet::new(); // row ids list which has been match by "OR" conjuction conditions thus shoudn't be deleted by "AND" delete row proceduree
-                        let mut it_op_id = 0;
-                        loop {
-                            if it_op_id < operations_for_row.len() && op_performed_whole {
-                                // get condition to later match
-                                let rm = operations_for_row.clone(); // to easy compare in And, Or conditions
-                                let op_for_row = &mut operations_for_row[it_op_id];
-                                // println!("{:#?}", op_for_row);
-
-                                // Src operation trashold:
-                                let sc_name = op_for_row.column.clone();
-                                let sc_val = op_for_row.value.clone();
-                                let mut match_found: bool = false;
-    
-                                //... Comparing clousure // op: "Eq"/"Less" etc...
-                                let mut search_match_in_row = |op_for_row: &mut RowWhereOperation| {
-                                    let mut and_condition_outcomes_from_each_row: Vec<(bool, RowId)> = Vec::new(); // store row matches for this condition (selected condition by "loop" block)
-
-                                    // Search match in each "row" of table
-                                    for row in &*t_d_rows {
-                                        let mut and_con_match_found_in_row = false;
-                                        
-                                        // Search result in each "row value" from table
-                                        for row_vals in &row.row {
-                                            // For matched results: perform all that sugest that row has been match
-                                            // KEEP Vigilance: The most promiment function for whole search operation
-                                            fn match_success(match_found: &mut bool, and_con_match_found_in_row: &mut bool, op_for_row: &mut RowWhereOperation, s_rows: &mut Vec<RowOperationForm>, matched_rows_list: &mut Vec<u128>, row: &RowOperationForm) {
-                                                // Pointers
-                                                *and_con_match_found_in_row = true; // that found result in row
-                                                *match_found = true; // match is here so indicate other members about that
-                                                op_for_row.perf = Some(true); // indicate that operation has been successfull performed
-                                                
-                                                // Body
-                                                if !matched_rows_list.contains(&row.id) { // Attach result only when it was not found previous
-                                                    s_rows.push(row.clone()); // attach seeked row to seeked rows list
-                                                    matched_rows_list.push(row.id); // attach row id to matched rows list
-                                                }
-                                            }
-
-                                            // Perform specific action abd add positive match result to results list
-                                            match op_for_row.op {
-                                                BinaryOperator::Eq => { // values must be equal
-                                                    if &row_vals.col == sc_name.as_ref().unwrap() && row_vals.value == sc_val {
-                                                        match_success(&mut match_found, &mut and_con_match_found_in_row, &mut op_for_row.clone(), &mut s_rows, &mut seeked_rows, row);
-                                                        break;
-                                                    }
-                                                },
-                                                BinaryOperator::NotEq => {
-                                                    if &row_vals.col == sc_name.as_ref().unwrap() && row_vals.value != sc_val {
-                                                        match_success(&mut match_found, &mut and_con_match_found_in_row, &mut op_for_row.clone(), &mut s_rows, &mut seeked_rows, row);
-                                                        break;
-                                                    }
-                                                },
-                                                BinaryOperator::Gt => { // value from database must be greater then given
-                                                    let (column_type, number_is_checker, number_to_check) = numeric_matches(&json_t_data, &sc_name, &sc_val, &row_vals); // data required for all numeric operations
-
-                                                    if (&row_vals.col == sc_name.as_ref().unwrap()) && (column_type.is_some() && column_type.unwrap() == SupportedSQLDataTypes::INT) && (number_is_checker.0 && number_to_check.0) {
-                                                        if number_to_check.1 > number_is_checker.1 { // value from row must be greater then that from query
-                                                            match_success(&mut match_found, &mut and_con_match_found_in_row, op_for_row, &mut s_rows, &mut seeked_rows, row);
-                                                            break;
-                                                        }
-                                                    }
-                                                },
-                                                BinaryOperator::GtEq => {
-                                                    let (column_type, number_is_checker, number_to_check) = numeric_matches(&json_t_data, &sc_name, &sc_val, &row_vals); // data required for all numeric operations
-
-                                                    if (&row_vals.col == sc_name.as_ref().unwrap()) && (column_type.is_some() && column_type.unwrap() == SupportedSQLDataTypes::INT) && (number_is_checker.0 && number_to_check.0) {
-                                                        if number_to_check.1 >= number_is_checker.1 { // value from row must be greater or equal to that from query
-                                                            match_success(&mut match_found, &mut and_con_match_found_in_row, op_for_row, &mut s_rows, &mut seeked_rows, row);
-                                                            break;
-                                                        }
-                                                    }
-                                                },
-                                                BinaryOperator::Lt => {
-                                                    let (column_type, number_is_checker, number_to_check) = numeric_matches(&json_t_data, &sc_name, &sc_val, &row_vals); // data required for all numeric operations
-
-                                                    if (&row_vals.col == sc_name.as_ref().unwrap()) && (column_type.is_some() && column_type.unwrap() == SupportedSQLDataTypes::INT) && (number_is_checker.0 && number_to_check.0) {
-                                                        if number_to_check.1 < number_is_checker.1 { // value from row must be greater or equal to that from query
-                                                            match_success(&mut match_found, &mut and_con_match_found_in_row, op_for_row, &mut s_rows, &mut seeked_rows, row);
-                                                            break;
-                                                        }
-                                                    }
-                                                },
-                                                BinaryOperator::LtEq => {
-                                                    let (column_type, number_is_checker, number_to_check) = numeric_matches(&json_t_data, &sc_name, &sc_val, &row_vals); // data required for all numeric operations
-
-                                                    if (&row_vals.col == sc_name.as_ref().unwrap()) && (column_type.is_some() && column_type.unwrap() == SupportedSQLDataTypes::INT) && (number_is_checker.0 && number_to_check.0) {
-                                                        if number_to_check.1 <= number_is_checker.1 { // value from row must be greater or equal to that from query
-                                                            match_success(&mut match_found, &mut and_con_match_found_in_row, op_for_row, &mut s_rows, &mut seeked_rows, row);
-                                                            break;
-                                                        }
-                                                    }
-                                                }
-                                                _ => () // no handled
-                                            }
-                                        }
-                                   
-                                        // Add result from condition search from this row
-                                        and_condition_outcomes_from_each_row.push((and_con_match_found_in_row, row.id));
-
-                                        // Add match searched by "OR" condition to not remove by "AND" condition procedure list
-                                        if match_found && it_op_id != 0 && rm[it_op_id - 1].op == BinaryOperator::Or {
-                                            or_exception_for_and_conditions.insert(row.id);
-                                        };
-                                    }
-
-                                    // Add this ("loop" block iteration) condition search result from each row to list search outcomes for each condition
-                                        // .. Don't add and condition for Or conjuction operator which preceedes condition or are behind first condition
-                                    if (it_op_id == 0 && rm.len() > 1 && rm[1].op == BinaryOperator::And) || (it_op_id > 0 && rm[it_op_id - 1].op != BinaryOperator::Or) || ((it_op_id > 0 && rm[it_op_id - 1].op == BinaryOperator::And) || (rm.len() > it_op_id + 1 && rm[it_op_id + 1].op == BinaryOperator::And)) {
-                                        and_outcomes_for_all_conditions.push(and_condition_outcomes_from_each_row);
-                                    };
-                                    
-                                    // For consistancy: when match wasn't found (cohestive working should be represented in this way)
-                                    if !match_found {
-                                        op_for_row.perf = Some(false);
-                                    }
-                                };
-    
-                                // And/Or conditions pissed here
-                                if let BinaryOperator::And = op_for_row.op {
-                                    ()
-                                }
-                                else if let BinaryOperator::Or = op_for_row.op {
-                                    () // Do nothing... Just go to next iteration (no "continue" statement (because it stop iterations counting hence next iteration will be reffering to this cycle so this made infinite loop))
-                                }
-                                else {
-                                    // Lead further for other "op" types like Eq/Gt/Less etc...
-                                    search_match_in_row(op_for_row);
-
-                                    // When result hasn't been matched in any row by above clousure (enclosed in brackets "{}")
-                                    if !match_found {
-                                        // Specific use cases
-                                        if it_op_id > 0 && rm[it_op_id - 1].op == BinaryOperator::And && (rm.len() > it_op_id + 1 && rm[it_op_id + 1].op == BinaryOperator::Or) {
-                                            // When match hasn't been found for "AND" conjcution but next conjuction is "OR"
-                                            ()
-                                        }
-                                    };
-                                }
-
-                                // increase iterated elements colunt
-                                it_op_id += 1;
-                            }
-                            else {
-                                break;
-                            }
-                        };
-                        // println!("{:?}", and_outcomes_for_all_conditions);
-
-                        // Remove matches (rows) which aren't fullfilled for all and conditions
-                        let and_conditions_len = and_outcomes_for_all_conditions.len();
-                        if and_conditions_len > 0 {
-                                // Iterate on only row outcomes for first condition because all conditions must have always same rows count and our aim is to obtain only each row id for code located inside
-                            for f_cond_row in and_outcomes_for_all_conditions[0].iter() {
-                                    //.. Whether in all "AND" conditions this row has been matched
-                                let matches_for_row = and_outcomes_for_all_conditions
-                                    .iter()
-                                    .all(|ocondition| {
-                                        let mut match_found_for_condition: bool = false;
-                                        for (result, id) in ocondition {
-                                            if *id == f_cond_row.1 {
-                                                match_found_for_condition = *result;
-                                            }
-                                        };
-                                        match_found_for_condition
-                                    });
-                                    // Remove row which doesn't match for all "AND" conditions and when row id isn't on "OR" exceptions list
-                                if !matches_for_row {
-                                    // println!("Didn't found match for row: {}\nOr exceptions list: {:?}", f_cond_row.1, or_exception_for_and_conditions);
-                                    for (on_list_id, RowOperationForm { row: _, id: row_id }) in s_rows.clone().iter().enumerate() {
-                                        // When row hasn't been matched, it isn't on or exceptions list and doesn't matched row id is equal to iterated row id on list with seeked rows
-                                        if *row_id == f_cond_row.1 && !or_exception_for_and_conditions.contains(&row_id) {
-                                            s_rows.remove(on_list_id);
-                                        }
-                                    }
-                                }
-                            }
-                        }
-
-                        // Attach search results to next processing stage (only when where results has been found else return table without rows as a result of function)
-                        if s_rows.len() > 0 {
-                            std::mem::drop(t_d_rows); // remove prepared rows from memory faster than RAII should do this automatically
-                            matched_rows.extend(
-                                s_rows
-                                        .iter()
-                                        .map(|RowOperationForm { row, id }| {
-                                            row.clone()
-                                        })
-                                        .collect::<Vec<_>>()
-                            );
-                        }
-                        else {
-                            json_t_data.rows = None;
-                            return Ok(json_t_data);
-                        };
-                    }
-                    // println!("{:#?}", matched_rows);
-
-                    // Go ahead only when user pass table column names or "all" option
-                    // Send to user only specified by him columns only when user pass these columns
-                    if user_pass_table_cols {
-                        // Return only fields for columns which user would like to get
-                        if resulting_columns[0] == "all" {
-                            // Return all columns for matched records
-                            json_t_data.rows = Some(matched_rows);
-                            return Ok(json_t_data);
-                        }
-                        else {
-                            // Return only fields for columns which user would like to get 
-                            let mut f_results = vec![] as Vec<Vec<JsonSQLTableColumnRow>>;
-                            for row in matched_rows {
-                                let mut row_passed_fields_ready = vec![] as Vec<JsonSQLTableColumnRow>;
-                                let _ = row
-                                    .iter()
-                                    .enumerate()
-                                    .filter(|field| {
-                                        let f_d = field.1;
-
-                                        if resulting_columns.contains(&f_d.col) {
-                                            return true
-                                        };
-
-                                        return false
-                                    })
-                                    .collect::<Vec<(usize, &JsonSQLTableColumnRow)>>()
-                                    .into_iter()
-                                    .for_each(|record| {
-                                        row_passed_fields_ready.push(record.1.to_owned())
-                                    });
-                                f_results.push(row_passed_fields_ready)
-                            }
-
-                            json_t_data.rows = Some(f_results);
-                            return Ok(json_t_data);
-                        }
-                    }
-                    else {
-                        return Err(())
-                    }
-                }
-                else {
-                    // Return table withput rows // with null benath "rows" key
-                    json_t_data.rows = None; // Return null for "rows" but not empty array. "serde_json" threat that as null in json file
-                    return Ok(json_t_data)
-                }
-            }
-            else {
-                // Return table withput rows // with null benath "rows" key
-                return Ok(json_t_data)
-            }
+            Ok(table_json)
+        },
+        Select(query) => {
+            // Compute results of query (rows from tables from "FROM" clause filtered, grouped, sorted and with only columns which user would like to get)
+            let (columns, rows) = sql_select::select(&query, None)?;
+            Ok(JsonSQLTable {
+                name: query.body.qualifiers().join(", "),
+                columns,
+                rows: if !rows.is_empty() { Some(rows) } else { None }, // When results aren't find then return table rows equal to "None"
+                constraints: None
+            })
         },
         Delete(table_path, condition) => {
             // Delete matched rows from table and return deleted rows
-            let table_data = fs::read_to_string(table_path).unwrap();
-            let mut json_t_data = serde_json::from_str::<JsonSQLTable>(&table_data).unwrap();
+            let (mut json_t_data, changes) = change_rows(table_path, None, condition.as_ref()).map_err(|_| ())?;
+            let deleted_rows = changes.into_iter().map(|(old_row, _)| old_row).collect::<Vec<_>>();
 
-            fn save_updated_table(table: JsonSQLTable, path: &PathBuf) -> Result<(), ()> {
-                let s = serde_json::to_string(&table).unwrap();
-                fs::write(path, s).map_or_else(|_| Err(()), |_| Ok(()))
-            }
-
-            // To peroform delete operation table must have got some rows otherwise will be return table without any rows
-            if let Some(rows_set) = &json_t_data.rows {
-                match condition {
-                    Some(condition_body) => {
-                        // list with converted expressions from 'WHERE'
-                        let mut operations_for_row: Vec<RowWhereOperation> = Vec::new(); // [{ column: Some("gender"), value: Some("male"), op: Eq }, { op: And, column: None, value: None }]                   
-                        let mut prep_rows = rows_set.into_iter()
-                            .enumerate()
-                            .map(|val| {
-                                RowOperationForm {
-                                    id: val.0 as u128,
-                                    row: val.1.to_owned()
-                                }
-                            })
-                            .collect::<Vec<_>>();
-                        // Convert whole to expected form
-                        convert_binarop(condition_body, &mut operations_for_row)?;
-                        let mut rows_to_delete: HashSet<u128> = HashSet::new(); // store only unique identificators for rows
-                        // iterate over conditions and search matches
-                        for cond in operations_for_row {
-                            if cond.op != BinaryOperator::And && cond.op != BinaryOperator::Or {
-                                let cond_colname = cond.column.unwrap();
-                                let cond_colvalue = cond.value.unwrap();
-                                for prep_row in &prep_rows {
-                                    for row in &prep_row.row {
-                                        let row_column = row.col.clone();
-                                        let row_value = row.value.clone().unwrap(); // FIXME: Null will cause error here!!!
-                                        if row_column == cond_colname.clone() {
-                                            let mut when_success_in_match = || {
-                                                rows_to_delete.insert(prep_row.id);
-                                            };
-    
-                                            // Perform specific action and 
-                                            match cond.op {
-                                                BinaryOperator::Eq => { // values must be equal
-                                                    if row_value == cond_colvalue {
-                                                        when_success_in_match();
-                                                    }
-                                                },
-                                                BinaryOperator::NotEq => {
-                                                    if row_value != cond_colvalue.clone() {
-                                                        when_success_in_match();
-                                                    }
-                                                },
-                                                BinaryOperator::Gt => { // value from database must be greater then given
-                                                    let (column_type, number_is_checker, number_to_check) = numeric_matches(&json_t_data, &Some(cond_colname.to_owned()), &Some(cond_colvalue.to_owned()), row); // data required for all numeric operations
-    
-                                                    if (column_type.is_some() && column_type.unwrap() == SupportedSQLDataTypes::INT) && (number_is_checker.0 && number_to_check.0) {
-                                                        if number_to_check.1 > number_is_checker.1 {
-                                                            when_success_in_match()
-                                                        }
-                                                    }
-                                                },
-                                                BinaryOperator::GtEq => {
-                                                    let (column_type, number_is_checker, number_to_check) = numeric_matches(&json_t_data, &Some(cond_colname.to_owned()), &Some(cond_colvalue.to_owned()), row); // data required for all numeric operations
-    
-                                                    if (column_type.is_some() && column_type.unwrap() == SupportedSQLDataTypes::INT) && (number_is_checker.0 && number_to_check.0) {
-                                                        if number_to_check.1 >= number_is_checker.1 {
-                                                            when_success_in_match()
-                                                        }
-                                                    }
-                                                },
-                                                BinaryOperator::Lt => {
-                                                    let (column_type, number_is_checker, number_to_check) = numeric_matches(&json_t_data, &Some(cond_colname.to_owned()), &Some(cond_colvalue.to_owned()), row); // data required for all numeric operations
-    
-                                                    if (column_type.is_some() && column_type.unwrap() == SupportedSQLDataTypes::INT) && (number_is_checker.0 && number_to_check.0) {
-                                                        if number_to_check.1 < number_is_checker.1 {
-                                                            when_success_in_match()
-                                                        }
-                                                    }
-                                                },
-                                                BinaryOperator::LtEq => {
-                                                    let (column_type, number_is_checker, number_to_check) = numeric_matches(&json_t_data, &Some(cond_colname.to_owned()), &Some(cond_colvalue.to_owned()), row); // data required for all numeric operations
-    
-                                                    if (column_type.is_some() && column_type.unwrap() == SupportedSQLDataTypes::INT) && (number_is_checker.0 && number_to_check.0) {
-                                                        if number_to_check.1 <= number_is_checker.1 {
-                                                            when_success_in_match()
-                                                        }
-                                                    }
-                                                }
-                                                _ => () // no handled
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                        // Delete rows which matches to query and add them to list collected deleted rows
-                        let mut deleted_rows: Vec<Vec<JsonSQLTableColumnRow>> = Vec::new(); // list with deleted rows
-                        for row_id_to_delete in rows_to_delete {
-                            prep_rows.clone().into_iter().enumerate().for_each(|prep_orow| {
-                                if prep_orow.1.id == row_id_to_delete {
-                                    deleted_rows.push(prep_orow.1.row);
-                                    prep_rows.remove(prep_orow.0);
-                                };
-                            })
-                        };
-                        // Save table without deleted rows
-                        let table_content = serde_json::to_string::<JsonSQLTable>(&JsonSQLTable { 
-                            rows: {
-                                let prep = prep_rows.into_iter()
-                                    .map(|row| row.row)
-                                    .collect::<Vec<_>>();
-                                if prep.len() > 0 {
-                                    Some(prep)
-                                }
-                                else {
-                                    None
-                                }
-                            }, 
-                            ..json_t_data.clone()
-                        }).unwrap(); // Assumes that table content always has got correct JSON syntax
-                        fs::write(table_path, table_content);
-                        // Return table with deleted rows
-                        json_t_data.rows = Some(deleted_rows);
-                        return Ok(json_t_data)
-                    },
-                    None => {
-                        // Delete all rows from table
-                        let table_before_rows_deletion = json_t_data.clone();
-                        json_t_data.rows = None;
-                        save_updated_table(json_t_data, table_path)?;
-                        Ok(table_before_rows_deletion)
-                    }
-                }
-            }
-            else {
-                Ok(json_t_data)
-            }
+            json_t_data.rows = if !deleted_rows.is_empty() { Some(deleted_rows) } else { None };
+            Ok(json_t_data)
         },
         Update(table_path, assigments, condition) => {
-            // Update table rows and return table which have got updated rows
-            let table_data = fs::read_to_string(table_path).unwrap();
-            let mut json_t_data = serde_json::from_str::<JsonSQLTable>(&table_data).unwrap();
-
-            // To peroform "update" operation table must have got some rows otherwise will be return table without any rows hence without performed update operation
-            if matches!(json_t_data.rows, Some(_)) {
-                    // Function to get Assigment value content and assigment value type
-                let get_asg_value = |value| {
-                    let asg_value = {
-                        if let Expr::Value(data_type) = value {
-                            use sqlparser::ast::Value;
-                            match data_type {
-                                Value::Boolean(bool) => Some((bool.to_string(), SupportedSQLDataTypes::BOOLEAN)),
-                                Value::Number(num, _) => Some((num.to_owned(), SupportedSQLDataTypes::INT)),
-                                Value::DoubleQuotedString(stri) | Value::EscapedStringLiteral(stri) | Value::SingleQuotedString(stri) => {
-                                    let stri_data_type = if stri.len() > 65_535 {
-                                        SupportedSQLDataTypes::TEXT
-                                    }
-                                    else {
-                                        SupportedSQLDataTypes::VARCHAR(None)
-                                    };
-                                    Some((stri.to_owned(), stri_data_type))
-                                },
-                                _ => None
-                            }
-                        }
-                        else {
-                            None
-                        }
-                    };
-
-                    asg_value
-                };
-                    // Function checking whether assigment inserted value and its type is correct with table column data type
-                let type_checker: fn(SupportedSQLDataTypes, &SupportedSQLDataTypes, &String) -> bool = |table_column_type, assigment_type, assigment_value| {
-                    if table_column_type == *assigment_type
-                    || ((matches!(table_column_type, SupportedSQLDataTypes::VARCHAR(_)) || table_column_type == SupportedSQLDataTypes::TEXT) && *assigment_type == SupportedSQLDataTypes::INT)
-                    || (matches!(table_column_type, SupportedSQLDataTypes::VARCHAR(_)) && matches!(*assigment_type, SupportedSQLDataTypes::VARCHAR(_))) {
-                        match table_column_type {
-                            SupportedSQLDataTypes::VARCHAR(length) => {
-                                let length = {
-                                    if let Some(len) = length {
-                                        len
-                                    }
-                                    else {
-                                        u16::MAX
-                                    }
-                                };
-                            
-                                // Value inserted to sting column with type Varchar must be smaller then inserted length or pre-defined for u16 type so 65_535
-                                if length as usize >= assigment_value.len() {
-                                    true
-                                }
-                                else {
-                                    false
-                                }
-                            },
-                            SupportedSQLDataTypes::TEXT => {
-                                if assigment_value.len() <= 16_777_215 {
-                                    true
-                                }
-                                else {
-                                    false
-                                }
-                            },
-                            _ => true
-                        }
-                    }
-                    else {
-                        false
-                    }
-                };
-
-                // When conditions has been passed update only specific row otheriwse update all rows
-                if condition.is_some() {
-                    // Update only specific rows (matched by condition from 'WHERE' statement)
-                    let table_rows = json_t_data.rows.clone().unwrap();
-                    let table_columns_names = json_t_data.columns.clone().into_iter().map(|column| column.name).collect::<Vec<_>>();
-                    let mut prep_rows = table_rows.into_iter()
-                        .enumerate()
-                        .map(|val| {
-                            RowOperationForm {
-                                id: val.0 as u128,
-                                row: val.1.to_owned()
-                            }
-                        })
-                        .collect::<Vec<_>>();
-    
-                    // Convert condition to more redable form
-                    let mut operations_for_row: Vec<RowWhereOperation> = Vec::new(); // [{ column: Some("gender"), value: Some("male"), op: Eq }, { op: And, column: None, value: None }]                   
-                    convert_binarop(condition.unwrap(), &mut operations_for_row)?;
-    
-                    // iterate over conditions and search matches
-                    let mut cond_rows_matched_ids: HashSet<u128> = HashSet::new();                
-                    for cond in operations_for_row {
-                        if cond.op != BinaryOperator::And && cond.op != BinaryOperator::Or {
-                            let cond_colname = cond.column.unwrap();
-                            let cond_colvalue = cond.value.unwrap();
-                            for prep_row in &prep_rows {
-                                for row in &prep_row.row {
-                                    let row_column = row.col.clone();
-                                    let row_value = row.value.clone().unwrap(); // FIXME: Null will cause error here!!!
-                                    if row_column == cond_colname.clone() {
-                                        let mut when_success_in_match = || {
-                                            cond_rows_matched_ids.insert(prep_row.id);
-                                        };
-    
-                                        // Perform specific action and 
-                                        match cond.op {
-                                            BinaryOperator::Eq => { // values must be equal
-                                                if row_value == cond_colvalue {
-                                                    when_success_in_match();
-                                                }
-                                            },
-                                            BinaryOperator::NotEq => {
-                                                if row_value != cond_colvalue.clone() {
-                                                    when_success_in_match();
-                                                }
-                                            },
-                                            BinaryOperator::Gt => { // value from database must be greater then given
-                                                let (column_type, number_is_checker, number_to_check) = numeric_matches(&json_t_data, &Some(cond_colname.to_owned()), &Some(cond_colvalue.to_owned()), row); // data required for all numeric operations
-    
-                                                if (column_type.is_some() && column_type.unwrap() == SupportedSQLDataTypes::INT) && (number_is_checker.0 && number_to_check.0) {
-                                                    if number_to_check.1 > number_is_checker.1 {
-                                                        when_success_in_match()
-                                                    }
-                                                }
-                                            },
-                                            BinaryOperator::GtEq => {
-                                                let (column_type, number_is_checker, number_to_check) = numeric_matches(&json_t_data, &Some(cond_colname.to_owned()), &Some(cond_colvalue.to_owned()), row); // data required for all numeric operations
-    
-                                                if (column_type.is_some() && column_type.unwrap() == SupportedSQLDataTypes::INT) && (number_is_checker.0 && number_to_check.0) {
-                                                    if number_to_check.1 >= number_is_checker.1 {
-                                                        when_success_in_match()
-                                                    }
-                                                }
-                                            },
-                                            BinaryOperator::Lt => {
-                                                let (column_type, number_is_checker, number_to_check) = numeric_matches(&json_t_data, &Some(cond_colname.to_owned()), &Some(cond_colvalue.to_owned()), row); // data required for all numeric operations
-    
-                                                if (column_type.is_some() && column_type.unwrap() == SupportedSQLDataTypes::INT) && (number_is_checker.0 && number_to_check.0) {
-                                                    if number_to_check.1 < number_is_checker.1 {
-                                                        when_success_in_match()
-                                                    }
-                                                }
-                                            },
-                                            BinaryOperator::LtEq => {
-                                                let (column_type, number_is_checker, number_to_check) = numeric_matches(&json_t_data, &Some(cond_colname.to_owned()), &Some(cond_colvalue.to_owned()), row); // data required for all numeric operations
-    
-                                                if (column_type.is_some() && column_type.unwrap() == SupportedSQLDataTypes::INT) && (number_is_checker.0 && number_to_check.0) {
-                                                    if number_to_check.1 <= number_is_checker.1 {
-                                                        when_success_in_match()
-                                                    }
-                                                }
-                                            }
-                                            _ => () // no handled
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-    
-                    // Search matched rows to conditions and change their values depends on "assigments" 
-                    let mut performed_updation = false;
-                    for matched_row_id in cond_rows_matched_ids.iter() {
-                        let matched_row = prep_rows.clone().into_iter().enumerate().find(|row_prep| {
-                            if row_prep.1.id == *matched_row_id {
-                                return true
-                            };
-                            false
-                        });
-    
-                        // Match row signature / whether found row or not
-                        match matched_row {
-                            Some((id_on_list_match, RowOperationForm { row, id: _ })) => {
-                                // Iterate over columns from matched row
-                                for (row_value_id, JsonSQLTableColumnRow { col: table_column_name, value }) in row.into_iter().enumerate() {
-                                    // Iterate over assigments in order to find column which must be changed
-                                    for Assignment { id: as_column_obj, value } in &assigments {
-                                        // When column name from iterated assigment is same as column name which must change
-                                        if &table_column_name == &as_column_obj[0].value {
-                                                // ... Obtain value and value datatype from assigment   
-                                                // ... Get Assigment value and value type               
-                                            match get_asg_value(value.to_owned()) {
-                                                Some(asg_value) => {
-                                                    // ... Obtain data type from column and check assigned value data type correcteness respect to table column data type 
-                                                    let table_column_type = json_t_data.get_column_type(&table_column_name).expect("Unexpected behaviour!");
-                                                    if type_checker(table_column_type, &asg_value.1, &asg_value.0) {
-                                                        prep_rows[id_on_list_match].row[row_value_id].value = Some(asg_value.0);
-                                                        performed_updation = true;
-                                                    }
-                                                },
-                                                None => break
-                                            };
-                                        }
-                                    }
-                                }
-                            },
-                            None => break
-                        }
-                    };
-                    
-                    // If condition has been matched and updated then return table with updated data otherwise branchback not updated table
-                    // When not updated table was returned that can means that assigment has column names which aren't table columns
-                    if performed_updation {
-                        Ok(
-                            JsonSQLTable { 
-                                rows: {
-                                    let acceptable_row_fmt = prep_rows.into_iter().map(|row| row.row).collect::<Vec<_>>();
-                                    Some(acceptable_row_fmt)
-                                },
-                                ..json_t_data
-                            }
-                        )
-                    }
-                    else {
-                        Ok(json_t_data)
-                    }
-                }
-                else {
-                    // Update all rows which will be match to rows
-
-                    // Iterate over each table row and his id
-                    for table_row in json_t_data.rows.clone().unwrap().into_iter().enumerate() {
-                        // Iterate over each table row column and its id in row
-                        for table_row_column in table_row.1.into_iter().enumerate() {
-                            let table_row_column_name = (table_row_column.1).col;
-                            // Iterate over each assigment from 'SET'
-                            for Assignment { id: assigment_column_object, value } in &assigments {
-                                if table_row_column_name == assigment_column_object[0].value {
-                                        // ... Obtain value and value datatype from assigment 
-                                        // ... Get Assigment value and value type               
-                                    match get_asg_value(value.to_owned()) {
-                                        Some(asg_value) => {
-                                            // Update row value
-                                            let table_column_type = json_t_data.get_column_type(&table_row_column_name).expect("Unexpected behaviour!");
-                                            if type_checker(table_column_type, &asg_value.1, &asg_value.0) {
-                                                json_t_data.rows.as_mut().unwrap()[table_row.0][table_row_column.0].value = Some(asg_value.0);
-                                            };
-                                        },
-                                        None => break
-                                    };
-                                };
-                            };
-                        };
-                    };
+            // Update matched rows of table and return them after update
+            let (mut json_t_data, changes) = change_rows(table_path, Some(&assigments), condition.as_ref()).map_err(|_| ())?;
+            let updated_rows = changes.into_iter().filter_map(|(_, new_row)| new_row).collect::<Vec<_>>();
 
-                    // Return table with or without updated rows (for loop cannot edit any row when appropriate environment variable will be fullfiled)
-                    Ok(json_t_data)
-                }
-            }
-            else {
-                Ok(json_t_data)
-            }
+            json_t_data.rows = if !updated_rows.is_empty() { Some(updated_rows) } else { None };
+            Ok(json_t_data)
         },
         AlterTable(table_path, operation) => {
             /// Checke whether table has got column under specific name. Return found column id on list and these column data
@@ -1459,7 +1058,7 @@ pub fn process_sql(sql_action: ProcessSQLSupportedQueries) -> Result<JsonSQLTabl
                                 // Go ahead only when convertion has been successfull passed
                                 if let Some(cell_sqlparser_datatype) = &cell_column_type_conv {
                                     // Go ahead only when cell type can be converted to specific datatype otherwise stop columns about change Err(())
-                                    if let Err(_) = DataType::check_type_innterop(new_data_type.clone(), cell_sqlparser_datatype.clone(), cell.value.as_ref()) {
+                                    if DataType::check_type_innterop(new_data_type.clone(), cell_sqlparser_datatype.clone(), sql_expr::value_text(cell.value.clone()).as_ref()).is_err() {
                                         whether_perform_further = false;
                                         break;
                                     }
@@ -1468,6 +1067,10 @@ pub fn process_sql(sql_action: ProcessSQLSupportedQueries) -> Result<JsonSQLTabl
 
                                         // Stop iterations. When new column type and old data type aren't that which must have checked every row value for column to swear correcteness
                                         if !matches!(cell_column_type_conv, Some(DataType::Text)) && !matches!(new_data_type, DataType::Varchar(_)) { // Varchar can be simple converted to Text but in inrevert (from "Varchar" to "Text") all table values must swear correcteness to Varchar type 
+                                            // Also each value converted to "DECIMAL" (or from it to "INT") must fit into new type
+                                            if matches!(new_data_type, DataType::Decimal(_) | DataType::Int(_)) {
+                                                continue;
+                                            };
                                             stop_row_iteration = true;
                                             break;
                                         }
@@ -1490,15 +1093,35 @@ pub fn process_sql(sql_action: ProcessSQLSupportedQueries) -> Result<JsonSQLTabl
                 whether_perform_further
             }
 
+            /// Check whether values of column in all stored rows of table (read page after page) are compatible with new column type
+            fn check_stored_rows_compatible_with_new_ctype(table_path: &Path, changing_column_name: &String, new_data_type: &DataType) -> bool {
+                let mut stored_rows = match sql_storage::table_rows(table_path) {
+                    Ok(stored_rows) => stored_rows,
+                    Err(_) => return false
+                };
+
+                loop {
+                    match stored_rows.next_rows(sql_storage::ROWS_BATCH) {
+                        Ok(rows) if rows.is_empty() => return true,
+                        Ok(rows) => {
+                            let json_t_data = JsonSQLTable { rows: Some(rows), ..stored_rows.definition().clone() };
+                            if !check_new_column_type_compatible_with_old_ctype(&json_t_data, changing_column_name, new_data_type) {
+                                return false;
+                            };
+                        },
+                        Err(_) => return false
+                    }
+                }
+            }
+
             // Before that procedure is call has been successfull checked that table under given path exists so this shouldn't be performed again
             match operation {
                 AlterTableOperation::RenameColumn { old_column_name, new_column_name } => {
                     let new_column_name = new_column_name.value;
                     let old_column_name = old_column_name.value;
 
-                    // Get and Parse table content 
-                    let table_data = fs::read_to_string(table_path).unwrap();
-                    let mut json_t_data = serde_json::from_str::<JsonSQLTable>(&table_data).unwrap();
+                    // Get and Parse table definition (rows are changed when they are saved)
+                    let mut json_t_data = sql_storage::read_definition(table_path).map_err(|_| ())?;
 
                     // Check column with "old_column_name" exists and Perform action and return result
                     if let Some((id, column_d)) = exists_column_check(&json_t_data, &old_column_name) {
@@ -1507,16 +1130,10 @@ pub fn process_sql(sql_action: ProcessSQLSupportedQueries) -> Result<JsonSQLTabl
 
                         // According to using "unique id" for each column (accoridng to previous commit "FIXME" located below (orginally)). It shouldn't be attached for each column beacause already names are unique column identificators. Beside that ALTER operation is performed less often then others so using "id" without "column name" would cause additional overhead (operation geting column name by search column with approptiate id) for operations that are performing often then ALTER like UPDATE (oposite approach to that using now will degrate popularly using Queries performance for that using less often)
                         // Also using column name is more comfortable when you look how sqlparser returns SQL operations tokens (match column name is just faster and more elegant in that case) (when i write this use case keynote)
-                        // Change column name for each row (perform only when table has got saved rows)
-                        if let Some(ref mut rows) = json_t_data.rows {
-                            rows.iter_mut().for_each(|ref mut row| {
-                                row.iter_mut().for_each(|cell| {
-                                    if cell.col == old_column_name {
-                                        cell.col = new_column_name.clone();
-                                    };
-                                });
-                            });
-                        }
+                        // Column name is changed for each row when rows are saved (see "alter_rows")
+
+                        // Change column name also in table constraints which reffer to it
+                        json_t_data.constraints.iter_mut().flatten().for_each(|constraint| constraint.rename_column(&old_column_name, &new_column_name));
 
                         // Return result
                         return Ok(json_t_data)
@@ -1526,9 +1143,8 @@ pub fn process_sql(sql_action: ProcessSQLSupportedQueries) -> Result<JsonSQLTabl
                 },
                 AlterTableOperation::AddColumn { column_def: adding_column } => {
                     if let Some(d_type) = DataType::convert(&adding_column.data_type) {
-                        // Get and Parse table content 
-                        let table_data = fs::read_to_string(table_path).unwrap();
-                        let mut json_t_data = serde_json::from_str::<JsonSQLTable>(&table_data).unwrap();
+                        // Get and Parse table definition (rows are changed when they are saved)
+                        let mut json_t_data = sql_storage::read_definition(table_path).map_err(|_| ())?;
                         
                         // Perform further only when column with same name doesn't exists
                         if exists_column_check(&json_t_data, adding_column.name.value.borrow()).is_none() {
@@ -1553,12 +1169,11 @@ pub fn process_sql(sql_action: ProcessSQLSupportedQueries) -> Result<JsonSQLTabl
                 AlterTableOperation::DropColumn { column_name, if_exists, cascade } => {
                     let column_name = column_name.value;
 
-                    // Get and Parse table content 
-                    let table_data = fs::read_to_string(table_path).unwrap();
-                    let mut json_t_data = serde_json::from_str::<JsonSQLTable>(&table_data).unwrap();
+                    // Get and Parse table definition (rows are changed when they are saved)
+                    let mut json_t_data = sql_storage::read_definition(table_path).map_err(|_| ())?;
 
-                    // Check whether column exists
-                    if exists_column_check(&json_t_data, column_name.borrow()).is_some() {
+                    // Check whether column exists. Column which is part of table constraint (e.g: composite key) can't be removed
+                    if exists_column_check(&json_t_data, column_name.borrow()).is_some() && !json_t_data.constraints.iter().flatten().any(|constraint| constraint.columns().contains(&column_name)) {
                         // Remove column
                         json_t_data.clone().columns.iter().enumerate().for_each(|column| {
                             if column.1.name == column_name {
@@ -1566,16 +1181,7 @@ pub fn process_sql(sql_action: ProcessSQLSupportedQueries) -> Result<JsonSQLTabl
                             }
                         });
 
-                        // Remove cells which belongs to column only when table has got rows
-                        if let Some(rows) = &mut json_t_data.rows {
-                            rows.clone().iter().enumerate().for_each(|row| {
-                                row.1.iter().enumerate().for_each(|cell| {
-                                    if cell.1.col == column_name {
-                                        rows[row.0].remove(cell.0);
-                                    }
-                                });
-                            });
-                        };
+                        // Cells which belongs to column are removed when rows are saved (see "alter_rows")
 
                         // Branch back result
                         return Ok(json_t_data)
@@ -1589,20 +1195,19 @@ pub fn process_sql(sql_action: ProcessSQLSupportedQueries) -> Result<JsonSQLTabl
                     let old_name = old_name.value;
                     let new_name = new_name.value;
 
-                    // Get and Parse table content 
-                    let table_data = fs::read_to_string(table_path).unwrap();
-                    let mut json_t_data = serde_json::from_str::<JsonSQLTable>(&table_data).unwrap();
+                    // Get and Parse table definition (rows are changed when they are saved)
+                    let mut json_t_data = sql_storage::read_definition(table_path).map_err(|_| ())?;
 
                     if exists_column_check(&json_t_data, &old_name).is_some() && exists_column_check(&json_t_data, &new_name).is_none() {
                         if let Some(d_type) = DataType::convert(&data_type) {     
                             // Go further only when already existsing column row datas can be changing to another datatype
-                            if check_new_column_type_compatible_with_old_ctype(&json_t_data, &old_name, &data_type) {
+                            if check_stored_rows_compatible_with_new_ctype(table_path, &old_name, &data_type) {
                                 // Change column
                                 for column in &mut json_t_data.columns {
                                     if column.name == old_name {
                                         let mut column_dat = JsonSQLTableColumn {
                                             name: new_name.clone(),
-                                            d_type,
+                                            d_type: d_type.clone(),
                                             ..column.clone()
                                         };
 
@@ -1613,17 +1218,10 @@ pub fn process_sql(sql_action: ProcessSQLSupportedQueries) -> Result<JsonSQLTabl
                                     }
                                 };
 
-                                // Change for each row cell in column name to that is assigned row cell value
-                                if let Some(rows) = &mut json_t_data.rows {
-                                    for row in rows {
-                                        for cell in row {
-                                            // Perform only for changing column cells
-                                            if cell.col == old_name {
-                                                cell.col = new_name.clone()
-                                            }
-                                        }
-                                    }
-                                }
+                                // Change column name also in table constraints which reffer to it
+                                json_t_data.constraints.iter_mut().flatten().for_each(|constraint| constraint.rename_column(&old_name, &new_name));
+
+                                // Name and value of each row cell in column are changed when rows are saved (see "alter_rows")
 
                                 // Return success
                                 return Ok(json_t_data);
@@ -1637,45 +1235,416 @@ pub fn process_sql(sql_action: ProcessSQLSupportedQueries) -> Result<JsonSQLTabl
                     // Change column datatype if it is possible
                     let column_name = column_name.value;
 
-                    // Get and Parse table content 
-                    let table_data = fs::read_to_string(table_path).unwrap();
-                    let mut json_t_data = serde_json::from_str::<JsonSQLTable>(&table_data).unwrap();
+                    // Get and Parse table definition (rows are changed when they are saved)
+                    let mut json_t_data = sql_storage::read_definition(table_path).map_err(|_| ())?;
 
                     // Perform branchback "Ok()" only for successfull operations
                     if let Some((col_list_id, column_data)) = exists_column_check(&json_t_data, &column_name) {
                         match op {
                             AlterColumnOperation::SetDataType { data_type, using } => {
                                 // Check whether all rows values can be converted to new datatype
-                                if check_new_column_type_compatible_with_old_ctype(&json_t_data, &column_name, &data_type) {
+                                if check_stored_rows_compatible_with_new_ctype(table_path, &column_name, &data_type) {
                                     // Change type
                                     json_t_data.columns[col_list_id] = JsonSQLTableColumn {
                                         d_type: DataType::convert(&data_type).expect("Miss in type system"), // when error that means "convert" assign function doesn't implement something what is implemented in "DataType::check_type_innterop" function/s
                                         ..column_data.clone()
                                     };
 
+                                    // Column cells are converted to new type when rows are saved (see "alter_rows")
+
                                     // Return changed column
                                     return Ok(json_t_data)
                                 }
                             },
-                            _ => ()
+                            // Existing rows are checked whether they fulfil NOT NULL together with other constraints after operation
+                            AlterColumnOperation::SetNotNull => {
+                                let constraints = json_t_data.columns[col_list_id].constraints.get_or_insert_with(Vec::new);
+                                if !constraints.iter().any(|constraint| matches!(constraint, SupportedSQLColumnConstraints::NOT_NULL)) {
+                                    constraints.push(SupportedSQLColumnConstraints::NOT_NULL);
+                                };
+
+                                return Ok(json_t_data)
+                            },
+                            AlterColumnOperation::DropNotNull => {
+                                json_t_data.columns[col_list_id].remove_constraints(|constraint| matches!(constraint, SupportedSQLColumnConstraints::NOT_NULL));
+
+                                return Ok(json_t_data)
+                            },
+                            AlterColumnOperation::SetDefault { value } => {
+                                // New default value replaces previous one and must be value which can be stored in column
+                                if let Some(default @ (SupportedSQLColumnConstraints::DEFAULT(_) | SupportedSQLColumnConstraints::DEFAULT_EXPRESSION(_))) = ColumnOptionDef::convert(ColumnOptionDef { name: None, option: ColumnOption::Default(value) }) {
+                                    let column = &mut json_t_data.columns[col_list_id];
+                                    column.remove_constraints(|constraint| matches!(constraint, SupportedSQLColumnConstraints::DEFAULT(_) | SupportedSQLColumnConstraints::DEFAULT_EXPRESSION(_)));
+                                    column.constraints.get_or_insert_with(Vec::new).push(default);
+
+                                    if column.default_value().is_ok() {
+                                        return Ok(json_t_data)
+                                    };
+                                };
+                            },
+                            AlterColumnOperation::DropDefault => {
+                                json_t_data.columns[col_list_id].remove_constraints(|constraint| matches!(constraint, SupportedSQLColumnConstraints::DEFAULT(_) | SupportedSQLColumnConstraints::DEFAULT_EXPRESSION(_)));
+
+                                return Ok(json_t_data)
+                            }
                         }
                     };
 
                     // Ret... when above action couldn't be performed
                     Err(())
                 },
-                _ => Err(()) // for unsupported operations
+                AlterTableOperation::AddConstraint(constraint) => {
+                    // Get and Parse table definition (rows are changed when they are saved)
+                    let mut json_t_data = sql_storage::read_definition(table_path).map_err(|_| ())?;
+
+                    // Constraint is added only when it is supported and correct together with already existing constraints. Whether existing rows fulfil it is checked like for other changes of rows
+                    let table_columns = json_t_data.columns.iter().map(|column| column.name.clone()).collect::<Vec<_>>();
+                    let constraint = SupportedSQLTableConstraints::from_table_constraint(&constraint, &json_t_data.name, &table_columns).ok_or(())?;
+                    json_t_data.constraints.get_or_insert_with(Vec::new).push(constraint);
+                    json_t_data.check_schema()?;
+
+                    Ok(json_t_data)
+                },
+                AlterTableOperation::DropConstraint { if_exists, name, cascade: false } => {
+                    let name = name.value;
+
+                    // Get and Parse table definition (rows are changed when they are saved)
+                    let mut json_t_data = sql_storage::read_definition(table_path).map_err(|_| ())?;
+
+                    // Constraint is searched among table constraints and keys attached to columns (e.g: "users_pkey" for column "id INT PRIMARY KEY")
+                    let table_name = json_t_data.name.clone();
+                    let constraints_count = json_t_data.constraints.iter().flatten().count();
+                    if let Some(constraints) = &mut json_t_data.constraints {
+                        constraints.retain(|constraint| constraint.name() != &name);
+                        if constraints.is_empty() {
+                            json_t_data.constraints = None;
+                        };
+                    };
+                    let mut dropped = constraints_count != json_t_data.constraints.iter().flatten().count();
+                    for column in &mut json_t_data.columns {
+                        let column_name = column.name.clone();
+                        dropped |= column.remove_constraints(|constraint| match constraint {
+                            SupportedSQLColumnConstraints::PRIMARY_KEY => SupportedSQLTableConstraints::key_name(&table_name, true, std::slice::from_ref(&column_name)) == name,
+                            SupportedSQLColumnConstraints::UNIQUE => SupportedSQLTableConstraints::key_name(&table_name, false, std::slice::from_ref(&column_name)) == name,
+                            _ => false
+                        });
+                    };
+
+                    // Dropping of not existing constraint is ignored only with "IF EXISTS"
+                    if dropped || if_exists {
+                        return Ok(json_t_data);
+                    };
+
+                    Err(())
+                },
+                _ => Err(()) // for unsupported operations (e.g: DROP CONSTRAINT .. CASCADE)
             }
         }
     }
 }
 
+/// Description of error of saving rows of table. Violated unique index is described as it is
+pub fn storage_error(err: std::io::Error) -> String {
+    match err.kind() {
+        std::io::ErrorKind::InvalidInput => err.to_string(),
+        _ => "Couldn't save changed rows of table".to_string()
+    }
+}
+
+/// Check whether rows of table (e.g: inserted or updated rows) fulfil table constraints and whether they haven't got the same values of keys as rows stored in table file
+/// (apart from rows stored under "replaced" locations which are changed by the same statement). Stored rows are read one by one
+pub fn check_rows(table_path: &Path, table: &JsonSQLTable, replaced: &HashSet<RowLocation>) -> Result<(), String> {
+    table.check_constraints()?;
+    sql_index::check_unique(table_path, table)?;
+
+    let rows = table.rows.as_deref().unwrap_or_default();
+    if rows.is_empty() {
+        return Ok(());
+    };
+
+    // Stored versions with the same values of key are found by index of key (row with NULL in key doesn't conflict with other rows). Keys are checked against all live versions of rows (also against versions not seen by statement, which belong to another transaction)
+    let visibility = sql_transactions::visibility();
+    for (name, primary, columns) in table.keys() {
+        let key = key_description(&name, primary, &columns);
+        let locations = sql_index::key_locations(table_path, &columns, rows).map_err(storage_error)?;
+
+        for (row, locations) in rows.iter().zip(locations) {
+            let locations = locations.into_iter().filter(|location| !replaced.contains(location)).collect::<Vec<_>>();
+            let values = columns.iter().map(|column| cell_value(row, column)).collect::<Vec<_>>();
+
+            for (_, version, stored_row) in sql_storage::read_versions_at(table_path, &locations).map_err(storage_error)? {
+                if same_values(&values, &columns.iter().map(|column| cell_value(&stored_row, column)).collect::<Vec<_>>()) && visibility.holds_values(version).map_err(storage_error)? {
+                    return Err(duplicate_key(values, &key));
+                };
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Insert rows of table (table definition with inserted rows, e.g: returned for "INSERT") into table file. All rows seen by statement are removed before insertion when "overwrite" is true ("INSERT OVERWRITE TABLE").
+/// Inserted rows must fulfil table constraints together with stored rows, otherwise description of violated constraint is returned as Err and nothing is saved. Changes of rows (removed rows and inserted rows) are returned
+pub fn insert_rows(table_path: &Path, table: &JsonSQLTable, overwrite: bool) -> Result<Vec<RowChange>, String> {
+    let mut writer = sql_storage::table_writer(table_path).map_err(storage_error)?;
+
+    let mut removed = HashSet::new();
+    let mut changes = vec![];
+    if overwrite {
+        let mut stored_rows = sql_storage::table_rows(table_path).map_err(storage_error)?;
+        while let Some(stored_row) = stored_rows.next_located() {
+            let (location, row) = stored_row.map_err(storage_error)?;
+            writer.delete_at(location).map_err(storage_error)?;
+            removed.insert(location);
+            changes.push((row, None));
+        }
+    };
+    check_rows(table_path, table, &removed)?;
+
+    for row in table.rows.iter().flatten() {
+        writer.insert_row(row).map_err(storage_error)?;
+        changes.push((vec![], Some(row.clone())));
+    }
+    // Counters of AUTO_INCREMENT columns are moved by inserted rows
+    if serde_json::to_vec(&writer.definition().columns).ok() != serde_json::to_vec(&table.columns).ok() {
+        writer.set_definition(&JsonSQLTable { name: table.name.clone(), columns: table.columns.clone(), rows: None, constraints: table.constraints.clone() }).map_err(storage_error)?;
+    };
+
+    writer.save().map_err(storage_error)?;
+    Ok(changes)
+}
+
+/// Row after assignments from 'SET'. Assigned value is literal or expression computed for row before update (e.g: "a = a + 1") and it must be value which can be stored in column (keyword DEFAULT assigns default value of column).
+/// Assignments to columns which don't exist are omitted
+fn assigned_row(definition: &JsonSQLTable, mut row: Vec<JsonSQLTableColumnRow>, assignments: &[Assignment], columns: &[RowsSetColumn], context: &ExprContext) -> Result<Vec<JsonSQLTableColumnRow>, ()> {
+    let values = sql_select::table_row_values(&definition.columns, &row);
+
+    for Assignment { id, value } in assignments {
+        let column = match definition.columns.iter().find(|column| column.name == id[0].value) {
+            Some(column) => column,
+            None => continue
+        };
+        let field = match value {
+            Expr::Identifier(ident) if ident.quote_style.is_none() && ident.value.to_uppercase() == "DEFAULT" => None,
+            _ => match ProcessSQLRowField::from_literal(value) {
+                Some(field) => Some(field),
+                None => Some(ProcessSQLRowField::from_value(sql_expr::evaluate(value, columns, &values, context)?, &sql_expr::expression_type(value, columns)))
+            }
+        };
+        let value = match field {
+            Some(field) => field.column_value(&column.d_type)?,
+            None => column.default_value()?
+        };
+
+        if let Some(cell) = row.iter_mut().find(|cell| cell.col == column.name) {
+            cell.value = value;
+        };
+    }
+
+    Ok(row)
+}
+
+/// Update rows of table matched by condition from 'WHERE' (all rows when condition isn't given) by assignments or delete them (when assignments aren't given).
+/// Rows are read from table file one by one (or only rows found by index) and only changed rows are saved (updated rows must fulfil table constraints together with other rows, otherwise description of violated constraint is returned as Err and nothing is saved).
+/// Table definition and changes of rows (rows before and after update) are returned
+pub fn change_rows(table_path: &Path, assignments: Option<&[Assignment]>, condition: Option<&Expr>) -> Result<(JsonSQLTable, Vec<RowChange>), String> {
+    fn failed<E>(_: E) -> String {
+        "SQL query couldn't been performed".to_string()
+    }
+    let definition = sql_storage::read_definition(table_path).map_err(failed)?;
+
+    // Only rows found by index of table are read when some index can be used for condition (other rows are read one by one)
+    let candidates = condition.and_then(|condition| sql_index::candidate_locations(table_path, &definition.name, condition));
+    let stored_rows: Box<dyn Iterator<Item = std::io::Result<(RowLocation, Vec<JsonSQLTableColumnRow>)>>> = match candidates {
+        Some(candidates) => {
+            let (table, locations) = sql_storage::read_table_rows_at(table_path, &candidates).map_err(failed)?;
+            Box::new(locations.into_iter().zip(table.rows.unwrap_or_default()).map(Ok))
+        },
+        None => {
+            let mut stored_rows = sql_storage::table_rows(table_path).map_err(failed)?;
+            Box::new(std::iter::from_fn(move || stored_rows.next_located()))
+        }
+    };
+
+    // Condition is computed for each read row
+    let condition_columns = RowsSet::from_json_table(&definition, &definition.name).columns;
+    let context = ExprContext { database: table_path.parent(), ..ExprContext::default() }; // subqueries are computed in database of table
+
+    let mut locations = vec![];
+    let mut changes = vec![];
+    for stored_row in stored_rows {
+        let (location, row) = stored_row.map_err(failed)?;
+        if let Some(condition) = condition {
+            if !sql_expr::matches(condition, &condition_columns, &sql_select::table_row_values(&definition.columns, &row), &context).map_err(failed)? {
+                continue;
+            };
+        };
+
+        match assignments {
+            Some(assignments) => {
+                let new_row = assigned_row(&definition, row.clone(), assignments, &condition_columns, &context).map_err(failed)?;
+                if new_row != row {
+                    locations.push(location);
+                    changes.push((row, Some(new_row)));
+                };
+            },
+            None => {
+                locations.push(location);
+                changes.push((row, None));
+            }
+        };
+    }
+
+    // Rows changed by another transaction are reported by writer before keys of updated rows are checked against stored rows (changes are written to table file by "save")
+    let mut writer = sql_storage::table_writer(table_path).map_err(storage_error)?;
+    for (location, (_, new_row)) in locations.iter().zip(&changes) {
+        match new_row {
+            Some(new_row) => writer.update_at(*location, new_row).map(|_| ()),
+            None => writer.delete_at(*location)
+        }.map_err(storage_error)?;
+    }
+
+    // Updated rows replace rows stored under their locations
+    let updated_rows = changes.iter().filter_map(|(_, new_row)| new_row.clone()).collect::<Vec<_>>();
+    if !updated_rows.is_empty() {
+        let updated_table = JsonSQLTable { rows: Some(updated_rows), ..definition.clone() };
+        check_rows(table_path, &updated_table, &locations.into_iter().collect())?;
+    };
+    writer.save().map_err(storage_error)?;
+
+    Ok((definition, changes))
+}
+
+/// Save table changed by "ALTER TABLE" (table definition returned by "process_sql" for operation). Stored rows are read and saved page after page: cells of rows are attached to columns of changed table
+/// (column of stored table is found under its name or under its position when it has been renamed) and converted to types of columns, cells of removed column are removed.
+/// Each page of rows must fulfil constraints of changed table and keys are checked by their indexes (built again with rows), otherwise description of violated constraint is returned as Err
+pub fn alter_rows(table_path: &Path, table: &JsonSQLTable) -> Result<(), String> {
+    let stored_definition = sql_storage::read_definition(table_path).map_err(storage_error)?;
+    let renamed = stored_definition.columns.len() == table.columns.len();
+    let column = |name: &String| {
+        table.columns.iter().find(|column| &column.name == name).or_else(|| {
+            stored_definition.columns.iter().position(|column| &column.name == name).filter(|_| renamed).map(|position| &table.columns[position])
+        })
+    };
+
+    sql_storage::rewrite_table(table_path, table, |rows| {
+        let rows = rows
+            .into_iter()
+            .map(|row| row.into_iter().filter_map(|cell| column(&cell.col).map(|column| JsonSQLTableColumnRow { col: column.name.clone(), value: cell.value.of_type(&column.d_type) })).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let checked_table = JsonSQLTable { rows: Some(rows), ..table.clone() };
+        checked_table.check_constraints().map_err(|reason| std::io::Error::new(std::io::ErrorKind::InvalidInput, reason))?;
+
+        Ok(checked_table.rows.unwrap_or_default())
+    })
+    .map_err(storage_error)
+}
+
+/// Rewrite JSON files of all tables from all databases (placed in "dbs" directory) to paged table files. Cell values of tables saved before they were typed are converted to types of their columns
+pub fn migrate_tables(dbs: &Path) -> std::io::Result<()> {
+    for database in fs::read_dir(dbs)? {
+        let database = database?.path();
+        if !database.is_dir() {
+            continue;
+        };
+
+        for table in fs::read_dir(&database)? {
+            let table = table?.path();
+
+            if table.extension().is_some_and(|extension| extension == "json") {
+                // Files which aren't tables are omitted. JSON file is removed only after its table has been saved
+                if let Ok(json_table) = JsonSQLTable::from_json(&fs::read_to_string(&table)?) {
+                    sql_storage::write_table(&table.with_extension(sql_storage::TABLE_EXTENSION), &json_table)?;
+                    sql_wal::remove_file(&table)?;
+                }
+            }
+        }
+    };
+
+    Ok(())
+}
+
 #[test]
 fn test_process_sql() {
     let tab_name = "new_table".to_string();
     let row1 = (String::from("imie"), SupportedSQLDataTypes::VARCHAR(Some(12)), Some(vec![SupportedSQLColumnConstraints::NOT_NULL]));
     let row2 = (String::from("imie"), SupportedSQLDataTypes::INT, None);
-    let computed_table = process_sql(ProcessSQLSupportedQueries::CreateTable(tab_name, vec![row1, row2])).unwrap();
+    let computed_table = process_sql(ProcessSQLSupportedQueries::CreateTable(tab_name, vec![row1, row2], vec![])).unwrap();
     let serialized = serde_json::to_string(&computed_table).unwrap();
     println!("Computed table is:\n{}", serialized)
 }
+
+#[test]
+fn test_process_sql_conditions() {
+    use sqlparser::{dialect::AnsiDialect, parser::Parser as SqlParser, tokenizer::Tokenizer};
+
+    // Create table with rows in temporary directory
+    let dir = std::env::temp_dir().join(format!("wastledb_conditions_{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&dir).unwrap();
+    sql_wal::set_data_path(&dir);
+    let path = sql_storage::table_path(&dir, "people");
+    let create = || {
+        let table = process_sql(ProcessSQLSupportedQueries::CreateTable("people".to_string(), vec![("name".to_string(), SupportedSQLDataTypes::VARCHAR(None), None), ("age".to_string(), SupportedSQLDataTypes::INT, None)], vec![])).unwrap();
+        fs::remove_file(&path).ok();
+        sql_storage::write_table(&path, &table).unwrap();
+        let rows = [("ann", "20"), ("bob", "35"), ("cid", "50")]
+            .iter()
+            .map(|(name, age)| vec![ProcessSQLRowField(name.to_string(), SupportedSQLDataTypes::VARCHAR(None)), ProcessSQLRowField(age.to_string(), SupportedSQLDataTypes::INT)])
+            .collect::<Vec<_>>();
+        let table = process_sql(ProcessSQLSupportedQueries::Insert(&path, None, rows, InsertOperations::Into)).unwrap();
+        sql_storage::write_table(&path, &table).unwrap();
+    };
+    let condition = |condition: &str| SqlParser::new(Tokenizer::new(&AnsiDialect {}, condition).tokenize().unwrap(), &AnsiDialect {}).parse_expr().unwrap();
+    let names = |table: &JsonSQLTable| table.rows.iter().flatten().map(|row| sql_expr::value_text(row[0].value.clone()).unwrap()).collect::<Vec<_>>();
+
+    // Rows are deleted only when they fullfil whole condition (not only one of its parts)
+    create();
+    let deleted = process_sql(ProcessSQLSupportedQueries::Delete(&path, Some(condition("age > 30 AND NOT (name = 'cid')")))).unwrap();
+    assert_eq!(names(&deleted), vec!["bob"]);
+    let remained = sql_storage::read_table(&path).unwrap();
+    assert_eq!(names(&remained), vec!["ann", "cid"]);
+
+    create();
+    let assigments = vec![Assignment { id: vec![sqlparser::ast::Ident::new("name")], value: condition("'old'") }];
+    let updated = process_sql(ProcessSQLSupportedQueries::Update(&path, assigments, Some(condition("age BETWEEN 30 AND 60 AND name LIKE '%i%'")))).unwrap();
+    assert_eq!(names(&updated), vec!["old"]);
+    let stored = sql_storage::read_table(&path).unwrap();
+    assert_eq!(names(&stored), vec!["ann", "bob", "old"]);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_migrate_tables() {
+    // Table saved in format where all values were stored as strings
+    let dbs = std::env::temp_dir().join(format!("wastledb_migrate_{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(dbs.join("db")).unwrap();
+    sql_wal::set_data_path(&dbs);
+    let legacy = r#"{"name":"t","columns":[{"name":"id","d_type":"INT","constraints":null},{"name":"price","d_type":"FLOAT","constraints":null},{"name":"active","d_type":"BOOLEAN","constraints":null},{"name":"name","d_type":{"VARCHAR":null},"constraints":null}],"rows":[[{"col":"id","value":"2"},{"col":"price","value":"1.5"},{"col":"active","value":"TRUE"},{"col":"name","value":"ann"}],[{"col":"id","value":"null"},{"col":"price","value":null},{"col":"active","value":"false"},{"col":"name","value":"10"}]]}"#;
+    fs::write(dbs.join("db").join("t.json"), legacy).unwrap();
+
+    migrate_tables(&dbs).unwrap();
+    assert!(!dbs.join("db").join("t.json").exists());
+    let migrated = serde_json::to_string(&sql_storage::read_table(&sql_storage::table_path(&dbs.join("db"), "t")).unwrap()).unwrap();
+    assert_eq!(migrated, r#"{"name":"t","columns":[{"name":"id","d_type":"INT","constraints":null},{"name":"price","d_type":"FLOAT","constraints":null},{"name":"active","d_type":"BOOLEAN","constraints":null},{"name":"name","d_type":{"VARCHAR":null},"constraints":null}],"rows":[[{"col":"id","value":2},{"col":"price","value":1.5},{"col":"active","value":true},{"col":"name","value":"ann"}],[{"col":"id","value":null},{"col":"price","value":null},{"col":"active","value":false},{"col":"name","value":"10"}]]}"#);
+
+    fs::remove_dir_all(&dbs).unwrap();
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::path::{Path, PathBuf};
+    use super::{process_sql, ProcessSQLSupportedQueries, ProcessSQLRowField, InsertOperations, SupportedSQLDataTypes};
+    use super::super::sql_storage;
+
+    /// Create table with rows (cell values written like in query) in database directory and return path to its file
+    pub fn create_test_table(database: &Path, name: &str, columns: Vec<(&str, SupportedSQLDataTypes)>, rows: Vec<Vec<&str>>) -> PathBuf {
+        let path = sql_storage::table_path(database, name);
+        let table = process_sql(ProcessSQLSupportedQueries::CreateTable(name.to_string(), columns.iter().map(|(name, d_type)| (name.to_string(), d_type.clone(), None)).collect(), vec![])).unwrap();
+        sql_storage::write_table(&path, &table).unwrap();
+        let rows = rows.into_iter().map(|row| row.into_iter().zip(&columns).map(|(value, (_, d_type))| ProcessSQLRowField(value.to_string(), d_type.clone())).collect()).collect();
+        let table = process_sql(ProcessSQLSupportedQueries::Insert(&path, None, rows, InsertOperations::Into)).unwrap();
+        sql_storage::write_table(&path, &table).unwrap();
+        path
+    }
+}
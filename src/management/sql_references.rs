@@ -0,0 +1,434 @@
+/* Check FOREIGN KEY constraints between tables of one database and perform actions "ON DELETE" and "ON UPDATE" for rows which reffer to deleted or updated rows */
+use std::{fs, io, path::Path, cmp::Ordering, collections::HashMap};
+
+use super::sql_json::{self, JsonSQLTable, JsonSQLTableColumnRow, SupportedSQLTableConstraints, SupportedSQLReferentialActions};
+use super::sql_expr::{RowValue, compare, value_text};
+use super::sql_storage::{self, RowLocation, Version};
+use super::{sql_index, sql_transactions};
+
+/// Change of table row: 1. row before change (empty for inserted row), 2. row after change (None when row has been deleted)
+pub type RowChange = (Vec<JsonSQLTableColumnRow>, Option<Vec<JsonSQLTableColumnRow>>);
+
+/// Foreign key whose action is "NO ACTION" together with values of referenced key which have been removed or changed (rows which still reffer to them are reported after all actions have been performed):
+/// 1. name of foreign key, 2. referencing table, 3. columns of foreign key, 4. referenced table, 5. referenced columns, 6. values of referenced key
+type UnresolvedKeys = (String, String, Vec<String>, String, Vec<String>, Vec<Vec<RowValue>>);
+
+/// Row which reffers to some of changed keys: 1. its location, 2. its version, 3. row, 4. position of key to which it reffers
+type ReferencingRow = (RowLocation, Version, Vec<JsonSQLTableColumnRow>, usize);
+
+/// Load definitions of all tables from database (without rows). Tables are stored under names of their files
+pub fn database_tables(database: &Path) -> Result<HashMap<String, JsonSQLTable>, String> {
+    load_tables(database, sql_storage::read_definition)
+}
+
+/// Load tables from database by function "read" (e.g: only their definitions without rows)
+fn load_tables(database: &Path, read: fn(&Path) -> io::Result<JsonSQLTable>) -> Result<HashMap<String, JsonSQLTable>, String> {
+    let mut tables = HashMap::new();
+
+    for entry in fs::read_dir(database).map_err(|_| "Couldn't read tables of database".to_string())?.flatten() {
+        let path = entry.path();
+
+        if path.extension().is_some_and(|extension| extension == sql_storage::TABLE_EXTENSION) {
+            if let Some(name) = path.file_stem().and_then(|name| name.to_str()) {
+                let table = read(&path).map_err(|_| format!("Couldn't read table \"{}\"", name))?;
+                tables.insert(name.to_string(), table);
+            }
+        }
+    };
+
+    Ok(tables)
+}
+
+/// Names of tables referenced by foreign keys of table
+fn referenced_tables(table: &JsonSQLTable) -> impl Iterator<Item = &String> {
+    table.constraints.iter().flatten().filter_map(|constraint| match constraint {
+        SupportedSQLTableConstraints::FOREIGN_KEY(_, _, referenced_table_name, ..) => Some(referenced_table_name),
+        _ => None
+    })
+}
+
+/// Tables whose foreign keys can be affected by change of rows of table "table_name": table itself, tables which reffer to it (also through other tables, because actions can be cascaded) and tables referenced by all of them
+fn linked_tables(table_name: &str, definitions: &HashMap<String, JsonSQLTable>) -> Vec<String> {
+    let mut linked = vec![table_name.to_string()];
+
+    let mut found = true;
+    while found {
+        found = false;
+        for (name, definition) in definitions {
+            if !linked.contains(name) && referenced_tables(definition).any(|referenced_table_name| linked.contains(referenced_table_name)) {
+                linked.push(name.clone());
+                found = true;
+            };
+        }
+    }
+
+    let referenced = linked
+        .iter()
+        .filter_map(|name| definitions.get(name))
+        .flat_map(referenced_tables)
+        .cloned()
+        .collect::<Vec<_>>();
+    linked.extend(referenced);
+
+    linked
+}
+
+/// Whether some table of database (also table itself) reffers to table "table_name" by foreign key, so removed rows of table are needed for actions of foreign keys
+pub fn is_referenced(database: &Path, table_name: &str) -> Result<bool, String> {
+    let definitions = load_tables(database, sql_storage::read_definition)?;
+    Ok(definitions.values().any(|definition| referenced_tables(definition).any(|referenced_table_name| referenced_table_name == table_name)))
+}
+
+/// Values from row for columns (NULL for column without cell in row)
+fn row_values(row: &[JsonSQLTableColumnRow], columns: &[String]) -> Vec<RowValue> {
+    columns
+        .iter()
+        .map(|column| row.iter().find(|cell| &cell.col == column).map_or(RowValue::Null, |cell| cell.value.clone()))
+        .collect()
+}
+
+/// Set values of columns in row
+fn set_row_values(row: &[JsonSQLTableColumnRow], columns: &[String], values: &[RowValue]) -> Vec<JsonSQLTableColumnRow> {
+    let mut row = row.to_vec();
+
+    for (column, value) in columns.iter().zip(values) {
+        match row.iter_mut().find(|cell| &cell.col == column) {
+            Some(cell) => cell.value = value.clone(),
+            None => row.push(JsonSQLTableColumnRow { col: column.clone(), value: value.clone() })
+        }
+    };
+
+    row
+}
+
+/// Whether values are equal (values are compared according to their types so e.g: 1 is equal to 1.0)
+fn values_equal(l_values: &[RowValue], r_values: &[RowValue]) -> bool {
+    l_values.len() == r_values.len() && l_values.iter().zip(r_values).all(|(l_value, r_value)| compare(l_value, r_value) == Some(Ordering::Equal))
+}
+
+/// Referenced columns of foreign key. When they weren't given in query then foreign key reffers to PRIMARY KEY of referenced table
+fn key_columns(referenced_table: &JsonSQLTable, referenced_columns: &[String]) -> Option<Vec<String>> {
+    if !referenced_columns.is_empty() {
+        return Some(referenced_columns.to_vec());
+    };
+
+    referenced_table.keys().into_iter().find(|(_, primary, _)| *primary).map(|(_, _, columns)| columns)
+}
+
+/// Columns of key referenced by foreign key "name" of table. Referenced columns must be PRIMARY KEY or UNIQUE key of referenced table, otherwise description of incorrect foreign key is returned as Err
+fn referenced_key(name: &str, columns: &[String], referenced_table_name: &str, referenced_columns: &[String], tables: &HashMap<String, JsonSQLTable>) -> Result<Vec<String>, String> {
+    let referenced_table = tables
+        .get(referenced_table_name)
+        .ok_or_else(|| format!("Table \"{}\" referenced by foreign key \"{}\" doesn't exist", referenced_table_name, name))?;
+    key_columns(referenced_table, referenced_columns)
+        .filter(|referenced_columns| referenced_columns.len() == columns.len() && referenced_table.keys().iter().any(|(_, _, key)| key == referenced_columns))
+        .ok_or_else(|| format!("Columns referenced by foreign key \"{}\" must be PRIMARY KEY or UNIQUE key of table \"{}\"", name, referenced_table_name))
+}
+
+/// Check whether foreign keys of table "table_name" and foreign keys which reffer to it still reffer to keys of their referenced tables (e.g: after key has been removed by ALTER TABLE)
+fn check_definitions(table_name: &str, definitions: &HashMap<String, JsonSQLTable>) -> Result<(), String> {
+    for (name, definition) in definitions {
+        for constraint in definition.constraints.iter().flatten() {
+            match constraint {
+                SupportedSQLTableConstraints::FOREIGN_KEY(key_name, columns, referenced_table_name, referenced_columns, ..) if name == table_name || referenced_table_name == table_name => {
+                    referenced_key(key_name, columns, referenced_table_name, referenced_columns, definitions)?;
+                },
+                _ => ()
+            };
+        }
+    }
+
+    Ok(())
+}
+
+/// Error of value of foreign key which doesn't reffer to any row
+fn missing_reference(values: Vec<RowValue>, name: &str, referenced_table_name: &str) -> String {
+    let values = values.into_iter().map(|value| value_text(value).unwrap_or_default()).collect::<Vec<_>>();
+    format!("Value ({}) of foreign key \"{}\" doesn't reffer to existing row of table \"{}\"", values.join(", "), name, referenced_table_name)
+}
+
+/// Tell for each values of key (with columns of key) whether row with them exists in table: Some(true) when it is seen by statement, Some(false) when it doesn't exist and None when it depends on end of another transaction in progress
+/// (which could remove row or not create it in the end). Key of row removed by another transaction exists after its end also when that transaction has created row with the same key. Rows are found by index of key
+fn existing_keys(path: &Path, columns: &[String], keys: &[Vec<RowValue>]) -> Result<Vec<Option<bool>>, String> {
+    let table_name = path.file_stem().and_then(|name| name.to_str()).unwrap_or_default();
+    let read_error = |_| format!("Couldn't read table \"{}\"", table_name);
+    let keys_rows = keys
+        .iter()
+        .map(|values| columns.iter().zip(values).map(|(column, value)| JsonSQLTableColumnRow { col: column.clone(), value: value.clone() }).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let locations = sql_index::key_locations(path, columns, &keys_rows).map_err(read_error)?;
+    sql_transactions::record_read(path);
+
+    // Versions found for all keys are read at once
+    let versions = sql_storage::read_versions_at(path, &locations.iter().flatten().copied().collect::<Vec<_>>())
+        .map_err(read_error)?
+        .into_iter()
+        .map(|(location, version, row)| (location, (version, row)))
+        .collect::<HashMap<_, _>>();
+
+    let visibility = sql_transactions::visibility();
+    let mut existing = vec![];
+    for (values, locations) in keys.iter().zip(locations) {
+        let (mut exists, mut removed, mut created) = (false, false, false);
+        for (version, row) in locations.iter().filter_map(|location| versions.get(location)) {
+            let version = *version;
+            if !values_equal(&row_values(row, columns), values) {
+                continue;
+            };
+            match (visibility.is_visible(version), visibility.is_pending(version)) {
+                (true, true) => removed = true,
+                (true, false) => exists = true,
+                (false, true) if visibility.is_live(version) => created = true,
+                _ => ()
+            };
+        }
+        existing.push(match (exists, removed, created) {
+            (true, ..) | (false, true, true) => Some(true),
+            (false, false, false) => Some(false),
+            _ => None
+        });
+    }
+
+    Ok(existing)
+}
+
+/// Rows of table (read page after page) whose values of columns are equal to some of keys, with their locations, versions and positions of their keys. Row created by another transaction in progress which has got some of keys is write-write conflict
+fn referencing_rows(path: &Path, columns: &[String], keys: &[Vec<RowValue>]) -> Result<Vec<ReferencingRow>, String> {
+    let table_name = path.file_stem().and_then(|name| name.to_str()).unwrap_or_default();
+    let read_error = |_| format!("Couldn't read table \"{}\"", table_name);
+    let visibility = sql_transactions::visibility();
+
+    // Keys are sorted so key of each row is found by binary search
+    let order = |l_values: &Vec<RowValue>, r_values: &Vec<RowValue>| l_values
+        .iter()
+        .zip(r_values)
+        .map(|(l_value, r_value)| compare(l_value, r_value).unwrap_or(Ordering::Less))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal);
+    let mut sorted_keys = keys.iter().enumerate().collect::<Vec<_>>();
+    sorted_keys.sort_by(|(_, l_key), (_, r_key)| order(l_key, r_key));
+
+    let mut rows = vec![];
+    let mut stored_rows = sql_storage::table_rows(path).map_err(read_error)?;
+    while let Some(stored_row) = stored_rows.next_version() {
+        let (location, version, row) = stored_row.map_err(read_error)?;
+        let values = row_values(&row, columns);
+        let position = match sorted_keys.binary_search_by(|(_, key)| order(key, &values)) {
+            Ok(found) if values_equal(&values, sorted_keys[found].1) => sorted_keys[found].0,
+            _ => continue
+        };
+
+        match (visibility.is_visible(version), visibility.is_pending(version)) {
+            (true, _) => rows.push((location, version, row, position)),
+            (false, true) if visibility.is_live(version) => return Err(sql_transactions::conflict().to_string()),
+            _ => ()
+        };
+    }
+
+    Ok(rows)
+}
+
+/// Check whether rows of table "table_name" (e.g: inserted rows) reffer to existing row of table referenced by each of its foreign keys (row with NULL in foreign key doesn't reffer to any row).
+/// Referenced columns must be PRIMARY KEY or UNIQUE key of referenced table and referenced rows are found by index of referenced key. Description of violated foreign key is returned as Err.
+/// Row which reffers only to rows of another transaction in progress (which could be removed or not created in the end) is write-write conflict
+fn check_inserted_rows(database: &Path, table_name: &str, tables: &HashMap<String, JsonSQLTable>, rows: &[Vec<JsonSQLTableColumnRow>]) -> Result<(), String> {
+    let table = tables.get(table_name).ok_or_else(|| format!("Table \"{}\" doesn't exist", table_name))?;
+    if rows.is_empty() {
+        return Ok(());
+    };
+
+    for constraint in table.constraints.iter().flatten() {
+        if let SupportedSQLTableConstraints::FOREIGN_KEY(name, columns, referenced_table_name, referenced_columns, _, _) = constraint {
+            let referenced_columns = referenced_key(name, columns, referenced_table_name, referenced_columns, tables)?;
+
+            // Values of foreign key are looked for as values of referenced key
+            let keys = rows.iter().map(|row| row_values(row, columns)).filter(|values| !values.contains(&RowValue::Null)).collect::<Vec<_>>();
+            let existing = existing_keys(&sql_storage::table_path(database, referenced_table_name), &referenced_columns, &keys)?;
+
+            for (values, exists) in keys.into_iter().zip(existing) {
+                match exists {
+                    Some(true) => (),
+                    Some(false) => return Err(missing_reference(values, name, referenced_table_name)),
+                    None => return Err(sql_transactions::conflict().to_string())
+                };
+            }
+        }
+    };
+
+    Ok(())
+}
+
+/// Name of foreign key and table to which it belongs for first foreign key (from other table) which reffers to table "table_name"
+pub fn referencing_key(table_name: &str, tables: &HashMap<String, JsonSQLTable>) -> Option<(String, String)> {
+    tables
+        .iter()
+        .filter(|(name, _)| name.as_str() != table_name)
+        .find_map(|(name, table)| {
+            table.constraints.iter().flatten().find_map(|constraint| match constraint {
+                SupportedSQLTableConstraints::FOREIGN_KEY(key_name, _, referenced_table_name, ..) if referenced_table_name == table_name => Some((key_name.clone(), name.clone())),
+                _ => None
+            })
+        })
+}
+
+/// Save changes of rows of table performed by actions of foreign keys under locations of changed rows. Updated rows must fulfil constraints of table together with stored rows (e.g: SET NULL can't be performed for column of PRIMARY KEY)
+fn save_changes(path: &Path, locations: &[RowLocation], changes: &[RowChange]) -> Result<(), String> {
+    let mut writer = sql_storage::table_writer(path).map_err(sql_json::storage_error)?;
+    for (location, (_, new_row)) in locations.iter().zip(changes) {
+        match new_row {
+            Some(new_row) => writer.update_at(*location, new_row).map(|_| ()),
+            None => writer.delete_at(*location)
+        }.map_err(sql_json::storage_error)?;
+    }
+
+    let updated_rows = changes.iter().filter_map(|(_, new_row)| new_row.clone()).collect::<Vec<_>>();
+    if !updated_rows.is_empty() {
+        let updated_table = JsonSQLTable { rows: Some(updated_rows), ..writer.definition().clone() };
+        sql_json::check_rows(path, &updated_table, &locations.iter().copied().collect())?;
+    };
+    writer.save().map_err(sql_json::storage_error)
+}
+
+/// Perform actions of foreign keys which reffer to table "table_name" for its changed rows (actions are performed only for keys of changed rows which don't exist anymore, e.g: row was updated but not its key).
+/// Rows which reffer to changed keys are found by reading referencing tables page after page and rows changed by actions are saved at once. They are also changed rows of their tables (so e.g: deletion can be cascaded through many tables).
+/// Updated rows of tables are collected in "updated" (their foreign keys are checked after all actions) and removed keys referenced by foreign keys without action are collected in "unresolved"
+fn perform_actions(database: &Path, tables: &HashMap<String, JsonSQLTable>, table_name: &str, changes: Vec<RowChange>, updated: &mut HashMap<String, Vec<Vec<JsonSQLTableColumnRow>>>, unresolved: &mut Vec<UnresolvedKeys>) -> Result<(), String> {
+    use SupportedSQLReferentialActions::*;
+
+    if changes.is_empty() {
+        return Ok(());
+    };
+    updated.entry(table_name.to_string()).or_default().extend(changes.iter().filter_map(|(_, new_row)| new_row.clone()));
+
+    for (referencing_table_name, referencing_table) in tables {
+        let foreign_keys = referencing_table.constraints
+            .iter()
+            .flatten()
+            .filter_map(|constraint| match constraint {
+                SupportedSQLTableConstraints::FOREIGN_KEY(name, columns, referenced_table_name, referenced_columns, on_delete, on_update) if referenced_table_name == table_name => {
+                    Some((name.clone(), columns.clone(), referenced_columns.clone(), *on_delete, *on_update))
+                },
+                _ => None
+            })
+            .collect::<Vec<_>>();
+
+        for (name, columns, referenced_columns, on_delete, on_update) in foreign_keys {
+            let referenced_columns = key_columns(&tables[table_name], &referenced_columns).ok_or_else(|| format!("Columns referenced by foreign key \"{}\" must be PRIMARY KEY or UNIQUE key of table \"{}\"", name, table_name))?;
+
+            // Keys of changed rows which don't exist anymore together with new keys of updated rows
+            let changed_keys = changes
+                .iter()
+                .map(|(old_row, new_row)| (row_values(old_row, &referenced_columns), new_row.as_ref().map(|new_row| row_values(new_row, &referenced_columns))))
+                .filter(|(old_key, _)| !old_key.contains(&RowValue::Null))
+                .collect::<Vec<_>>();
+            let old_keys = changed_keys.iter().map(|(old_key, _)| old_key.clone()).collect::<Vec<_>>();
+            let existing = existing_keys(&sql_storage::table_path(database, table_name), &referenced_columns, &old_keys)?;
+            let mut removed_keys = vec![];
+            for (changed_key, exists) in changed_keys.into_iter().zip(existing) {
+                match exists {
+                    Some(true) => (),
+                    Some(false) => removed_keys.push(changed_key),
+                    None => return Err(sql_transactions::conflict().to_string())
+                };
+            }
+            if removed_keys.is_empty() {
+                continue;
+            };
+
+            // Rows which reffer to not existing row are reported after all actions have been performed
+            let action = |new_key: &Option<Vec<RowValue>>| if new_key.is_some() { on_update } else { on_delete };
+            let not_acting_keys = removed_keys.iter().filter(|(_, new_key)| action(new_key) == NO_ACTION).map(|(old_key, _)| old_key.clone()).collect::<Vec<_>>();
+            if !not_acting_keys.is_empty() {
+                unresolved.push((name.clone(), referencing_table_name.clone(), columns.clone(), table_name.to_string(), referenced_columns.clone(), not_acting_keys));
+            };
+
+            let referencing_path = sql_storage::table_path(database, referencing_table_name);
+            let keys = removed_keys.iter().map(|(old_key, _)| old_key.clone()).collect::<Vec<_>>();
+            let mut locations = vec![];
+            let mut referencing_changes = vec![] as Vec<RowChange>;
+            for (location, _, row, position) in referencing_rows(&referencing_path, &columns, &keys)? {
+                let new_key = &removed_keys[position].1;
+                let new_row = match (action(new_key), new_key) {
+                    (RESTRICT, _) => {
+                        let operation = if new_key.is_some() { "updated" } else { "deleted" };
+                        return Err(format!("Row of table \"{}\" can't be {} because it is referenced by foreign key \"{}\" of table \"{}\"", table_name, operation, name, referencing_table_name));
+                    },
+                    (NO_ACTION, _) => continue,
+                    (CASCADE, None) => None,
+                    (CASCADE, Some(new_key)) => Some(set_row_values(&row, &columns, new_key)),
+                    (SET_NULL, _) => Some(set_row_values(&row, &columns, &vec![RowValue::Null; columns.len()]))
+                };
+                locations.push(location);
+                referencing_changes.push((row, new_row));
+            }
+
+            if !referencing_changes.is_empty() {
+                save_changes(&referencing_path, &locations, &referencing_changes)?;
+                perform_actions(database, tables, referencing_table_name, referencing_changes, updated, unresolved)?;
+            };
+        }
+    };
+
+    Ok(())
+}
+
+/// Maintain foreign keys after rows of table "table_name" have been inserted, updated or deleted and saved in table file ("changes" are its inserted, updated and deleted rows).
+/// Actions "ON DELETE" and "ON UPDATE" are performed and saved for rows (from all tables of database) which reffer to changed rows and then foreign keys of changed rows are checked together with rows which still reffer to removed keys.
+/// When rows have been only inserted (or table definition has been created) then only foreign keys of inserted rows are checked. When some foreign key is violated then its description is returned as Err (and statement should be reverted)
+pub fn maintain_foreign_keys(database: &Path, table_name: &str, changes: Vec<RowChange>) -> Result<(), String> {
+    // Foreign keys are maintained only when table is linked by them to other tables
+    let definitions = load_tables(database, sql_storage::read_definition)?;
+    let linked = linked_tables(table_name, &definitions);
+    if !linked.iter().filter_map(|name| definitions.get(name)).any(|definition| referenced_tables(definition).next().is_some()) {
+        return Ok(());
+    };
+
+    check_definitions(table_name, &definitions)?;
+    if changes.iter().all(|(old_row, _)| old_row.is_empty()) {
+        let rows = changes.into_iter().filter_map(|(_, new_row)| new_row).collect::<Vec<_>>();
+        return check_inserted_rows(database, table_name, &definitions, &rows);
+    };
+
+    let (mut updated, mut unresolved) = (HashMap::new(), vec![]);
+    perform_actions(database, &definitions, table_name, changes, &mut updated, &mut unresolved)?;
+
+    // Updated rows must reffer to existing rows by their foreign keys
+    for (updated_table_name, rows) in updated {
+        check_inserted_rows(database, &updated_table_name, &definitions, &rows)?;
+    }
+
+    // Rows which still reffer to removed key (foreign key without action) are violation of foreign key, unless key has been created again. Row removed by another transaction in progress isn't checked
+    let visibility = sql_transactions::visibility();
+    for (name, referencing_table_name, columns, referenced_table_name, referenced_columns, keys) in unresolved {
+        let existing = existing_keys(&sql_storage::table_path(database, &referenced_table_name), &referenced_columns, &keys)?;
+        let (keys, existing) = keys.into_iter().zip(existing).filter(|(_, exists)| *exists != Some(true)).unzip::<_, _, Vec<_>, Vec<_>>();
+
+        if let Some((_, _, row, position)) = referencing_rows(&sql_storage::table_path(database, &referencing_table_name), &columns, &keys)?.into_iter().find(|(_, version, ..)| !visibility.is_pending(*version)) {
+            return match existing[position] {
+                Some(_) => Err(missing_reference(row_values(&row, &columns), &name, &referenced_table_name)),
+                None => Err(sql_transactions::conflict().to_string())
+            };
+        };
+    }
+
+    Ok(())
+}
+
+/// Check foreign keys of all rows of table "table_name" (e.g: after its definition has been changed by ALTER TABLE) and whether foreign keys which reffer to table still reffer to its key.
+/// Rows are read page after page and their foreign keys are checked together for each batch of rows
+pub fn check_foreign_keys(database: &Path, table_name: &str) -> Result<(), String> {
+    let definitions = load_tables(database, sql_storage::read_definition)?;
+    check_definitions(table_name, &definitions)?;
+    if definitions.get(table_name).is_none_or(|definition| referenced_tables(definition).next().is_none()) {
+        return Ok(());
+    };
+
+    let mut stored_rows = sql_storage::table_rows(&sql_storage::table_path(database, table_name)).map_err(|_| format!("Couldn't read table \"{}\"", table_name))?;
+    loop {
+        let rows = stored_rows.next_rows(sql_storage::ROWS_BATCH).map_err(|_| format!("Couldn't read table \"{}\"", table_name))?;
+        if rows.is_empty() {
+            return Ok(());
+        };
+        check_inserted_rows(database, table_name, &definitions, &rows)?;
+    }
+}
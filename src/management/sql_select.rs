@@ -0,0 +1,1301 @@
+/* Compute results of SELECT query from tables attached to "FROM" clause (also these joined by "JOIN" clauses) */
+use std::{path::{Path, PathBuf}, cmp::Ordering, collections::{HashMap, HashSet}};
+
+use sqlparser::ast::{Expr, FunctionArg, FunctionArgExpr, JoinOperator, JoinConstraint, OrderByExpr, Query, SetExpr, SetOperator, SelectItem, ObjectName, TableFactor, TableWithJoins, Value as SQLParserValue};
+
+use super::sql_json::{JsonSQLTable, JsonSQLTableColumn, JsonSQLTableColumnRow, SupportedSQLDataTypes};
+use super::{sql_storage, sql_index};
+use super::sql_expr::{RowsSetColumn, RowValue, ExprContext, OuterRow, DECIMAL_MAX_PRECISION, column_position, compare, value_key, aggregate_function, contains_aggregate, expression_type, evaluate, matches, value_matches};
+
+#[derive(Debug, Clone)]
+/// Table attached to "FROM" clause of SELECT query
+pub struct SelectFromTable {
+    /// path to table file
+    pub path: PathBuf,
+    /// name under which table columns can be reffered in query (table alias or table name when alias wasn't attached)
+    pub qualifier: String,
+    /// how table is joined with tables attached before it. None only for first table from "FROM" clause
+    pub join: Option<JoinOperator>,
+}
+
+#[derive(Debug, Clone)]
+/// SELECT query in form in which its results are computed
+pub struct SelectQuery {
+    /// directory of database in which query is computed (subqueries are computed in it too)
+    pub database: PathBuf,
+    /// from where result rows come: single SELECT or set operation on results of other queries
+    pub body: SelectQueryBody,
+    pub order_by: Vec<OrderByExpr>,
+    /// maximal count of returned rows
+    pub limit: Option<usize>,
+    /// count of skipped rows
+    pub offset: usize,
+}
+
+#[derive(Debug, Clone)]
+/// Single SELECT (without set operations, "ORDER BY", "LIMIT" and "OFFSET")
+pub struct SelectBody {
+    /// tables from "FROM" clause (with joins)
+    pub tables: Vec<SelectFromTable>,
+    pub projection: Vec<SelectProjectionItem>,
+    /// condition from "WHERE" clause
+    pub condition: Option<Expr>,
+    pub group_by: Vec<Expr>,
+    pub having: Option<Expr>,
+}
+
+#[derive(Debug, Clone)]
+/// Body of SELECT query (query without "ORDER BY", "LIMIT" and "OFFSET")
+pub enum SelectQueryBody {
+    Select(Box<SelectBody>), // 1. single SELECT
+    SetOperation(SetOperator, bool, Box<SelectQueryBody>, Box<SelectQueryBody>), // 1. "UNION", "INTERSECT" or "EXCEPT", 2. whether duplicated rows are kept ("ALL"), 3. left query, 4. right query
+    Query(Box<SelectQuery>) // 1. query in parentheses (can have got own "ORDER BY", "LIMIT" and "OFFSET")
+}
+
+impl SelectQuery {
+    /// Convert parsed SELECT query. Each table from "FROM" clause must exists in database and queries combined by set operation must return compatible columns.
+    /// When query can't be computed then reason is returned as error
+    pub fn from_query(database: &Path, query: &Query) -> Result<SelectQuery, String> {
+        let limit = match &query.limit { // LIMIT ...
+            Some(limit) => Some(rows_count(limit).ok_or_else(|| "LIMIT must be a non-negative integer".to_string())?),
+            None => None
+        };
+        let offset = match &query.offset { // OFFSET ...
+            Some(offset) => rows_count(&offset.value).ok_or_else(|| "OFFSET must be a non-negative integer".to_string())?,
+            None => 0
+        };
+
+        Ok(SelectQuery {
+            database: database.to_path_buf(),
+            body: SelectQueryBody::from_set_expr(database, &query.body)?,
+            order_by: query.order_by.clone(), // ORDER BY ...
+            limit,
+            offset
+        })
+    }
+}
+
+impl SelectQueryBody {
+    /// Convert body of parsed SELECT query
+    fn from_set_expr(database: &Path, body: &SetExpr) -> Result<SelectQueryBody, String> {
+        match body {
+            SetExpr::Select(select_query) => {
+                Ok(SelectQueryBody::Select(Box::new(SelectBody {
+                    tables: select_from_tables(database, &select_query.from)?, // ... FROM "table_name" [JOIN "table_name" ON ...]
+                    projection: projection_items(&select_query.projection)?, // SELECT ...
+                    condition: select_query.selection.clone(), // WHERE ...
+                    group_by: select_query.group_by.clone(), // GROUP BY ...
+                    having: select_query.having.clone(), // HAVING ...
+                })))
+            },
+            SetExpr::SetOperation { op, all, left, right } => { // ... UNION [ALL] ..., ... INTERSECT [ALL] ..., ... EXCEPT [ALL] ...
+                let left = SelectQueryBody::from_set_expr(database, left)?;
+                let right = SelectQueryBody::from_set_expr(database, right)?;
+
+                // Both queries must return same count of columns with compatible data types
+                let columns_error = |_| format!("Couldn't obtain columns of queries combined by {}. Check whether all reffered columns exists and aren't ambiguous", op);
+                let l_columns = left.columns().map_err(columns_error)?;
+                let r_columns = right.columns().map_err(columns_error)?;
+                if l_columns.len() != r_columns.len() {
+                    return Err(format!("Queries combined by {} must return the same count of columns ({} and {} given)", op, l_columns.len(), r_columns.len()));
+                };
+                for (column_id, (l_column, r_column)) in l_columns.iter().zip(r_columns.iter()).enumerate() {
+                    if common_type(&l_column.d_type, &r_column.d_type).is_none() {
+                        return Err(format!("Column {} of queries combined by {} has got incompatible data types ({:?} and {:?})", column_id + 1, op, l_column.d_type, r_column.d_type));
+                    };
+                }
+
+                Ok(SelectQueryBody::SetOperation(op.clone(), *all, Box::new(left), Box::new(right)))
+            },
+            SetExpr::Query(query) => Ok(SelectQueryBody::Query(Box::new(SelectQuery::from_query(database, query)?))), // (SELECT ...)
+            _ => Err("Not supported query".to_string())
+        }
+    }
+
+    /// Names (or aliases) of all tables from which query takes rows
+    pub fn qualifiers(&self) -> Vec<String> {
+        match self {
+            SelectQueryBody::Select(select_body) => select_body.tables.iter().map(|table| table.qualifier.clone()).collect::<Vec<_>>(),
+            SelectQueryBody::SetOperation(_, _, left, right) => [left.qualifiers(), right.qualifiers()].concat(),
+            SelectQueryBody::Query(query) => query.body.qualifiers()
+        }
+    }
+
+    /// Columns returned by query. Obtained only from tables columns so rows aren't computed
+    fn columns(&self) -> Result<Vec<JsonSQLTableColumn>, ()> {
+        match self {
+            SelectQueryBody::Select(select_body) => {
+                let mut result: Option<RowsSet> = None;
+                for table in &select_body.tables {
+                    let table_set = RowsSet {
+                        rows: vec![],
+                        ..RowsSet::from_table(&table.path, &table.qualifier)?
+                    };
+
+                    result = match (result, &table.join) {
+                        (None, _) => Some(table_set),
+                        (Some(left), Some(operator)) => Some(join(left, table_set, operator, &ExprContext::default())?),
+                        (Some(_), None) => return Err(())
+                    };
+                }
+                let rows_set = result.ok_or(())?;
+
+                Ok(selected_columns(&rows_set.columns, &select_body.projection)?
+                    .into_iter()
+                    .map(|column| JsonSQLTableColumn { name: column.name, d_type: column.d_type, constraints: None })
+                    .collect::<Vec<_>>())
+            },
+            SelectQueryBody::SetOperation(_, _, left, right) => combined_columns(left.columns()?, &right.columns()?),
+            SelectQueryBody::Query(query) => query.body.columns()
+        }
+    }
+}
+
+/// Columns of results of set operation: names come from left query and data types are common for both queries
+fn combined_columns(left: Vec<JsonSQLTableColumn>, right: &[JsonSQLTableColumn]) -> Result<Vec<JsonSQLTableColumn>, ()> {
+    if left.len() != right.len() {
+        return Err(());
+    };
+
+    left.into_iter()
+        .zip(right.iter())
+        .map(|(l_column, r_column)| Ok(JsonSQLTableColumn { d_type: common_type(&l_column.d_type, &r_column.d_type).ok_or(())?, ..l_column }))
+        .collect::<Result<Vec<_>, ()>>()
+}
+
+/// Data type to which values of both types can be converted without loss. None when types are incompatible
+fn common_type(left: &SupportedSQLDataTypes, right: &SupportedSQLDataTypes) -> Option<SupportedSQLDataTypes> {
+    use SupportedSQLDataTypes::*;
+
+    match (left, right) {
+        (l_type, r_type) if l_type == r_type => Some(l_type.clone()),
+        (NULL, other) | (other, NULL) => Some(other.clone()), // e.g: "SELECT NULL"
+        (INT | FLOAT, INT | FLOAT) => Some(FLOAT),
+        // Decimal keeps all integer digits and digits after decimal point of both types
+        (DECIMAL(l_precision, l_scale), DECIMAL(r_precision, r_scale)) => {
+            let scale = *l_scale.max(r_scale);
+            let integer_digits = (l_precision - l_scale).max(r_precision - r_scale);
+            Some(DECIMAL((integer_digits + scale).min(DECIMAL_MAX_PRECISION), scale))
+        },
+        (DECIMAL(_, scale), INT) | (INT, DECIMAL(_, scale)) => Some(DECIMAL(DECIMAL_MAX_PRECISION, *scale)),
+        (DECIMAL(..), FLOAT) | (FLOAT, DECIMAL(..)) => Some(FLOAT),
+        (VARCHAR(Some(l_len)), VARCHAR(Some(r_len))) => Some(VARCHAR(Some(*l_len.max(r_len)))),
+        (VARCHAR(_), VARCHAR(_)) => Some(VARCHAR(None)),
+        (LONGTEXT, TEXT | VARCHAR(_) | LONGTEXT) | (TEXT | VARCHAR(_), LONGTEXT) => Some(LONGTEXT),
+        (TEXT | VARCHAR(_), TEXT | VARCHAR(_)) => Some(TEXT),
+        (DATE | DATETIMESTAMP, DATE | DATETIMESTAMP) => Some(DATETIMESTAMP),
+        _ => None
+    }
+}
+
+/// Obtain rows count from "LIMIT" or "OFFSET" clause. Only non-negative integer literals are accepted
+fn rows_count(expr: &Expr) -> Option<usize> {
+    match expr {
+        Expr::Value(SQLParserValue::Number(num, _)) => num.parse::<usize>().ok(),
+        _ => None
+    }
+}
+
+/// Convert SELECT projection to items from which result columns are computed
+fn projection_items(projection: &[SelectItem]) -> Result<Vec<SelectProjectionItem>, String> {
+    if projection.is_empty() {
+        return Err("Incompatible projection values!".to_string());
+    };
+
+    let mut items = vec![] as Vec<SelectProjectionItem>;
+    for item in projection {
+        match item {
+            SelectItem::Wildcard => { // select all result fields from record
+                items.push(SelectProjectionItem::Wildcard);
+            },
+            SelectItem::UnnamedExpr(inside) => { // single attribute to display
+                match inside {
+                    Expr::Identifier(ident) if !ident.value.is_empty() => items.push(SelectProjectionItem::Expr(inside.clone(), ident.value.clone())),
+                    // attribute from specific table e.g: "users.name"
+                    Expr::CompoundIdentifier(idents) if idents.len() == 2 && idents.iter().all(|ident| !ident.value.is_empty()) => {
+                        items.push(SelectProjectionItem::Expr(inside.clone(), format!("{}.{}", idents[0].value, idents[1].value)))
+                    },
+                    // Don't allow to empty field names
+                    Expr::Identifier(_) | Expr::CompoundIdentifier(_) => return Err("Incompatible projection values!".to_string()),
+                    // computed value e.g: "price * qty", "COUNT(*)" returned under its text form
+                    _ => items.push(SelectProjectionItem::Expr(inside.clone(), inside.to_string()))
+                }
+            },
+            SelectItem::ExprWithAlias { expr, alias } if !alias.value.is_empty() => { // value returned under alias name e.g: "price * qty AS total"
+                items.push(SelectProjectionItem::Expr(expr.clone(), alias.value.clone()))
+            },
+            SelectItem::QualifiedWildcard(ObjectName(table)) if table.len() == 1 => { // all result fields from specific table e.g: "users.*"
+                items.push(SelectProjectionItem::QualifiedWildcard(table[0].value.clone()))
+            },
+            _ => return Err("Incompatible projection values!".to_string())
+        }
+    }
+
+    Ok(items)
+}
+
+/// Whether query uses constructs which aren't supported by own engine (common table expressions, derived tables, aliases of table columns or window functions).
+/// Such queries are computed by DataFusion
+pub fn uses_unsupported_constructs(query: &Query) -> bool {
+    query.with.is_some() || query.order_by.iter().any(|order| contains_window_function(&order.expr)) || body_uses_unsupported_constructs(&query.body)
+}
+
+/// Whether body of query uses constructs which aren't supported by own engine
+fn body_uses_unsupported_constructs(body: &SetExpr) -> bool {
+    match body {
+        SetExpr::Select(select_query) => {
+            let unsupported_relation = |relation: &TableFactor| match relation {
+                TableFactor::Table { alias, .. } => alias.as_ref().is_some_and(|alias| !alias.columns.is_empty()),
+                _ => true
+            };
+            let unsupported_item = |item: &SelectItem| match item {
+                SelectItem::UnnamedExpr(expr) | SelectItem::ExprWithAlias { expr, .. } => contains_window_function(expr),
+                _ => false
+            };
+
+            select_query.from.iter().any(|table| unsupported_relation(&table.relation) || table.joins.iter().any(|join| unsupported_relation(&join.relation)))
+                || select_query.projection.iter().any(unsupported_item)
+        },
+        SetExpr::SetOperation { left, right, .. } => body_uses_unsupported_constructs(left) || body_uses_unsupported_constructs(right),
+        SetExpr::Query(query) => uses_unsupported_constructs(query),
+        _ => false
+    }
+}
+
+/// Whether expression contains call of window function (function with "OVER" clause)
+fn contains_window_function(expr: &Expr) -> bool {
+    match expr {
+        Expr::Function(function) => function.over.is_some() || function.args.iter().any(|arg| match arg {
+            FunctionArg::Unnamed(FunctionArgExpr::Expr(arg)) | FunctionArg::Named { arg: FunctionArgExpr::Expr(arg), .. } => contains_window_function(arg),
+            _ => false
+        }),
+        Expr::Nested(inside) | Expr::UnaryOp { expr: inside, .. } | Expr::Cast { expr: inside, .. } => contains_window_function(inside),
+        Expr::BinaryOp { left, right, .. } => contains_window_function(left) || contains_window_function(right),
+        _ => false
+    }
+}
+
+/// Obtain tables attached to "FROM" clause of SELECT query together with the way in which they're joined. Each table must exists in database
+fn select_from_tables(database: &Path, from: &[TableWithJoins]) -> Result<Vec<SelectFromTable>, String> {
+    let mut tables = vec![] as Vec<SelectFromTable>;
+    for (from_id, table_with_joins) in from.iter().enumerate() {
+        // Tables separated by comma are joined as "CROSS JOIN"
+        let first_join = if from_id == 0 { None } else { Some(JoinOperator::CrossJoin) };
+        let relations = std::iter::once((&table_with_joins.relation, first_join))
+            .chain(table_with_joins.joins.iter().map(|join| (&join.relation, Some(join.join_operator.clone()))));
+
+        for (relation, join) in relations {
+            if let TableFactor::Table { name, alias, args: _, with_hints: _ } = relation {
+                // table name length must not be empty e.g: empty quotes "" or ''
+                let table_name = match name.0.last() {
+                    Some(table_name) if !table_name.value.is_empty() => table_name.value.clone(),
+                    _ => return Err("Table name doesn't fullfill requirements".to_string())
+                };
+                let qualifier = match alias {
+                    Some(alias) if alias.columns.is_empty() => alias.name.value.clone(),
+                    Some(_) => return Err("Aliases for table columns aren't supported".to_string()),
+                    None => table_name.clone()
+                };
+
+                // Same name can't reffer to 2 tables
+                if tables.iter().any(|table| table.qualifier == qualifier) {
+                    return Err(format!("Table name \"{}\" is specified more than once. Use alias to distinguish tables", qualifier));
+                };
+
+                let path = sql_storage::table_path(database, &table_name);
+                if !path.exists() {
+                    return Err(format!("Table \"{}\" given by you doesn't exists in database to which you're connected", table_name));
+                };
+
+                tables.push(SelectFromTable { path, qualifier, join });
+            }
+            else {
+                return Err("Couldn't obtain table name".to_string());
+            }
+        }
+    }
+
+    Ok(tables)
+}
+
+/// Compute results of SELECT query. Outer row is attached when query is correlated subquery
+pub fn select(query: &SelectQuery, outer: Option<&OuterRow>) -> Result<(Vec<JsonSQLTableColumn>, Vec<Vec<JsonSQLTableColumnRow>>), ()> {
+    let context = ExprContext { database: Some(&query.database), group: None, outer };
+    let results_projection = [SelectProjectionItem::Wildcard];
+
+    let (rows_groups, projection) = match &query.body {
+        SelectQueryBody::Select(select_body) => {
+            // Compute rows from all tables from "FROM" clause (joined when there are more of them). From each table are taken only rows found by its index when index can be used for 'WHERE'
+            let rows_set = rows_from_tables(&select_body.tables, select_body.condition.as_ref(), &context)?;
+
+            //... Search results from conditions from 'WHERE'
+            let rows_set = match &select_body.condition {
+                Some(condition) => filter(rows_set, condition, &context)?,
+                None => rows_set
+            };
+
+            // Aggregate results by 'GROUP BY' and leave only groups from 'HAVING'
+            (aggregate(rows_set, &select_body.group_by, select_body.having.as_ref(), &select_body.projection, &context)?, select_body.projection.as_slice())
+        },
+        body => {
+            // Results of set operation can be only sorted by their columns and returned whole
+            let (columns, rows) = body_results(&query.database, body, outer)?;
+            // Results have got no table name so their columns are reffered only by names
+            let columns = columns
+                .into_iter()
+                .map(|column| RowsSetColumn { table: None, name: column.name, d_type: column.d_type, hidden: false })
+                .collect::<Vec<_>>();
+            let groups = rows
+                .into_iter()
+                .map(|row| RowsGroup { row, rows: None })
+                .collect::<Vec<_>>();
+
+            (RowsGroups { columns, groups }, results_projection.as_slice())
+        }
+    };
+
+    // Sort results by expressions from 'ORDER BY' and leave only range of them selected by 'LIMIT' and 'OFFSET'
+    let rows_groups = order(rows_groups, &query.order_by, projection, &context)?;
+    let rows_groups = paginate(rows_groups, query.limit, query.offset);
+
+    // Return only fields for columns which user would like to get
+    project(&rows_groups, projection, &context)
+}
+
+/// Columns of query results with cells of each result row (in same order as columns)
+type BodyResults = (Vec<JsonSQLTableColumn>, Vec<Vec<RowValue>>);
+
+/// Compute result columns and rows of query body. Results of set operation are computed from results of both combined queries
+fn body_results(database: &Path, body: &SelectQueryBody, outer: Option<&OuterRow>) -> Result<BodyResults, ()> {
+    let (columns, rows) = match body {
+        SelectQueryBody::SetOperation(operator, all, left, right) => {
+            let (l_columns, l_rows) = body_results(database, left, outer)?;
+            let (r_columns, r_rows) = body_results(database, right, outer)?;
+            let columns = combined_columns(l_columns, &r_columns)?;
+
+            // Rows are equal when all their values are equal (NULL is treated as equal to NULL). Values are compared in type common for both queries
+            let row_key = |row: &Vec<RowValue>| format!("{:?}", row.iter().zip(columns.iter()).map(|(cell, column)| cell.clone().of_type(&column.d_type)).collect::<Vec<_>>());
+            let mut right_counts = HashMap::<String, usize>::new();
+            for row in &r_rows {
+                *right_counts.entry(row_key(row)).or_insert(0) += 1;
+            }
+
+            let candidates = match operator {
+                SetOperator::Union => [l_rows, r_rows].concat(),
+                _ => l_rows
+            };
+            let mut seen = HashSet::<String>::new();
+            let mut rows = vec![] as Vec<Vec<RowValue>>;
+            for row in candidates {
+                let key = row_key(&row);
+                // With "ALL" each row from right query matches only one row from left query
+                let in_right = match right_counts.get_mut(&key) {
+                    Some(count) if *count > 0 => {
+                        if *all { *count -= 1 };
+                        true
+                    },
+                    _ => false
+                };
+                let keep = match operator {
+                    SetOperator::Union => true,
+                    SetOperator::Intersect => in_right,
+                    SetOperator::Except => !in_right
+                };
+
+                // Without "ALL" duplicated rows are returned once
+                if keep && (*all || seen.insert(key)) {
+                    rows.push(row);
+                };
+            }
+
+            return Ok((columns, rows));
+        },
+        SelectQueryBody::Query(query) => select(query, outer)?,
+        select_body => select(&SelectQuery { database: database.to_path_buf(), body: select_body.clone(), order_by: vec![], limit: None, offset: 0 }, outer)?
+    };
+
+    Ok((columns, rows.into_iter().map(|row| row.into_iter().map(|cell| cell.value).collect::<Vec<_>>()).collect::<Vec<_>>()))
+}
+
+/// Values of table row in order of table columns. Cells in saved row don't have to be in same order as table columns (e.g: after INSERT for specific columns) so they are placed by column name
+pub fn table_row_values(columns: &[JsonSQLTableColumn], row: &[JsonSQLTableColumnRow]) -> Vec<RowValue> {
+    columns
+        .iter()
+        .map(|column| {
+            row.iter()
+                .find(|cell| cell.col == column.name)
+                .map_or(RowValue::Null, |cell| cell.value.clone())
+        })
+        .collect::<Vec<_>>()
+}
+
+/// Rows computed durning SELECT query. Each row has got cells in same order as "columns"
+#[derive(Debug, Clone)]
+pub struct RowsSet {
+    pub columns: Vec<RowsSetColumn>,
+    pub rows: Vec<Vec<RowValue>>,
+}
+
+impl RowsSet {
+    /// Load table from file and represent it as rows set whose columns can be reffered by "qualifier". Rows are read from file one by one so they aren't kept in memory twice (as table rows and as rows set)
+    pub fn from_table(path: &Path, qualifier: &str) -> Result<RowsSet, ()> {
+        let table_rows = sql_storage::table_rows(path).map_err(|_| ())?;
+        let mut rows_set = RowsSet::from_json_table(table_rows.definition(), qualifier);
+        let table_columns = table_rows.definition().columns.clone();
+
+        for row in table_rows {
+            rows_set.rows.push(table_row_values(&table_columns, &row.map_err(|_| ())?));
+        }
+
+        Ok(rows_set)
+    }
+
+    /// Load only rows stored under given locations in table file (e.g: found by index) and represent them as rows set whose columns can be reffered by "qualifier"
+    pub fn from_table_rows(path: &Path, qualifier: &str, locations: &[sql_storage::RowLocation]) -> Result<RowsSet, ()> {
+        let (table, _) = sql_storage::read_table_rows_at(path, locations).map_err(|_| ())?;
+
+        Ok(RowsSet::from_json_table(&table, qualifier))
+    }
+
+    /// Represent loaded table as rows set whose columns can be reffered by "qualifier". Rows are in same order as in table
+    pub fn from_json_table(table: &JsonSQLTable, qualifier: &str) -> RowsSet {
+        let columns = table.columns
+            .iter()
+            .map(|column| RowsSetColumn {
+                table: Some(qualifier.to_string()),
+                name: column.name.clone(),
+                d_type: column.d_type.clone(),
+                hidden: false
+            })
+            .collect::<Vec<_>>();
+
+        let rows = table.rows
+            .iter()
+            .flatten()
+            .map(|row| table_row_values(&table.columns, row))
+            .collect::<Vec<_>>();
+
+        RowsSet { columns, rows }
+    }
+
+    /// Get position of column reffered by name (and optionaly by table name).
+    /// Ok(None) is returned when column doesn't exists and Err(()) when column name without table name is ambiguous
+    pub fn column_position(&self, table: Option<&str>, name: &str) -> Result<Option<usize>, ()> {
+        column_position(&self.columns, table, name)
+    }
+
+}
+
+/// Name under which column is returned to user. Column is qualified by table name only when other column with same name exists in rows set
+fn output_name(columns: &[RowsSetColumn], position: usize) -> String {
+    let column = &columns[position];
+    let same_name_count = columns.iter().filter(|col| col.name == column.name).count();
+
+    match &column.table {
+        Some(table) if same_name_count > 1 => format!("{}.{}", table, column.name),
+        _ => column.name.clone()
+    }
+}
+
+/// Compute rows set from all tables attached to "FROM" clause. Tables are joined in same order in that they were attached to query.
+/// From each table are taken only rows found by its index when index can be used for condition from 'WHERE' (comparison of column of table with literal joined by "AND" with rest of condition isn't true for other rows of table and for NULL which replaces them in outer join)
+pub fn rows_from_tables(tables: &[SelectFromTable], condition: Option<&Expr>, context: &ExprContext) -> Result<RowsSet, ()> {
+    let mut result: Option<RowsSet> = None;
+
+    for table in tables {
+        let table_set = match condition.and_then(|condition| sql_index::candidate_locations(&table.path, &table.qualifier, condition)) {
+            Some(locations) => RowsSet::from_table_rows(&table.path, &table.qualifier, &locations)?,
+            None => RowsSet::from_table(&table.path, &table.qualifier)?
+        };
+
+        result = match (result, &table.join) {
+            (None, _) => Some(table_set),
+            (Some(left), Some(operator)) => Some(join(left, table_set, operator, context)?),
+            (Some(_), None) => return Err(()) // each next table must be joined in some way
+        };
+    }
+
+    // Query without "FROM" clause (e.g: SELECT nextval('orders_seq')) is computed for single row without columns
+    Ok(result.unwrap_or(RowsSet { columns: vec![], rows: vec![vec![]] }))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum JoinKind {
+    Inner,
+    Left,
+    Right,
+    Full
+}
+
+/// Join 2 rows sets using nested loop
+fn join(left: RowsSet, right: RowsSet, operator: &JoinOperator, context: &ExprContext) -> Result<RowsSet, ()> {
+    let (kind, constraint) = match operator {
+        JoinOperator::Inner(constraint) => (JoinKind::Inner, constraint.clone()),
+        JoinOperator::LeftOuter(constraint) => (JoinKind::Left, constraint.clone()),
+        JoinOperator::RightOuter(constraint) => (JoinKind::Right, constraint.clone()),
+        JoinOperator::FullOuter(constraint) => (JoinKind::Full, constraint.clone()),
+        JoinOperator::CrossJoin => (JoinKind::Inner, JoinConstraint::None),
+        _ => return Err(()) // CROSS APPLY and OUTER APPLY aren't supported
+    };
+
+    // Columns by which tables are joined for "USING (..)" and "NATURAL" joins
+    let using_columns = match &constraint {
+        JoinConstraint::Using(idents) => idents.iter().map(|ident| ident.value.clone()).collect::<Vec<_>>(),
+        JoinConstraint::Natural => {
+            left.columns
+                .iter()
+                .filter(|l_col| !l_col.hidden && right.columns.iter().any(|r_col| !r_col.hidden && r_col.name == l_col.name))
+                .map(|l_col| l_col.name.clone())
+                .collect::<Vec<_>>()
+        },
+        _ => vec![]
+    };
+    let mut using_positions = vec![] as Vec<(usize, usize)>;
+    for column_name in &using_columns {
+        let l_pos = left.column_position(None, column_name)?.ok_or(())?;
+        let r_pos = right.column_position(None, column_name)?.ok_or(())?;
+        using_positions.push((l_pos, r_pos));
+    }
+
+    // Columns of joined set: merged "USING" columns go first, after them all columns from left and right set (those merged become hidden)
+    let mut columns = using_positions
+        .iter()
+        .map(|(l_pos, _)| RowsSetColumn {
+            table: None,
+            hidden: false,
+            ..left.columns[*l_pos].clone()
+        })
+        .collect::<Vec<_>>();
+    columns.extend(left.columns.iter().enumerate().map(|(pos, column)| RowsSetColumn {
+        hidden: column.hidden || using_positions.iter().any(|(l_pos, _)| *l_pos == pos),
+        ..column.clone()
+    }));
+    columns.extend(right.columns.iter().enumerate().map(|(pos, column)| RowsSetColumn {
+        hidden: column.hidden || using_positions.iter().any(|(_, r_pos)| *r_pos == pos),
+        ..column.clone()
+    }));
+
+    let left_nulls = vec![RowValue::Null; left.columns.len()];
+    let right_nulls = vec![RowValue::Null; right.columns.len()];
+    let combine = |l_row: &Vec<RowValue>, r_row: &Vec<RowValue>| {
+        let mut row = using_positions
+            .iter()
+            .map(|(l_pos, r_pos)| match &l_row[*l_pos] {
+                RowValue::Null => r_row[*r_pos].clone(),
+                l_value => l_value.clone()
+            })
+            .collect::<Vec<_>>();
+        row.extend(l_row.iter().cloned());
+        row.extend(r_row.iter().cloned());
+        row
+    };
+
+    // Whether combined row fullfils join condition
+    let row_matches = |row: &Vec<RowValue>| -> Result<bool, ()> {
+        match &constraint {
+            JoinConstraint::On(condition) => matches(condition, &columns, row, context),
+            JoinConstraint::Using(_) | JoinConstraint::Natural => {
+                let offset = using_positions.len();
+                Ok(using_positions.iter().all(|(l_pos, r_pos)| {
+                    compare(&row[offset + l_pos], &row[offset + left.columns.len() + r_pos]) == Some(Ordering::Equal)
+                }))
+            },
+            JoinConstraint::None => Ok(true)
+        }
+    };
+
+    let mut rows = vec![] as Vec<Vec<RowValue>>;
+    let mut right_matched = vec![false; right.rows.len()];
+    for l_row in &left.rows {
+        let mut left_matched = false;
+
+        for (r_id, r_row) in right.rows.iter().enumerate() {
+            let row = combine(l_row, r_row);
+            if row_matches(&row)? {
+                left_matched = true;
+                right_matched[r_id] = true;
+                rows.push(row);
+            }
+        }
+
+        // Left row without pair is attached with nulls in place of right row cells
+        if !left_matched && matches!(kind, JoinKind::Left | JoinKind::Full) {
+            rows.push(combine(l_row, &right_nulls));
+        }
+    }
+
+    // Right rows without pair are attached with nulls in place of left row cells
+    if matches!(kind, JoinKind::Right | JoinKind::Full) {
+        for (r_id, r_row) in right.rows.iter().enumerate() {
+            if !right_matched[r_id] {
+                rows.push(combine(&left_nulls, r_row));
+            }
+        }
+    }
+
+    Ok(RowsSet { columns, rows })
+}
+
+/// Leave in rows set only rows which fullfil condition from 'WHERE'
+pub fn filter(rows_set: RowsSet, condition: &Expr, context: &ExprContext) -> Result<RowsSet, ()> {
+    let mut rows = vec![] as Vec<Vec<RowValue>>;
+    for row in rows_set.rows {
+        if matches(condition, &rows_set.columns, &row, context)? {
+            rows.push(row);
+        }
+    }
+
+    Ok(RowsSet { rows, ..rows_set })
+}
+
+#[derive(Debug, Clone)]
+/// Single item from SELECT projection
+pub enum SelectProjectionItem {
+    Wildcard, // "*"
+    QualifiedWildcard(String), // 1. table name from "table.*"
+    Expr(Expr, String) // 1. expression computed for each result row (column, aggregate function), 2. name under which result is returned
+}
+
+#[derive(Debug, Clone)]
+/// Rows from which single result row is computed
+pub struct RowsGroup {
+    /// row from which are taken values of columns which aren't aggregated
+    pub row: Vec<RowValue>,
+    /// all rows aggregated into group. None when query doesn't aggregate rows
+    pub rows: Option<Vec<Vec<RowValue>>>,
+}
+
+#[derive(Debug, Clone)]
+/// Rows set divided into groups from which result rows are computed. Without aggregation each row forms own group
+pub struct RowsGroups {
+    pub columns: Vec<RowsSetColumn>,
+    pub groups: Vec<RowsGroup>,
+}
+
+impl RowsGroup {
+    /// Compute value of expression for group
+    fn evaluate(&self, expr: &Expr, columns: &[RowsSetColumn], context: &ExprContext) -> Result<RowValue, ()> {
+        evaluate(expr, columns, &self.row, &ExprContext { group: self.rows.as_deref(), ..*context })
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Value returned for projection column
+enum SelectedValue {
+    Column(usize), // 1. position of column in rows set, its cell is returned unchanged
+    Computed(Expr) // 1. expression computed for each result row
+}
+
+#[derive(Debug, Clone)]
+/// Column returned by SELECT query
+struct SelectedColumn {
+    value: SelectedValue,
+    name: String,
+    d_type: SupportedSQLDataTypes,
+}
+
+impl SelectedColumn {
+    /// Compute column cell for group
+    fn cell(&self, group: &RowsGroup, columns: &[RowsSetColumn], context: &ExprContext) -> Result<RowValue, ()> {
+        match &self.value {
+            SelectedValue::Column(position) => Ok(group.row[*position].clone()),
+            SelectedValue::Computed(expr) => group.evaluate(expr, columns, context)
+        }
+    }
+}
+
+/// Columns returned by SELECT projection with names under which they're returned
+fn selected_columns(columns: &[RowsSetColumn], projection: &[SelectProjectionItem]) -> Result<Vec<SelectedColumn>, ()> {
+    let mut selected = vec![] as Vec<SelectedColumn>;
+    let from_position = |position: usize| SelectedColumn {
+        value: SelectedValue::Column(position),
+        name: output_name(columns, position),
+        d_type: columns[position].d_type.clone()
+    };
+
+    for item in projection {
+        match item {
+            SelectProjectionItem::Wildcard => {
+                for (position, column) in columns.iter().enumerate() {
+                    if !column.hidden {
+                        selected.push(from_position(position));
+                    }
+                }
+            },
+            SelectProjectionItem::QualifiedWildcard(table) => {
+                let table_positions = columns
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, column)| column.table.as_deref() == Some(table))
+                    .map(|(position, _)| position)
+                    .collect::<Vec<_>>();
+
+                if table_positions.is_empty() {
+                    return Err(());
+                };
+
+                for position in table_positions {
+                    selected.push(from_position(position));
+                }
+            },
+            SelectProjectionItem::Expr(expr, name) => {
+                // Plain columns are returned without change and must exists
+                let position = match expr {
+                    Expr::Identifier(ident) => Some(column_position(columns, None, &ident.value)?.ok_or(())?),
+                    Expr::CompoundIdentifier(idents) if idents.len() == 2 => Some(column_position(columns, Some(&idents[0].value), &idents[1].value)?.ok_or(())?),
+                    _ => None
+                };
+
+                selected.push(SelectedColumn {
+                    value: position.map_or_else(|| SelectedValue::Computed(expr.clone()), SelectedValue::Column),
+                    name: name.clone(),
+                    d_type: expression_type(expr, columns)
+                });
+            }
+        }
+    }
+
+    Ok(selected)
+}
+
+/// Divide rows into groups by "GROUP BY" expressions and leave only groups which fullfil "HAVING" condition.
+/// Rows are aggregated when query has got "GROUP BY" or "HAVING" clause or aggregate function in projection, otherwise each row is placed in separate group
+pub fn aggregate(rows_set: RowsSet, group_by: &[Expr], having: Option<&Expr>, projection: &[SelectProjectionItem], context: &ExprContext) -> Result<RowsGroups, ()> {
+    let projection_aggregates = projection.iter().any(|item| matches!(item, SelectProjectionItem::Expr(expr, _) if contains_aggregate(expr)));
+    if group_by.is_empty() && having.is_none() && !projection_aggregates {
+        let groups = rows_set.rows
+            .into_iter()
+            .map(|row| RowsGroup { row, rows: None })
+            .collect::<Vec<_>>();
+        return Ok(RowsGroups { columns: rows_set.columns, groups });
+    };
+
+    // Columns which aren't aggregated must be attached to "GROUP BY" clause
+    let projection_exprs = projection
+        .iter()
+        .map(|item| match item {
+            SelectProjectionItem::Expr(expr, _) => Ok(expr),
+            _ => Err(()) // "*" can't be used with aggregation
+        })
+        .collect::<Result<Vec<_>, ()>>()?;
+    for expr in projection_exprs.into_iter().chain(having) {
+        if !is_grouped(expr, group_by, &rows_set.columns)? {
+            return Err(());
+        };
+    }
+
+    let mut groups = vec![] as Vec<RowsGroup>;
+    if group_by.is_empty() {
+        // Whole rows set is single group, even when it is empty
+        groups.push(RowsGroup {
+            row: rows_set.rows.first().cloned().unwrap_or_else(|| vec![RowValue::Null; rows_set.columns.len()]),
+            rows: Some(rows_set.rows)
+        });
+    }
+    else {
+        // Group position under its keys representation
+        let mut groups_positions = HashMap::<Vec<String>, usize>::new();
+        for row in rows_set.rows {
+            let keys = group_by
+                .iter()
+                .map(|expr| evaluate(expr, &rows_set.columns, &row, context).map(|value| value_key(&value)))
+                .collect::<Result<Vec<_>, ()>>()?;
+
+            match groups_positions.get(&keys) {
+                Some(position) => groups[*position].rows.get_or_insert_with(Vec::new).push(row),
+                None => {
+                    groups_positions.insert(keys, groups.len());
+                    groups.push(RowsGroup { row: row.clone(), rows: Some(vec![row]) });
+                }
+            }
+        }
+    }
+
+    //... Leave only groups from "HAVING"
+    if let Some(condition) = having {
+        let mut having_groups = vec![] as Vec<RowsGroup>;
+        for group in groups {
+            if value_matches(group.evaluate(condition, &rows_set.columns, context)?)? {
+                having_groups.push(group);
+            }
+        }
+        groups = having_groups;
+    };
+
+    Ok(RowsGroups { columns: rows_set.columns, groups })
+}
+
+/// Whether expression uses only columns from "GROUP BY" clause outside of aggregate functions
+fn is_grouped(expr: &Expr, group_by: &[Expr], columns: &[RowsSetColumn]) -> Result<bool, ()> {
+    // Position of column reffered by expression
+    let reffered_column = |expr: &Expr| -> Result<Option<usize>, ()> {
+        match expr {
+            Expr::Identifier(ident) => column_position(columns, None, &ident.value),
+            Expr::CompoundIdentifier(idents) if idents.len() == 2 => column_position(columns, Some(&idents[0].value), &idents[1].value),
+            _ => Ok(None)
+        }
+    };
+
+    if group_by.contains(expr) || aggregate_function(expr).is_some() {
+        return Ok(true);
+    };
+
+    match expr {
+        Expr::Identifier(_) | Expr::CompoundIdentifier(_) => {
+            match reffered_column(expr)? {
+                Some(position) => {
+                    for group_expr in group_by {
+                        if reffered_column(group_expr)? == Some(position) {
+                            return Ok(true);
+                        };
+                    }
+                    Ok(false)
+                },
+                None => Ok(true) // not a column
+            }
+        },
+        Expr::Nested(inside) => is_grouped(inside, group_by, columns),
+        Expr::BinaryOp { left, right, .. } => Ok(is_grouped(left, group_by, columns)? && is_grouped(right, group_by, columns)?),
+        Expr::UnaryOp { expr, .. } => is_grouped(expr, group_by, columns),
+        _ => Ok(true)
+    }
+}
+
+/// Sort groups according to "ORDER BY" clause. Each expression is computed for group and compared according to its column data type.
+/// Number (e.g: "ORDER BY 2") reffers to column on that position from SELECT projection and name can reffer to column returned under alias
+pub fn order(rows_groups: RowsGroups, order_by: &[OrderByExpr], projection: &[SelectProjectionItem], context: &ExprContext) -> Result<RowsGroups, ()> {
+    if order_by.is_empty() {
+        return Ok(rows_groups);
+    };
+
+    // Columns reffered by number or alias
+    let selected = selected_columns(&rows_groups.columns, projection)?;
+    let mut keyed_groups = vec![] as Vec<(Vec<RowValue>, RowsGroup)>;
+    for group in rows_groups.groups {
+        let mut keys = vec![] as Vec<RowValue>;
+        for order_expr in order_by {
+            let key = match &order_expr.expr {
+                Expr::Value(SQLParserValue::Number(num, _)) => {
+                    let position = num.parse::<usize>().map_err(|_| ())?;
+                    let column = selected.get(position.wrapping_sub(1)).ok_or(())?;
+                    column.cell(&group, &rows_groups.columns, context)?.of_type(&column.d_type)
+                },
+                Expr::Identifier(ident) if selected.iter().any(|column| column.name == ident.value) => {
+                    let column = selected.iter().find(|column| column.name == ident.value).ok_or(())?;
+                    column.cell(&group, &rows_groups.columns, context)?.of_type(&column.d_type)
+                },
+                expr => group.evaluate(expr, &rows_groups.columns, context)?
+            };
+            keys.push(key);
+        }
+        keyed_groups.push((keys, group));
+    }
+
+    keyed_groups.sort_by(|(l_keys, _), (r_keys, _)| {
+        for (key_id, order_expr) in order_by.iter().enumerate() {
+            let ascending = order_expr.asc.unwrap_or(true);
+            // By default NULLs are treated as greater then any other value
+            let nulls_first = order_expr.nulls_first.unwrap_or(!ascending);
+
+            let ordering = match (&l_keys[key_id], &r_keys[key_id]) {
+                (RowValue::Null, RowValue::Null) => Ordering::Equal,
+                (RowValue::Null, _) => if nulls_first { Ordering::Less } else { Ordering::Greater },
+                (_, RowValue::Null) => if nulls_first { Ordering::Greater } else { Ordering::Less },
+                (l_key, r_key) => {
+                    let ordering = compare(l_key, r_key).unwrap_or(Ordering::Equal);
+                    if ascending { ordering } else { ordering.reverse() }
+                }
+            };
+
+            if ordering != Ordering::Equal {
+                return ordering;
+            };
+        }
+
+        Ordering::Equal
+    });
+
+    Ok(RowsGroups {
+        groups: keyed_groups.into_iter().map(|(_, group)| group).collect::<Vec<_>>(),
+        ..rows_groups
+    })
+}
+
+/// Skip "offset" groups and leave only "limit" groups from rest of them
+pub fn paginate(rows_groups: RowsGroups, limit: Option<usize>, offset: usize) -> RowsGroups {
+    let groups = rows_groups.groups
+        .into_iter()
+        .skip(offset)
+        .take(limit.unwrap_or(usize::MAX))
+        .collect::<Vec<_>>();
+
+    RowsGroups { groups, ..rows_groups }
+}
+
+/// Compute result rows with columns required by SELECT projection
+pub fn project(rows_groups: &RowsGroups, projection: &[SelectProjectionItem], context: &ExprContext) -> Result<(Vec<JsonSQLTableColumn>, Vec<Vec<JsonSQLTableColumnRow>>), ()> {
+    let selected = selected_columns(&rows_groups.columns, projection)?;
+
+    let columns = selected
+        .iter()
+        .map(|column| JsonSQLTableColumn {
+            name: column.name.clone(),
+            d_type: column.d_type.clone(),
+            constraints: None
+        })
+        .collect::<Vec<_>>();
+    let mut rows = vec![] as Vec<Vec<JsonSQLTableColumnRow>>;
+    for group in &rows_groups.groups {
+        let mut row = vec![] as Vec<JsonSQLTableColumnRow>;
+        for column in &selected {
+            row.push(JsonSQLTableColumnRow {
+                col: column.name.clone(),
+                value: column.cell(group, &rows_groups.columns, context)?
+            });
+        }
+        rows.push(row);
+    }
+
+    Ok((columns, rows))
+}
+
+#[cfg(test)]
+/// Projection from its short form: "all" for "*", "table.*" or column name
+fn test_projection(projection: Vec<&str>) -> Vec<SelectProjectionItem> {
+    use sqlparser::ast::Ident;
+
+    projection
+        .into_iter()
+        .map(|item| match item.split_once('.') {
+            _ if item == "all" => SelectProjectionItem::Wildcard,
+            Some((table, "*")) => SelectProjectionItem::QualifiedWildcard(table.to_string()),
+            Some((table, column)) => SelectProjectionItem::Expr(Expr::CompoundIdentifier(vec![Ident::new(table), Ident::new(column)]), item.to_string()),
+            None => SelectProjectionItem::Expr(Expr::Identifier(Ident::new(item)), item.to_string())
+        })
+        .collect::<Vec<_>>()
+}
+
+#[test]
+fn test_select_join() {
+    use super::sql_expr::value_text;
+    use super::sql_json::{process_sql, ProcessSQLSupportedQueries, ProcessSQLRowField as Field, InsertOperations};
+    use sqlparser::{dialect::AnsiDialect, parser::Parser as SqlParser, ast::{Statement, SetExpr}};
+
+    // Create tables "users" and "orders" in temporary directory
+    let dir = std::env::temp_dir().join(format!("wastledb_join_{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    super::sql_wal::set_data_path(&dir);
+    let create = |name: &str, columns: Vec<&str>, rows: Vec<Vec<&str>>| {
+        let path = sql_storage::table_path(&dir, name);
+        let d_type = |column: &str| if column == "name" { SupportedSQLDataTypes::VARCHAR(None) } else { SupportedSQLDataTypes::INT };
+        let table = process_sql(ProcessSQLSupportedQueries::CreateTable(name.to_string(), columns.iter().map(|n| (n.to_string(), d_type(n), None)).collect(), vec![])).unwrap();
+        sql_storage::write_table(&path, &table).unwrap();
+        let rows = rows.into_iter().map(|row| row.into_iter().zip(columns.iter()).map(|(val, n)| Field(val.to_string(), d_type(n))).collect::<Vec<_>>()).collect::<Vec<_>>();
+        let table = process_sql(ProcessSQLSupportedQueries::Insert(&path, None, rows, InsertOperations::Into)).unwrap();
+        sql_storage::write_table(&path, &table).unwrap();
+        path
+    };
+    let users = create("users", vec!["id", "name"], vec![vec!["1", "ann"], vec!["2", "bob"]]);
+    let orders = create("orders", vec!["id", "user_id"], vec![vec!["10", "1"], vec!["11", "1"], vec!["12", "3"], vec!["2", "3"]]);
+
+    // Compute join described by SQL query and return its rows
+    let run = |sql: &str, projection: Vec<&str>| {
+        let statement = SqlParser::parse_sql(&AnsiDialect {}, sql).unwrap().remove(0);
+        let select = match statement {
+            Statement::Query(query) => match *query.body {
+                SetExpr::Select(select) => select,
+                _ => unreachable!()
+            },
+            _ => unreachable!()
+        };
+        let from = &select.from[0];
+        let mut tables = vec![SelectFromTable { path: users.clone(), qualifier: "u".to_string(), join: None }];
+        tables.push(SelectFromTable { path: orders.clone(), qualifier: "o".to_string(), join: Some(from.joins[0].join_operator.clone()) });
+
+        let rows_set = rows_from_tables(&tables, None, &ExprContext::default()).unwrap();
+        let rows_set = match &select.selection {
+            Some(condition) => filter(rows_set, condition, &ExprContext::default()).unwrap(),
+            None => rows_set
+        };
+        let rows_groups = aggregate(rows_set, &[], None, &[], &ExprContext::default()).unwrap();
+        project(&rows_groups, &test_projection(projection), &ExprContext::default()).unwrap().1
+            .into_iter()
+            .map(|row| row.into_iter().map(|cell| value_text(cell.value)).collect::<Vec<_>>())
+            .collect::<Vec<_>>()
+    };
+    let some = |val: &str| Some(val.to_string());
+
+    let inner = run("SELECT * FROM users u INNER JOIN orders o ON u.id = o.user_id", vec!["u.name", "o.id"]);
+    assert_eq!(inner, vec![vec![some("ann"), some("10")], vec![some("ann"), some("11")]]);
+
+    let left = run("SELECT * FROM users u LEFT JOIN orders o ON u.id = o.user_id", vec!["name", "o.id"]);
+    assert_eq!(left.len(), 3);
+    assert_eq!(left[2], vec![some("bob"), None]);
+
+    let right = run("SELECT * FROM users u RIGHT JOIN orders o ON u.id = o.user_id WHERE o.id >= 11", vec!["u.name", "o.id"]);
+    assert_eq!(right, vec![vec![some("ann"), some("11")], vec![None, some("12")]]);
+
+    let full = run("SELECT * FROM users u FULL JOIN orders o ON u.id = o.user_id", vec!["all"]);
+    assert_eq!(full.len(), 5);
+
+    let cross = run("SELECT * FROM users u CROSS JOIN orders o", vec!["u.*"]);
+    assert_eq!(cross.len(), 8);
+
+    // Columns of "USING" are merged into one column placed before other columns
+    let using = run("SELECT * FROM users u JOIN orders o USING (id)", vec!["all"]);
+    assert_eq!(using, vec![vec![some("2"), some("bob"), some("3")]]);
+    let using = run("SELECT * FROM users u JOIN orders o USING (id)", vec!["id", "o.user_id"]);
+    assert_eq!(using, vec![vec![some("2"), some("3")]]);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_select_order() {
+    use super::sql_expr::value_text;
+    use sqlparser::{dialect::AnsiDialect, parser::Parser as SqlParser, ast::Statement};
+
+    let column = |name: &str, d_type: SupportedSQLDataTypes| RowsSetColumn { table: Some("t".to_string()), name: name.to_string(), d_type, hidden: false };
+    let text = |val: &str| RowValue::Text(val.to_string());
+    let rows_set = RowsSet {
+        columns: vec![column("name", SupportedSQLDataTypes::VARCHAR(None)), column("age", SupportedSQLDataTypes::INT)],
+        rows: vec![
+            vec![text("bob"), RowValue::Int(9)],
+            vec![text("ann"), RowValue::Null],
+            vec![text("cid"), RowValue::Int(10)],
+            vec![text("dan"), RowValue::Int(9)],
+        ]
+    };
+
+    // Sort rows set by "ORDER BY" clause from SQL query and return only names
+    let run = |sql: &str, limit: Option<usize>, offset: usize| {
+        let order_by = match SqlParser::parse_sql(&AnsiDialect {}, sql).unwrap().remove(0) {
+            Statement::Query(query) => query.order_by,
+            _ => unreachable!()
+        };
+        let rows_groups = aggregate(rows_set.clone(), &[], None, &[], &ExprContext::default()).unwrap();
+        let rows_groups = paginate(order(rows_groups, &order_by, &test_projection(vec!["name", "age"]), &ExprContext::default()).unwrap(), limit, offset);
+        rows_groups.groups.into_iter().map(|group| value_text(group.row[0].clone()).unwrap()).collect::<Vec<_>>()
+    };
+
+    // Integers are compared as numbers and NULLs are last by default for ascending order
+    assert_eq!(run("SELECT * FROM t ORDER BY age, name DESC", None, 0), vec!["dan", "bob", "cid", "ann"]);
+    assert_eq!(run("SELECT * FROM t ORDER BY age DESC", None, 0), vec!["ann", "cid", "bob", "dan"]);
+    assert_eq!(run("SELECT * FROM t ORDER BY age ASC NULLS FIRST", None, 0), vec!["ann", "bob", "dan", "cid"]);
+    assert_eq!(run("SELECT * FROM t ORDER BY 1 DESC", Some(2), 1), vec!["cid", "bob"]);
+    assert_eq!(run("SELECT * FROM t ORDER BY name", None, 5), vec![] as Vec<String>);
+}
+
+#[test]
+fn test_select_aggregate() {
+    use super::sql_expr::value_text;
+    use sqlparser::{dialect::AnsiDialect, parser::Parser as SqlParser, ast::{Statement, SetExpr, SelectItem}};
+
+    let column = |name: &str, d_type: SupportedSQLDataTypes| RowsSetColumn { table: Some("t".to_string()), name: name.to_string(), d_type, hidden: false };
+    let some = |val: &str| Some(val.to_string());
+    let text = |val: &str| RowValue::Text(val.to_string());
+    let rows_set = RowsSet {
+        columns: vec![column("dept", SupportedSQLDataTypes::VARCHAR(None)), column("salary", SupportedSQLDataTypes::INT)],
+        rows: vec![
+            vec![text("it"), RowValue::Int(10)],
+            vec![text("hr"), RowValue::Int(4)],
+            vec![text("it"), RowValue::Int(20)],
+            vec![text("hr"), RowValue::Null],
+            vec![text("ops"), RowValue::Int(7)],
+        ]
+    };
+
+    // Compute aggregated results of SQL query for rows set
+    let run = |sql: &str, rows_set: RowsSet| {
+        let (select, order_by) = match SqlParser::parse_sql(&AnsiDialect {}, sql).unwrap().remove(0) {
+            Statement::Query(query) => match *query.body {
+                SetExpr::Select(select) => (select, query.order_by),
+                _ => unreachable!()
+            },
+            _ => unreachable!()
+        };
+        let projection = select.projection
+            .iter()
+            .map(|item| match item {
+                SelectItem::UnnamedExpr(expr) => SelectProjectionItem::Expr(expr.clone(), expr.to_string()),
+                _ => unreachable!()
+            })
+            .collect::<Vec<_>>();
+        let rows_groups = aggregate(rows_set, &select.group_by, select.having.as_ref(), &projection, &ExprContext::default())?;
+        let rows_groups = order(rows_groups, &order_by, &projection, &ExprContext::default())?;
+        let (columns, rows) = project(&rows_groups, &projection, &ExprContext::default())?;
+        Ok::<_, ()>((columns, rows.into_iter().map(|row| row.into_iter().map(|cell| value_text(cell.value)).collect::<Vec<_>>()).collect::<Vec<_>>()))
+    };
+
+    let (columns, rows) = run("SELECT COUNT(*), COUNT(salary), SUM(salary), AVG(salary), MIN(dept), MAX(salary) FROM t", rows_set.clone()).unwrap();
+    assert_eq!(columns[0].name, "COUNT(*)");
+    assert_eq!(columns[2].d_type, SupportedSQLDataTypes::INT);
+    assert_eq!(columns[3].d_type, SupportedSQLDataTypes::FLOAT);
+    assert_eq!(rows, vec![vec![some("5"), some("4"), some("41"), some("10.25"), some("hr"), some("20")]]);
+
+    let (_, rows) = run("SELECT dept, COUNT(*), SUM(salary) FROM t GROUP BY dept HAVING COUNT(*) > 1 ORDER BY dept", rows_set.clone()).unwrap();
+    assert_eq!(rows, vec![vec![some("hr"), some("2"), some("4")], vec![some("it"), some("2"), some("30")]]);
+
+    let (_, rows) = run("SELECT dept FROM t GROUP BY dept ORDER BY SUM(salary) DESC", rows_set.clone()).unwrap();
+    assert_eq!(rows, vec![vec![some("it")], vec![some("ops")], vec![some("hr")]]);
+
+    // Aggregation of empty rows set still returns single row
+    let (_, rows) = run("SELECT COUNT(*), SUM(salary) FROM t", RowsSet { rows: vec![], ..rows_set.clone() }).unwrap();
+    assert_eq!(rows, vec![vec![some("0"), None]]);
+
+    // Numbers which are equal when compared (also of different types and decimal scales) are in single group
+    let numbers = vec![RowValue::Int(1), RowValue::Float(1.0), RowValue::Decimal(100, 2), RowValue::Decimal(10, 1), RowValue::Int(2)];
+    let (_, rows) = run("SELECT COUNT(*) FROM t GROUP BY salary ORDER BY COUNT(*)", RowsSet {
+        rows: numbers.into_iter().map(|number| vec![text("it"), number]).collect(),
+        ..rows_set.clone()
+    }).unwrap();
+    assert_eq!(rows, vec![vec![some("1")], vec![some("4")]]);
+
+    // Column which isn't grouped can't be returned with aggregates
+    assert!(run("SELECT dept, COUNT(*) FROM t", rows_set.clone()).is_err());
+    assert!(run("SELECT salary FROM t GROUP BY dept", rows_set).is_err());
+}
+
+#[test]
+fn test_select_computed_projection() {
+    use super::sql_expr::value_text;
+    use sqlparser::{dialect::AnsiDialect, parser::Parser as SqlParser, ast::{Statement, SetExpr, SelectItem}};
+
+    let column = |name: &str, d_type: SupportedSQLDataTypes| RowsSetColumn { table: Some("t".to_string()), name: name.to_string(), d_type, hidden: false };
+    let some = |val: &str| Some(val.to_string());
+    let text = |val: &str| RowValue::Text(val.to_string());
+    let rows_set = RowsSet {
+        columns: vec![column("item", SupportedSQLDataTypes::VARCHAR(None)), column("price", SupportedSQLDataTypes::FLOAT), column("qty", SupportedSQLDataTypes::INT)],
+        rows: vec![
+            vec![text("pen"), RowValue::Float(1.5), RowValue::Int(4)],
+            vec![text("book"), RowValue::Float(20.0), RowValue::Int(1)],
+            vec![text("cup"), RowValue::Float(3.0), RowValue::Null],
+        ]
+    };
+
+    // Compute projection from SQL query
+    let run = |sql: &str| {
+        let (select, order_by) = match SqlParser::parse_sql(&AnsiDialect {}, sql).unwrap().remove(0) {
+            Statement::Query(query) => match *query.body {
+                SetExpr::Select(select) => (select, query.order_by),
+                _ => unreachable!()
+            },
+            _ => unreachable!()
+        };
+        let projection = select.projection
+            .iter()
+            .map(|item| match item {
+                SelectItem::UnnamedExpr(expr) => SelectProjectionItem::Expr(expr.clone(), expr.to_string()),
+                SelectItem::ExprWithAlias { expr, alias } => SelectProjectionItem::Expr(expr.clone(), alias.value.clone()),
+                _ => unreachable!()
+            })
+            .collect::<Vec<_>>();
+        let rows_groups = aggregate(rows_set.clone(), &select.group_by, None, &projection, &ExprContext::default()).unwrap();
+        let rows_groups = order(rows_groups, &order_by, &projection, &ExprContext::default()).unwrap();
+        project(&rows_groups, &projection, &ExprContext::default()).unwrap()
+    };
+
+    let (columns, rows) = run("SELECT item AS name, price * qty AS total, qty + 1, 'x' || item, 7 FROM t ORDER BY total DESC");
+    assert_eq!(columns.iter().map(|column| column.name.as_str()).collect::<Vec<_>>(), vec!["name", "total", "qty + 1", "'x' || item", "7"]);
+    assert_eq!(columns[1].d_type, SupportedSQLDataTypes::FLOAT);
+    assert_eq!(columns[2].d_type, SupportedSQLDataTypes::INT);
+    let rows = rows.into_iter().map(|row| row.into_iter().map(|cell| value_text(cell.value)).collect::<Vec<_>>()).collect::<Vec<_>>();
+    assert_eq!(rows, vec![
+        vec![some("cup"), None, None, some("xcup"), some("7")],
+        vec![some("book"), some("20"), some("2"), some("xbook"), some("7")],
+        vec![some("pen"), some("6"), some("5"), some("xpen"), some("7")],
+    ]);
+
+    let (_, rows) = run("SELECT SUM(price * qty) / COUNT(qty) AS avg_total FROM t");
+    assert_eq!(rows[0][0].value, RowValue::Float(13.0));
+}
+
+#[test]
+fn test_select_subqueries() {
+    use super::sql_expr::value_text;
+    use super::sql_json::{process_sql, ProcessSQLSupportedQueries, ProcessSQLRowField as Field, InsertOperations};
+    use sqlparser::{dialect::AnsiDialect, parser::Parser as SqlParser, ast::Statement};
+
+    // Create tables "users" and "orders" in temporary database
+    let dir = std::env::temp_dir().join(format!("wastledb_subqueries_{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    super::sql_wal::set_data_path(&dir);
+    let create = |name: &str, columns: Vec<&str>, rows: Vec<Vec<&str>>| {
+        let path = sql_storage::table_path(&dir, name);
+        let table = process_sql(ProcessSQLSupportedQueries::CreateTable(name.to_string(), columns.into_iter().map(|n| (n.to_string(), SupportedSQLDataTypes::INT, None)).collect(), vec![])).unwrap();
+        sql_storage::write_table(&path, &table).unwrap();
+        let rows = rows.into_iter().map(|row| row.into_iter().map(|val| Field(val.to_string(), SupportedSQLDataTypes::INT)).collect::<Vec<_>>()).collect::<Vec<_>>();
+        let table = process_sql(ProcessSQLSupportedQueries::Insert(&path, None, rows, InsertOperations::Into)).unwrap();
+        sql_storage::write_table(&path, &table).unwrap();
+    };
+    create("users", vec!["id", "age"], vec![vec!["1", "20"], vec!["2", "30"], vec!["3", "40"]]);
+    create("orders", vec!["id", "user_id"], vec![vec!["10", "1"], vec!["11", "1"], vec!["12", "3"]]);
+
+    // Compute query in database and return its rows
+    let run = |sql: &str| {
+        let query = match SqlParser::parse_sql(&AnsiDialect {}, sql).unwrap().remove(0) {
+            Statement::Query(query) => query,
+            _ => unreachable!()
+        };
+        let (_, rows) = select(&SelectQuery::from_query(&dir, &query).unwrap(), None)?;
+        Ok::<_, ()>(rows.into_iter().map(|row| row.into_iter().map(|cell| value_text(cell.value).unwrap_or_default()).collect::<Vec<_>>()).collect::<Vec<_>>())
+    };
+
+    assert_eq!(run("SELECT id FROM users WHERE id IN (SELECT user_id FROM orders)").unwrap(), vec![vec!["1"], vec!["3"]]);
+    assert_eq!(run("SELECT id FROM users WHERE id NOT IN (SELECT user_id FROM orders WHERE id > 10)").unwrap(), vec![vec!["2"]]);
+
+    // Correlated subqueries reffer to row of outer query
+    assert_eq!(run("SELECT id FROM users u WHERE NOT EXISTS (SELECT * FROM orders o WHERE o.user_id = u.id)").unwrap(), vec![vec!["2"]]);
+    assert_eq!(run("SELECT id, (SELECT COUNT(*) FROM orders o WHERE o.user_id = u.id) AS orders FROM users u").unwrap(), vec![vec!["1", "2"], vec!["2", "0"], vec!["3", "1"]]);
+    assert_eq!(run("SELECT id FROM users WHERE age > (SELECT AVG(age) FROM users)").unwrap(), vec![vec!["3"]]);
+
+    // Scalar subquery can't return more than one row
+    assert!(run("SELECT id FROM users WHERE id = (SELECT user_id FROM orders)").is_err());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_select_set_operations() {
+    use super::sql_expr::value_text;
+    use super::sql_json::tests::create_test_table;
+    use sqlparser::{dialect::AnsiDialect, parser::Parser as SqlParser, ast::Statement};
+
+    // Create tables "clients" and "workers" in temporary database
+    let dir = std::env::temp_dir().join(format!("wastledb_set_operations_{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    super::sql_wal::set_data_path(&dir);
+    create_test_table(&dir, "clients", vec![("name", SupportedSQLDataTypes::TEXT), ("age", SupportedSQLDataTypes::INT)], vec![vec!["ann", "20"], vec!["bob", "30"], vec!["bob", "30"], vec!["cid", "40"]]);
+    create_test_table(&dir, "workers", vec![("login", SupportedSQLDataTypes::VARCHAR(Some(10))), ("salary", SupportedSQLDataTypes::FLOAT)], vec![vec!["bob", "30"], vec!["dan", "50"]]);
+
+    let query = |sql: &str| match SqlParser::parse_sql(&AnsiDialect {}, sql).unwrap().remove(0) {
+        Statement::Query(query) => SelectQuery::from_query(&dir, &query),
+        _ => unreachable!()
+    };
+    let run = |sql: &str| {
+        let (columns, rows) = select(&query(sql).unwrap(), None).unwrap();
+        let names = columns.into_iter().map(|column| column.name).collect::<Vec<_>>();
+        (names, rows.into_iter().map(|row| row.into_iter().map(|cell| value_text(cell.value).unwrap_or_default()).collect::<Vec<_>>()).collect::<Vec<_>>())
+    };
+
+    // Names of columns come from first query and duplicates are removed without "ALL"
+    let (names, rows) = run("SELECT name, age FROM clients UNION SELECT login, salary FROM workers ORDER BY age DESC");
+    assert_eq!(names, vec!["name", "age"]);
+    assert_eq!(rows, vec![vec!["dan", "50"], vec!["cid", "40"], vec!["bob", "30"], vec!["ann", "20"]]);
+    assert_eq!(run("SELECT name FROM clients UNION ALL SELECT login FROM workers").1.len(), 6);
+    assert_eq!(run("SELECT name FROM clients UNION SELECT login FROM workers ORDER BY 1 LIMIT 2 OFFSET 1").1, vec![vec!["bob"], vec!["cid"]]);
+
+    assert_eq!(run("SELECT name, age FROM clients INTERSECT SELECT login, salary FROM workers").1, vec![vec!["bob", "30"]]);
+    assert_eq!(run("SELECT name FROM clients INTERSECT ALL SELECT login FROM workers").1, vec![vec!["bob"]]);
+    assert_eq!(run("SELECT name FROM clients EXCEPT SELECT login FROM workers").1, vec![vec!["ann"], vec!["cid"]]);
+    assert_eq!(run("SELECT name FROM clients EXCEPT ALL SELECT login FROM workers").1, vec![vec!["ann"], vec!["bob"], vec!["cid"]]);
+
+    // Operations are combined according to parentheses
+    assert_eq!(run("SELECT login FROM workers EXCEPT (SELECT name FROM clients WHERE age > 25 ORDER BY age LIMIT 1)").1, vec![vec!["dan"]]);
+
+    // Combined queries must return compatible columns
+    assert!(query("SELECT name, age FROM clients UNION SELECT login FROM workers").unwrap_err().contains("count of columns"));
+    assert!(query("SELECT name FROM clients UNION SELECT salary FROM workers").unwrap_err().contains("incompatible data types"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,76 @@
+/* Sequences created by "CREATE SEQUENCE". Counters of all sequences of database are stored in file placed beside database directory ("<database>.sequences.json") so they survive server restarts and aren't treated as tables */
+use std::{fs, path::{Path, PathBuf}, collections::HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use super::sql_wal;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+/// Represent state of sequence
+pub struct JsonSQLSequence {
+    /// value returned by last call of "nextval". None when "nextval" wasn't called yet for sequence
+    pub last_value: Option<i128>,
+}
+
+/// Path to file with sequences of database
+pub fn sequences_path(database: &Path) -> PathBuf {
+    let database_name = database.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    database.with_file_name(format!("{}.sequences.json", database_name))
+}
+
+/// Load all sequences of database (database without file with sequences hasn't got any sequence)
+fn read_sequences(database: &Path) -> Result<HashMap<String, JsonSQLSequence>, String> {
+    match fs::read_to_string(sequences_path(database)) {
+        Ok(content) => serde_json::from_str(&content).map_err(|_| "Couldn't read sequences of database".to_string()),
+        Err(_) => Ok(HashMap::new())
+    }
+}
+
+/// Save all sequences of database
+fn save_sequences(database: &Path, sequences: &HashMap<String, JsonSQLSequence>) -> Result<(), String> {
+    let content = serde_json::to_string(sequences).map_err(|_| "Couldn't save sequences of database".to_string())?;
+    sql_wal::write_file(&sequences_path(database), content.as_bytes()).map_err(|_| "Couldn't save sequences of database".to_string())
+}
+
+/// Create sequence. Creation of already existing sequence is ignored only when "if_not_exists" is set
+pub fn create_sequence(database: &Path, name: &str, if_not_exists: bool) -> Result<(), String> {
+    let mut sequences = read_sequences(database)?;
+
+    if sequences.contains_key(name) {
+        return if if_not_exists { Ok(()) } else { Err(format!("Sequence \"{}\" already exists", name)) };
+    };
+
+    sequences.insert(name.to_string(), JsonSQLSequence::default());
+    save_sequences(database, &sequences)
+}
+
+/// Remove sequence. Removing of not existing sequence is ignored only when "if_exists" is set
+pub fn drop_sequence(database: &Path, name: &str, if_exists: bool) -> Result<(), String> {
+    let mut sequences = read_sequences(database)?;
+
+    if sequences.remove(name).is_none() {
+        return if if_exists { Ok(()) } else { Err(format!("Sequence \"{}\" doesn't exist", name)) };
+    };
+
+    save_sequences(database, &sequences)
+}
+
+/// Move sequence forward and return its new value ("nextval"). First value of sequence is 1
+pub fn next_value(database: &Path, name: &str) -> Result<i128, String> {
+    let mut sequences = read_sequences(database)?;
+    let sequence = sequences.get_mut(name).ok_or_else(|| format!("Sequence \"{}\" doesn't exist", name))?;
+
+    let value = sequence.last_value.map_or(1, |last_value| last_value + 1);
+    sequence.last_value = Some(value);
+    save_sequences(database, &sequences)?;
+
+    Ok(value)
+}
+
+/// Value returned by last call of "nextval" for sequence ("currval")
+pub fn current_value(database: &Path, name: &str) -> Result<i128, String> {
+    let sequences = read_sequences(database)?;
+    let sequence = sequences.get(name).ok_or_else(|| format!("Sequence \"{}\" doesn't exist", name))?;
+
+    sequence.last_value.ok_or_else(|| format!("Sequence \"{}\" hasn't got current value because \"nextval\" wasn't called for it yet", name))
+}
@@ -0,0 +1,1111 @@
+/* Paged storage of tables. Table file ("<table>.tbl") is divided into pages with fixed size so operation which changes only some rows rewrites only pages in which these rows are stored (instead of whole file).
+   Page 0 is header of file, table definition (name, columns and constraints) is stored in chain of blob pages and rows are stored in slots of heap pages (row too big for heap page is stored in its own chain of blob pages).
+   Each row can be stored in many versions (with ids of transactions which created and removed version), so transactions see rows according to their snapshots (see "sql_transactions").
+   Free space of each page is tracked by free space map (chain of pages) which is used to find page for new row. Pages released by removed data are linked into list of free pages and they are reused before file is extended */
+use std::{fs::File, io::{self, Read, Seek, SeekFrom}, path::{Path, PathBuf}, collections::{BTreeMap, BTreeSet, HashSet}};
+
+use super::sql_json::{JsonSQLTable, JsonSQLTableColumnRow};
+use super::{sql_index, sql_transactions, sql_wal};
+
+/// Extension of table files
+pub const TABLE_EXTENSION: &str = "tbl";
+/// Size of each page of table file in bytes
+pub const PAGE_SIZE: usize = 4096;
+/// Count of pages changed in memory after which changes of operation which goes through all rows of table (e.g: "write_table") are written to file, so only part of big table is kept in memory
+pub const CHANGED_PAGES_LIMIT: usize = 64;
+/// Count of rows which are read and checked together by operations which go through all rows of table (e.g: rows whose foreign keys are checked by one search of referenced rows)
+pub const ROWS_BATCH: usize = 1000;
+/// Bytes at start of table file by which it is recognized
+const MAGIC: &[u8; 8] = b"WASTLEDB";
+/// Version of table file format
+const VERSION: u16 = 1;
+
+/// Kinds of pages (stored in first byte of each page other than header)
+const FREE_PAGE: u8 = 0;
+const HEAP_PAGE: u8 = 1;
+const BLOB_PAGE: u8 = 2;
+const FSM_PAGE: u8 = 3;
+
+/// Heap page: 1. kind, 2. unused byte, 3. count of slots (u16) and then slots (offset and length of record as u16). Records are placed from end of page
+const HEAP_HEADER: usize = 4;
+const SLOT_SIZE: usize = 4;
+/// Blob page: 1. kind, 2. unused byte, 3. length of data in page (u16), 4. next page of blob (u32, 0 for last page) and then data
+const BLOB_HEADER: usize = 8;
+/// Free space map page: 1. kind, 2. 3 unused bytes, 3. next page of map (u32, 0 for last page) and then free space of pages (one byte for each page)
+const FSM_HEADER: usize = 8;
+const FSM_ENTRIES: usize = PAGE_SIZE - FSM_HEADER;
+/// Free space in map is stored in units of this count of bytes
+const FSM_UNIT: usize = 16;
+
+/// Record in heap page starts with kind of record: row stored in record or reference to blob with row (u32 with first page of blob)
+const INLINE_ROW: u8 = 0;
+const BLOB_ROW: u8 = 1;
+/// Versioned record: kind is followed by version of row (ids of transactions which created and removed row, u64 each) and then by row or reference to blob. Row seen by all transactions is stored without version
+const INLINE_VERSION: u8 = 2;
+const BLOB_VERSION: u8 = 3;
+const VERSION_SIZE: usize = 16;
+/// Rows longer than this count of bytes are stored in blobs
+const MAX_INLINE_ROW: usize = PAGE_SIZE / 4;
+
+/// Location of row in table file: 1. page number, 2. slot in page
+pub type RowLocation = (u32, usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+/// Version of row: ids of transaction which created row and of transaction which removed it (0 when row hasn't been removed). Version (0, 0) is seen by all transactions
+pub struct Version {
+    pub created: u64,
+    pub removed: u64,
+}
+
+/// Stored version of row: 1. location, 2. version, 3. row
+pub type RowVersion = (RowLocation, Version, Vec<JsonSQLTableColumnRow>);
+
+fn read_u16(page: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([page[offset], page[offset + 1]])
+}
+
+fn read_u32(page: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([page[offset], page[offset + 1], page[offset + 2], page[offset + 3]])
+}
+
+fn write_u16(page: &mut [u8], offset: usize, value: u16) {
+    page[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+}
+
+fn write_u32(page: &mut [u8], offset: usize, value: u32) {
+    page[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+fn corrupted() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "Table file is corrupted")
+}
+
+/// Record of heap page with version of row and content (row or first page of blob with row)
+fn join_record(version: Version, inline: bool, content: &[u8]) -> Vec<u8> {
+    if version == Version::default() {
+        return [&[if inline { INLINE_ROW } else { BLOB_ROW }], content].concat();
+    };
+
+    [&[if inline { INLINE_VERSION } else { BLOB_VERSION }][..], &version.created.to_le_bytes(), &version.removed.to_le_bytes(), content].concat()
+}
+
+/// Split record of heap page into version of row, information whether row is stored in record and content (row or first page of blob with row)
+fn split_record(record: &[u8]) -> io::Result<(Version, bool, &[u8])> {
+    let id = |offset: usize| u64::from_le_bytes(record[offset..offset + 8].try_into().unwrap());
+    match record.first() {
+        Some(&INLINE_ROW) => Ok((Version::default(), true, &record[1..])),
+        Some(&BLOB_ROW) => Ok((Version::default(), false, &record[1..])),
+        Some(&kind @ (INLINE_VERSION | BLOB_VERSION)) if record.len() > VERSION_SIZE => Ok((Version { created: id(1), removed: id(9) }, kind == INLINE_VERSION, &record[1 + VERSION_SIZE..])),
+        _ => Err(corrupted())
+    }
+}
+
+/// Path to file of table placed in database directory
+pub fn table_path(database: &Path, table_name: &str) -> PathBuf {
+    database.join(format!("{}.{}", table_name, TABLE_EXTENSION))
+}
+
+#[derive(Debug, Clone, Copy)]
+/// Header of table file stored in page 0: magic bytes, version (u16), page size (u32) and then fields below (u32 each). Page number 0 means that page doesn't exist
+struct Header {
+    /// count of all pages in file (including header)
+    page_count: u32,
+    /// first page of blob with table definition
+    definition_page: u32,
+    /// first page of free space map
+    fsm_page: u32,
+    /// first page of list of free pages
+    free_page: u32,
+}
+
+impl Header {
+    fn decode(page: &[u8]) -> io::Result<Header> {
+        if &page[..8] != MAGIC || read_u16(page, 8) != VERSION || read_u32(page, 10) as usize != PAGE_SIZE {
+            return Err(corrupted());
+        };
+
+        Ok(Header { page_count: read_u32(page, 14), definition_page: read_u32(page, 18), fsm_page: read_u32(page, 22), free_page: read_u32(page, 26) })
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut page = vec![0; PAGE_SIZE];
+        page[..8].copy_from_slice(MAGIC);
+        write_u16(&mut page, 8, VERSION);
+        write_u32(&mut page, 10, PAGE_SIZE as u32);
+        write_u32(&mut page, 14, self.page_count);
+        write_u32(&mut page, 18, self.definition_page);
+        write_u32(&mut page, 22, self.fsm_page);
+        write_u32(&mut page, 26, self.free_page);
+        page
+    }
+}
+
+/// Heap page with records of rows in slots. Slot without record is empty and it can be reused by next record (empty slots at end of page aren't stored)
+struct HeapPage {
+    slots: Vec<Option<Vec<u8>>>,
+}
+
+impl HeapPage {
+    fn decode(page: &[u8]) -> io::Result<HeapPage> {
+        let count = read_u16(page, 2) as usize;
+        if page[0] != HEAP_PAGE || HEAP_HEADER + count * SLOT_SIZE > PAGE_SIZE {
+            return Err(corrupted());
+        };
+
+        let mut slots = Vec::with_capacity(count);
+        for slot in 0..count {
+            let offset = read_u16(page, HEAP_HEADER + slot * SLOT_SIZE) as usize;
+            let length = read_u16(page, HEAP_HEADER + slot * SLOT_SIZE + 2) as usize;
+            slots.push(match length {
+                0 => None,
+                _ => Some(page.get(offset..offset + length).ok_or_else(corrupted)?.to_vec())
+            });
+        }
+
+        Ok(HeapPage { slots })
+    }
+
+    /// Page with records placed one after another from end of page (so free space of page is always in one piece)
+    fn encode(&self) -> Vec<u8> {
+        let mut page = vec![0; PAGE_SIZE];
+        page[0] = HEAP_PAGE;
+        write_u16(&mut page, 2, self.slots.len() as u16);
+
+        let mut end = PAGE_SIZE;
+        for (slot, record) in self.slots.iter().enumerate() {
+            let (offset, length) = match record {
+                Some(record) => {
+                    end -= record.len();
+                    page[end..end + record.len()].copy_from_slice(record);
+                    (end, record.len())
+                },
+                None => (0, 0)
+            };
+            write_u16(&mut page, HEAP_HEADER + slot * SLOT_SIZE, offset as u16);
+            write_u16(&mut page, HEAP_HEADER + slot * SLOT_SIZE + 2, length as u16);
+        }
+
+        page
+    }
+
+    fn free_space(&self) -> usize {
+        PAGE_SIZE - HEAP_HEADER - self.slots.len() * SLOT_SIZE - self.slots.iter().flatten().map(Vec::len).sum::<usize>()
+    }
+
+    /// Place record in first empty slot or in new slot and return that slot
+    fn insert(&mut self, record: Vec<u8>) -> usize {
+        match self.slots.iter().position(Option::is_none) {
+            Some(slot) => {
+                self.slots[slot] = Some(record);
+                slot
+            },
+            None => {
+                self.slots.push(Some(record));
+                self.slots.len() - 1
+            }
+        }
+    }
+
+    /// Take record out of slot
+    fn remove(&mut self, slot: usize) -> io::Result<Vec<u8>> {
+        let record = self.slots.get_mut(slot).and_then(Option::take).ok_or_else(corrupted)?;
+        self.trim();
+
+        Ok(record)
+    }
+
+    /// Remove empty slots from end of page
+    fn trim(&mut self) {
+        while matches!(self.slots.last(), Some(None)) {
+            self.slots.pop();
+        }
+    }
+}
+
+/// Opened table file. Changed pages are kept in memory until they are written to file by "flush"
+struct TableFile {
+    path: PathBuf,
+    /// None for file which hasn't been saved yet
+    file: Option<File>,
+    header: Header,
+    header_changed: bool,
+    /// pages of free space map in order of chain
+    fsm_pages: Vec<u32>,
+    /// free space of each page in units of "FSM_UNIT" bytes (0 for pages which aren't heap pages)
+    fsm: Vec<u8>,
+    /// positions in "fsm_pages" of free space map pages which have to be saved
+    fsm_changed: BTreeSet<usize>,
+    /// pages changed in memory
+    changed_pages: BTreeMap<u32, Vec<u8>>,
+    /// page from which search of page with free space starts (page in which last row was inserted)
+    insert_hint: usize,
+}
+
+impl TableFile {
+    fn open(path: &Path) -> io::Result<TableFile> {
+        TableFile::open_as(path, sql_wal::access())
+    }
+
+    /// Open table file for statement (it can't be opened when it is changed by another statement)
+    fn open_as(path: &Path, access: sql_wal::Access) -> io::Result<TableFile> {
+        sql_wal::check_access(path, access)?;
+        let mut file = File::open(path)?;
+        let mut header_page = vec![0; PAGE_SIZE];
+        file.read_exact(&mut header_page)?;
+        let header = Header::decode(&header_page)?;
+
+        let mut table_file = TableFile { path: path.to_path_buf(), file: Some(file), header, header_changed: false, fsm_pages: vec![], fsm: vec![], fsm_changed: BTreeSet::new(), changed_pages: BTreeMap::new(), insert_hint: 0 };
+        let mut fsm_page = header.fsm_page;
+        while fsm_page != 0 {
+            let page = table_file.read_page(fsm_page)?;
+            if page[0] != FSM_PAGE || table_file.fsm_pages.len() > header.page_count as usize {
+                return Err(corrupted());
+            };
+            table_file.fsm_pages.push(fsm_page);
+            table_file.fsm.extend_from_slice(&page[FSM_HEADER..]);
+            fsm_page = read_u32(&page, 4);
+        }
+        table_file.fsm.resize(header.page_count as usize, 0);
+
+        Ok(table_file)
+    }
+
+    /// New file with header only (it is saved by "flush")
+    fn create(path: &Path) -> TableFile {
+        let header = Header { page_count: 1, definition_page: 0, fsm_page: 0, free_page: 0 };
+
+        TableFile { path: path.to_path_buf(), file: None, header, header_changed: true, fsm_pages: vec![], fsm: vec![0], fsm_changed: BTreeSet::new(), changed_pages: BTreeMap::new(), insert_hint: 0 }
+    }
+
+    fn read_page(&mut self, number: u32) -> io::Result<Vec<u8>> {
+        if let Some(page) = self.changed_pages.get(&number) {
+            return Ok(page.clone());
+        };
+        let file = match self.file.as_mut() {
+            Some(file) if number != 0 && number < self.header.page_count => file,
+            _ => return Err(corrupted())
+        };
+
+        let mut page = vec![0; PAGE_SIZE];
+        file.seek(SeekFrom::Start(number as u64 * PAGE_SIZE as u64))?;
+        file.read_exact(&mut page)?;
+        Ok(page)
+    }
+
+    fn set_free_space(&mut self, number: u32, free_space: usize) {
+        let units = (free_space / FSM_UNIT).min(u8::MAX as usize) as u8;
+
+        if self.fsm[number as usize] != units {
+            self.fsm[number as usize] = units;
+            self.fsm_changed.insert(number as usize / FSM_ENTRIES);
+        };
+    }
+
+    /// Obtain page for new data: first page from list of free pages or new page at end of file. New page which isn't covered by free space map becomes next page of map
+    fn allocate_page(&mut self) -> io::Result<u32> {
+        self.header_changed = true;
+
+        if self.header.free_page != 0 {
+            let number = self.header.free_page;
+            let page = self.read_page(number)?;
+            if page[0] != FREE_PAGE {
+                return Err(corrupted());
+            };
+            self.header.free_page = read_u32(&page, 4);
+            return Ok(number);
+        };
+
+        loop {
+            let number = self.header.page_count;
+            self.header.page_count = number.checked_add(1).ok_or_else(|| io::Error::other("Table file is too big"))?;
+            self.fsm.push(0);
+
+            if (number as usize) < self.fsm_pages.len() * FSM_ENTRIES {
+                return Ok(number);
+            };
+
+            let mut fsm_page = vec![0; PAGE_SIZE];
+            fsm_page[0] = FSM_PAGE;
+            match self.fsm_pages.last() {
+                Some(&last) => {
+                    let mut last_page = self.read_page(last)?;
+                    write_u32(&mut last_page, 4, number);
+                    self.changed_pages.insert(last, last_page);
+                },
+                None => self.header.fsm_page = number
+            };
+            self.fsm_pages.push(number);
+            self.fsm_changed.insert(self.fsm_pages.len() - 1);
+            self.changed_pages.insert(number, fsm_page);
+        }
+    }
+
+    /// Add page to list of free pages
+    fn release_page(&mut self, number: u32) {
+        let mut page = vec![0; PAGE_SIZE];
+        page[0] = FREE_PAGE;
+        write_u32(&mut page, 4, self.header.free_page);
+
+        self.header.free_page = number;
+        self.header_changed = true;
+        self.set_free_space(number, 0);
+        self.changed_pages.insert(number, page);
+    }
+
+    /// Save data in chain of blob pages and return first page of chain
+    fn write_blob(&mut self, data: &[u8]) -> io::Result<u32> {
+        // Chain is created from its end so each page can point to already created next page
+        let mut next = 0;
+        for chunk in data.chunks(PAGE_SIZE - BLOB_HEADER).rev() {
+            let number = self.allocate_page()?;
+            let mut page = vec![0; PAGE_SIZE];
+            page[0] = BLOB_PAGE;
+            write_u16(&mut page, 2, chunk.len() as u16);
+            write_u32(&mut page, 4, next);
+            page[BLOB_HEADER..BLOB_HEADER + chunk.len()].copy_from_slice(chunk);
+            self.changed_pages.insert(number, page);
+            next = number;
+        }
+
+        Ok(next)
+    }
+
+    /// Pages of blob in order of chain
+    fn blob_pages(&mut self, first_page: u32) -> io::Result<Vec<(u32, Vec<u8>)>> {
+        let mut pages = vec![];
+        let mut number = first_page;
+        while number != 0 {
+            let page = self.read_page(number)?;
+            if page[0] != BLOB_PAGE || pages.len() > self.header.page_count as usize {
+                return Err(corrupted());
+            };
+            let next = read_u32(&page, 4);
+            pages.push((number, page));
+            number = next;
+        }
+
+        Ok(pages)
+    }
+
+    fn read_blob(&mut self, first_page: u32) -> io::Result<Vec<u8>> {
+        let mut data = vec![];
+        for (_, page) in self.blob_pages(first_page)? {
+            let length = read_u16(&page, 2) as usize;
+            data.extend_from_slice(page.get(BLOB_HEADER..BLOB_HEADER + length).ok_or_else(corrupted)?);
+        }
+
+        Ok(data)
+    }
+
+    fn release_blob(&mut self, first_page: u32) -> io::Result<()> {
+        for (number, _) in self.blob_pages(first_page)? {
+            self.release_page(number);
+        }
+
+        Ok(())
+    }
+
+    /// Replace table definition when it has been changed
+    fn set_definition(&mut self, definition: &[u8]) -> io::Result<()> {
+        let old_page = self.header.definition_page;
+        if old_page != 0 {
+            if self.read_blob(old_page)? == definition {
+                return Ok(());
+            };
+            self.release_blob(old_page)?;
+        };
+
+        self.header.definition_page = self.write_blob(definition)?;
+        self.header_changed = true;
+        Ok(())
+    }
+
+    fn definition(&mut self) -> io::Result<JsonSQLTable> {
+        if self.header.definition_page == 0 {
+            return Err(corrupted());
+        };
+
+        let definition = self.read_blob(self.header.definition_page)?;
+        Ok(serde_json::from_slice(&definition)?)
+    }
+
+    /// Record under which version of row is stored in heap page
+    fn row_record(&mut self, row: &[u8], version: Version) -> io::Result<Vec<u8>> {
+        if row.len() <= MAX_INLINE_ROW {
+            return Ok(join_record(version, true, row));
+        };
+
+        let first_page = self.write_blob(row)?;
+        Ok(join_record(version, false, &first_page.to_le_bytes()))
+    }
+
+    /// Version of row and row stored under record
+    fn record_row(&mut self, record: &[u8]) -> io::Result<(Version, Vec<u8>)> {
+        match split_record(record)? {
+            (version, true, row) => Ok((version, row.to_vec())),
+            (version, false, first_page) if first_page.len() == 4 => Ok((version, self.read_blob(read_u32(first_page, 0))?)),
+            _ => Err(corrupted())
+        }
+    }
+
+    /// Release pages used by record (blob with row)
+    fn release_record(&mut self, record: &[u8]) -> io::Result<()> {
+        match split_record(record)? {
+            (_, false, first_page) if first_page.len() == 4 => self.release_blob(read_u32(first_page, 0)),
+            _ => Ok(())
+        }
+    }
+
+    /// Versions of rows stored in page with their slots. Pages which aren't heap pages haven't got rows
+    fn page_rows(&mut self, number: u32) -> io::Result<Vec<(usize, Version, Vec<u8>)>> {
+        let page = self.read_page(number)?;
+        if page[0] != HEAP_PAGE {
+            return Ok(vec![]);
+        };
+
+        let mut rows = vec![];
+        for (slot, record) in HeapPage::decode(&page)?.slots.into_iter().enumerate() {
+            if let Some(record) = record {
+                let (version, row) = self.record_row(&record)?;
+                rows.push((slot, version, row));
+            }
+        }
+
+        Ok(rows)
+    }
+
+    fn record_at(&mut self, (number, slot): RowLocation) -> io::Result<Vec<u8>> {
+        let page = self.heap_page(number)?;
+        page.slots.get(slot).cloned().flatten().ok_or_else(corrupted)
+    }
+
+    fn version_at(&mut self, location: RowLocation) -> io::Result<Version> {
+        Ok(split_record(&self.record_at(location)?)?.0)
+    }
+
+    /// Version of row stored in slot
+    fn row_at(&mut self, location: RowLocation) -> io::Result<(Version, Vec<u8>)> {
+        let record = self.record_at(location)?;
+        self.record_row(&record)
+    }
+
+    fn heap_page(&mut self, number: u32) -> io::Result<HeapPage> {
+        HeapPage::decode(&self.read_page(number)?)
+    }
+
+    /// Save changed heap page. Page without records is released
+    fn save_heap_page(&mut self, number: u32, page: HeapPage) {
+        if page.slots.is_empty() {
+            self.release_page(number);
+            return;
+        };
+
+        self.set_free_space(number, page.free_space());
+        self.changed_pages.insert(number, page.encode());
+    }
+
+    /// Place record in page which has got enough free space for it (according to free space map) or in new page
+    fn insert_record(&mut self, record: Vec<u8>) -> io::Result<RowLocation> {
+        let required_units = (record.len() + SLOT_SIZE).div_ceil(FSM_UNIT);
+        loop {
+            let hint = self.insert_hint.min(self.fsm.len());
+            let with_space = self.fsm[hint..]
+                .iter()
+                .position(|&units| units as usize >= required_units)
+                .map(|position| hint + position)
+                .or_else(|| self.fsm[..hint].iter().position(|&units| units as usize >= required_units));
+
+            let (number, mut page) = match with_space {
+                Some(number) => (number as u32, self.heap_page(number as u32)?),
+                None => (self.allocate_page()?, HeapPage { slots: vec![] })
+            };
+            // Map can overstate free space of page whose changes have been reverted by rollback of transaction
+            if record.len() + SLOT_SIZE > page.free_space() {
+                self.set_free_space(number, page.free_space());
+                continue;
+            };
+
+            let slot = page.insert(record);
+            self.save_heap_page(number, page);
+            self.insert_hint = number as usize;
+
+            return Ok((number, slot));
+        }
+    }
+
+    fn insert_row(&mut self, row: &[u8], version: Version) -> io::Result<RowLocation> {
+        let record = self.row_record(row, version)?;
+        self.insert_record(record)
+    }
+
+    /// Replace record stored in slot and return new location of record. New record stays in the same slot when it fits into page
+    fn replace_record(&mut self, (number, slot): RowLocation, record: Vec<u8>) -> io::Result<RowLocation> {
+        let mut page = self.heap_page(number)?;
+        page.slots.get_mut(slot).and_then(Option::take).ok_or_else(corrupted)?;
+
+        if record.len() <= page.free_space() {
+            page.slots[slot] = Some(record);
+            self.save_heap_page(number, page);
+            return Ok((number, slot));
+        };
+
+        page.trim();
+        self.save_heap_page(number, page);
+        self.insert_record(record)
+    }
+
+    /// Replace row stored in slot and return new location of row
+    fn replace_row(&mut self, location: RowLocation, row: &[u8], version: Version) -> io::Result<RowLocation> {
+        let old_record = self.record_at(location)?;
+        self.release_record(&old_record)?;
+        let record = self.row_record(row, version)?;
+        self.replace_record(location, record)
+    }
+
+    /// Change version of row stored in slot and return new location of row (row is moved when versioned record doesn't fit into page)
+    fn set_version(&mut self, location: RowLocation, version: Version) -> io::Result<RowLocation> {
+        let record = self.record_at(location)?;
+        let (_, inline, content) = split_record(&record)?;
+        self.replace_record(location, join_record(version, inline, content))
+    }
+
+    fn delete_row(&mut self, (number, slot): RowLocation) -> io::Result<()> {
+        let mut page = self.heap_page(number)?;
+        let record = page.remove(slot)?;
+        self.release_record(&record)?;
+        self.save_heap_page(number, page);
+
+        Ok(())
+    }
+
+    /// Remove version of row seen by statement: version created by writer of statement is deleted and other version is marked as removed by writer (it stays until it isn't seen by any transaction).
+    /// Version removed by another transaction can't be removed. Location of kept version is returned
+    fn delete_at(&mut self, location: RowLocation, visibility: &sql_transactions::Visibility) -> io::Result<Option<RowLocation>> {
+        let version = self.version_at(location)?;
+        if visibility.is_written(version) {
+            self.delete_row(location)?;
+            return Ok(None);
+        };
+        if visibility.is_conflicting(version) {
+            return Err(sql_transactions::conflict());
+        };
+
+        self.set_version(location, Version { removed: visibility.writer(), ..version }).map(Some)
+    }
+
+    /// Replace version of row seen by statement by new version created by writer of statement. Returns location of kept previous version (see "delete_at") and location of new version
+    fn update_at(&mut self, location: RowLocation, row: &[u8], visibility: &sql_transactions::Visibility) -> io::Result<(Option<RowLocation>, RowLocation)> {
+        let version = Version { created: visibility.writer(), removed: 0 };
+        if visibility.is_written(self.version_at(location)?) {
+            return Ok((None, self.replace_row(location, row, version)?));
+        };
+
+        let kept = self.delete_at(location, visibility)?;
+        Ok((kept, self.insert_row(row, version)?))
+    }
+
+    /// Remove versions of rows stored in page which can't be seen by any transaction. Removed rows are returned with their locations
+    fn prune_page(&mut self, number: u32, visibility: &sql_transactions::Visibility) -> io::Result<Vec<(RowLocation, Vec<u8>)>> {
+        let mut removed = vec![];
+        for (slot, version, row) in self.page_rows(number)? {
+            if visibility.is_dead(version) {
+                self.delete_row((number, slot))?;
+                removed.push(((number, slot), row));
+            };
+        }
+
+        Ok(removed)
+    }
+
+    /// Write changed pages to file (through write-ahead log)
+    fn flush(&mut self) -> io::Result<()> {
+        for position in std::mem::take(&mut self.fsm_changed) {
+            let number = self.fsm_pages[position];
+            let mut page = self.read_page(number)?;
+            let entries = &self.fsm[(position * FSM_ENTRIES).min(self.fsm.len())..((position + 1) * FSM_ENTRIES).min(self.fsm.len())];
+            page[FSM_HEADER..FSM_HEADER + entries.len()].copy_from_slice(entries);
+            self.changed_pages.insert(number, page);
+        }
+        if self.header_changed {
+            self.changed_pages.insert(0, self.header.encode());
+            self.header_changed = false;
+        };
+
+        let pages = std::mem::take(&mut self.changed_pages)
+            .into_iter()
+            .map(|(number, page)| (number as u64 * PAGE_SIZE as u64, page))
+            .collect::<Vec<_>>();
+        sql_wal::write_at(&self.path, pages)?;
+
+        // File created by first flush is read when next pages are changed
+        if self.file.is_none() {
+            self.file = Some(File::open(&self.path)?);
+        };
+        Ok(())
+    }
+}
+
+/// Rows of table read from its file page after page (only one page of table is kept in memory at once, so also tables bigger than memory can be read). Only versions of rows seen by statement performed by thread are read
+pub struct TableRows {
+    file: TableFile,
+    definition: JsonSQLTable,
+    visibility: sql_transactions::Visibility,
+    next_page: u32,
+    page_rows: std::vec::IntoIter<(usize, Version, Vec<u8>)>,
+}
+
+impl TableRows {
+    /// Table definition (table without rows)
+    pub fn definition(&self) -> &JsonSQLTable {
+        &self.definition
+    }
+
+    /// Next row together with its location in table file
+    pub fn next_located(&mut self) -> Option<io::Result<(RowLocation, Vec<JsonSQLTableColumnRow>)>> {
+        self.next_stored(true).map(|row| row.map(|(location, _, row)| (location, row)))
+    }
+
+    /// Next stored version of row (also version which isn't seen by statement, e.g: created by another transaction in progress) together with its location
+    pub fn next_version(&mut self) -> Option<io::Result<RowVersion>> {
+        self.next_stored(false)
+    }
+
+    /// Next rows seen by statement (at most "count" rows, no rows when all rows have been read)
+    pub fn next_rows(&mut self, count: usize) -> io::Result<Vec<Vec<JsonSQLTableColumnRow>>> {
+        self.by_ref().take(count).collect()
+    }
+
+    fn next_stored(&mut self, seen: bool) -> Option<io::Result<RowVersion>> {
+        loop {
+            if let Some((slot, version, row)) = self.page_rows.next() {
+                if seen && !self.visibility.is_visible(version) {
+                    continue;
+                };
+                return Some(serde_json::from_slice(&row).map(|row| ((self.next_page - 1, slot), version, self.definition.typed_row(row))).map_err(io::Error::from));
+            };
+            if self.next_page >= self.file.header.page_count {
+                return None;
+            };
+
+            let number = self.next_page;
+            self.next_page += 1;
+            match self.file.page_rows(number) {
+                Ok(rows) => self.page_rows = rows.into_iter(),
+                Err(err) => {
+                    self.next_page = self.file.header.page_count;
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
+
+impl Iterator for TableRows {
+    type Item = io::Result<Vec<JsonSQLTableColumnRow>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_located().map(|row| row.map(|(_, row)| row))
+    }
+}
+
+/// Open table file in order to read its rows one by one
+pub fn table_rows(path: &Path) -> io::Result<TableRows> {
+    let rows = table_rows_as(path, sql_transactions::visibility(), sql_wal::access())?;
+    sql_transactions::record_read(path);
+    Ok(rows)
+}
+
+/// Open table file in order to read rows seen by statement (e.g: by statement for which query is computed on other thread). Read isn't remembered by statement
+pub fn table_rows_as(path: &Path, visibility: sql_transactions::Visibility, access: sql_wal::Access) -> io::Result<TableRows> {
+    let mut file = TableFile::open_as(path, access)?;
+    let definition = file.definition()?;
+
+    Ok(TableRows { file, definition, visibility, next_page: 1, page_rows: vec![].into_iter() })
+}
+
+/// Read table definition (name, columns and constraints) without rows
+pub fn read_definition(path: &Path) -> io::Result<JsonSQLTable> {
+    TableFile::open(path)?.definition()
+}
+
+/// Read whole table (definition and all rows) from its file. Cell values have got types of their columns
+pub fn read_table(path: &Path) -> io::Result<JsonSQLTable> {
+    let mut rows = table_rows(path)?;
+    let stored_rows = rows.by_ref().collect::<io::Result<Vec<_>>>()?;
+
+    Ok(JsonSQLTable { rows: if !stored_rows.is_empty() { Some(stored_rows) } else { None }, ..rows.definition })
+}
+
+/// Read table with only rows stored under given locations (e.g: found by index) together with their locations. Rows are in same order as in whole table
+pub fn read_table_rows_at(path: &Path, locations: &[RowLocation]) -> io::Result<(JsonSQLTable, Vec<RowLocation>)> {
+    let mut file = TableFile::open(path)?;
+    let definition = file.definition()?;
+    let visibility = sql_transactions::visibility();
+    sql_transactions::record_read(path);
+    let mut locations = locations.to_vec();
+    locations.sort_unstable();
+    locations.dedup();
+
+    let mut rows = vec![];
+    let mut rows_locations = vec![];
+    for location in locations {
+        let (version, row) = file.row_at(location)?;
+        if visibility.is_visible(version) {
+            rows.push(definition.typed_row(serde_json::from_slice(&row)?));
+            rows_locations.push(location);
+        };
+    }
+
+    Ok((JsonSQLTable { rows: if !rows.is_empty() { Some(rows) } else { None }, ..definition }, rows_locations))
+}
+
+/// Read stored versions of rows under given locations (also versions which aren't seen by statement, e.g: created by another transaction in progress). Empty locations are omitted
+pub fn read_versions_at(path: &Path, locations: &[RowLocation]) -> io::Result<Vec<RowVersion>> {
+    let mut file = TableFile::open(path)?;
+    let definition = file.definition()?;
+
+    let mut versions = vec![];
+    for location in locations {
+        let page = file.read_page(location.0)?;
+        if page[0] != HEAP_PAGE {
+            continue;
+        };
+        if let Some(record) = HeapPage::decode(&page)?.slots.get(location.1).cloned().flatten() {
+            let (version, row) = file.record_row(&record)?;
+            versions.push((*location, version, definition.typed_row(serde_json::from_slice(&row)?)));
+        };
+    }
+
+    Ok(versions)
+}
+
+/// Table opened by statement in order to change its rows one by one (e.g: rows matched by condition of UPDATE). Changes are written to table file and to indexes of table by "save".
+/// Versions which can't be seen by any transaction are removed from pages in which rows are changed
+pub struct TableWriter {
+    file: TableFile,
+    definition: JsonSQLTable,
+    indexes: sql_index::TableIndexes,
+    visibility: sql_transactions::Visibility,
+    /// pages from which dead versions have been removed
+    pruned: BTreeSet<u32>,
+    /// rows removed from locations and rows saved under locations (for indexes)
+    removed: Vec<(RowLocation, Vec<JsonSQLTableColumnRow>)>,
+    saved: Vec<(RowLocation, Vec<JsonSQLTableColumnRow>)>,
+    changed: bool,
+}
+
+impl TableWriter {
+    /// Table definition (table without rows)
+    pub fn definition(&self) -> &JsonSQLTable {
+        &self.definition
+    }
+
+    /// Replace table definition by definition with the same columns (e.g: with moved counters of AUTO_INCREMENT columns)
+    pub fn set_definition(&mut self, definition: &JsonSQLTable) -> io::Result<()> {
+        let definition = JsonSQLTable { rows: None, ..definition.clone() };
+        self.file.set_definition(&serde_json::to_vec(&definition)?)?;
+        self.definition = definition;
+        self.changed = true;
+
+        Ok(())
+    }
+
+    fn typed_row(&self, row: &[u8]) -> io::Result<Vec<JsonSQLTableColumnRow>> {
+        Ok(self.definition.typed_row(serde_json::from_slice(row)?))
+    }
+
+    /// Remove dead versions from page (once for each page)
+    fn prune(&mut self, number: u32) -> io::Result<()> {
+        if self.pruned.insert(number) {
+            for (pruned, row) in self.file.prune_page(number, &self.visibility)? {
+                if self.indexes.is_maintained() {
+                    self.removed.push((pruned, self.typed_row(&row)?));
+                };
+            }
+        };
+        Ok(())
+    }
+
+    /// Remove dead versions from page of location and return row stored under location when indexes of table have to be changed
+    fn prepare(&mut self, location: RowLocation) -> io::Result<Option<Vec<JsonSQLTableColumnRow>>> {
+        self.prune(location.0)?;
+        self.changed = true;
+
+        match self.indexes.is_maintained() {
+            true => {
+                let (_, row) = self.file.row_at(location)?;
+                self.typed_row(&row).map(Some)
+            },
+            false => Ok(None)
+        }
+    }
+
+    /// Entries of indexes for previous version of row which has been kept under other location (e.g: moved to other page)
+    fn keep(&mut self, location: RowLocation, kept: Option<RowLocation>, row: Option<Vec<JsonSQLTableColumnRow>>) {
+        if let Some(row) = row {
+            if kept != Some(location) {
+                if let Some(kept) = kept {
+                    self.saved.push((kept, row.clone()));
+                };
+                self.removed.push((location, row));
+            }
+        }
+    }
+
+    /// Insert row created by writer of statement and return its location
+    pub fn insert_row(&mut self, row: &[JsonSQLTableColumnRow]) -> io::Result<RowLocation> {
+        let location = self.file.insert_row(&serde_json::to_vec(row)?, Version { created: self.visibility.writer(), removed: 0 })?;
+        if self.indexes.is_maintained() {
+            self.saved.push((location, row.to_vec()));
+        };
+        self.changed = true;
+
+        Ok(location)
+    }
+
+    /// Replace row seen by statement by new row and return location of new row
+    pub fn update_at(&mut self, location: RowLocation, row: &[JsonSQLTableColumnRow]) -> io::Result<RowLocation> {
+        let stored_row = self.prepare(location)?;
+        let (kept, new_location) = self.file.update_at(location, &serde_json::to_vec(row)?, &self.visibility)?;
+        self.keep(location, kept, stored_row);
+        if self.indexes.is_maintained() {
+            self.saved.push((new_location, row.to_vec()));
+        };
+
+        Ok(new_location)
+    }
+
+    /// Delete row seen by statement
+    pub fn delete_at(&mut self, location: RowLocation) -> io::Result<()> {
+        let stored_row = self.prepare(location)?;
+        let kept = self.file.delete_at(location, &self.visibility)?;
+        self.keep(location, kept, stored_row);
+
+        Ok(())
+    }
+
+    /// Write changes to table file and to indexes when many pages have been changed (e.g: when all rows of big table are changed), so only part of table is kept in memory. Changes written before end of statement are reverted with statement when it fails
+    pub fn write_changes(&mut self) -> io::Result<()> {
+        if self.file.changed_pages.len() < CHANGED_PAGES_LIMIT {
+            return Ok(());
+        };
+
+        let (file, visibility) = (&mut self.file, &self.visibility);
+        let mut live = |location| file.version_at(location).and_then(|version| visibility.holds_values(version));
+        self.indexes.update(&std::mem::take(&mut self.removed), std::mem::take(&mut self.saved).iter().map(|(location, row)| (*location, row.as_slice())), &mut live)?;
+        if self.changed {
+            sql_transactions::record_change(&self.file.path);
+        };
+
+        self.file.flush()?;
+        self.indexes.flush()
+    }
+
+    /// Remove all rows seen by statement page after page. Each removed row is passed to "removed" (e.g: in order to perform actions of foreign keys which reffer to it)
+    fn remove_all(&mut self, mut removed: impl FnMut(Vec<JsonSQLTableColumnRow>)) -> io::Result<()> {
+        self.changed = true;
+
+        for number in 1..self.file.header.page_count {
+            self.prune(number)?;
+            for (slot, version, row) in self.file.page_rows(number)? {
+                if self.visibility.is_visible(version) {
+                    removed(self.typed_row(&row)?);
+                    self.delete_at((number, slot))?;
+                };
+            }
+            self.write_changes()?;
+        }
+        Ok(())
+    }
+
+    /// Write changed pages to table file and change entries of indexes for changed rows (unique index rejects row with the same values as live row and row of transaction in progress is write-write conflict)
+    pub fn save(mut self) -> io::Result<()> {
+        if !self.changed && !self.indexes.is_rebuilt() {
+            return Ok(());
+        };
+
+        // Indexes of keys which haven't been built yet (e.g: for table saved before keys had got indexes) are built from all rows
+        let (file, visibility) = (&mut self.file, &self.visibility);
+        rebuild_indexes(file, &self.definition, &mut self.indexes, visibility)?;
+        let mut live = |location| file.version_at(location).and_then(|version| visibility.holds_values(version));
+        self.indexes.update(&self.removed, self.saved.iter().map(|(location, row)| (*location, row.as_slice())), &mut live)?;
+        if self.changed {
+            sql_transactions::record_change(&self.file.path);
+        };
+
+        self.file.flush()?;
+        self.indexes.flush()
+    }
+}
+
+/// Add entries of all stored versions of rows (read page after page) to indexes which are built again (e.g: index of key added to table)
+fn rebuild_indexes(file: &mut TableFile, definition: &JsonSQLTable, indexes: &mut sql_index::TableIndexes, visibility: &sql_transactions::Visibility) -> io::Result<()> {
+    if !indexes.is_rebuilt() {
+        return Ok(());
+    };
+
+    for number in 1..file.header.page_count {
+        for (slot, _, row) in file.page_rows(number)? {
+            let row = definition.typed_row(serde_json::from_slice(&row)?);
+            let mut live = |location| file.version_at(location).and_then(|version| visibility.holds_values(version));
+            indexes.rebuild((number, slot), &row, &mut live)?;
+        }
+        indexes.write_changes()?;
+    }
+    Ok(())
+}
+
+/// Open table file in order to change its rows by statement performed by thread
+pub fn table_writer(path: &Path) -> io::Result<TableWriter> {
+    let mut file = TableFile::open(path)?;
+    let definition = file.definition()?;
+    let indexes = sql_index::TableIndexes::open(path, Some(&definition), &definition)?;
+
+    Ok(TableWriter { file, definition, indexes, visibility: sql_transactions::visibility(), pruned: BTreeSet::new(), removed: vec![], saved: vec![], changed: false })
+}
+
+/// Open table file (file is created when it doesn't exist) whose definition is replaced by definition of table. Returned writer changes rows of table and indexes according to new definition and previous definition of table is returned beside it
+fn replacing_writer(path: &Path, table: &JsonSQLTable) -> io::Result<(TableWriter, Option<JsonSQLTable>)> {
+    let mut file = if path.exists() { TableFile::open(path)? } else { TableFile::create(path) };
+    let definition = JsonSQLTable { name: table.name.clone(), columns: table.columns.clone(), rows: None, constraints: table.constraints.clone() };
+    let stored_definition = if file.header.definition_page != 0 { Some(file.definition()?) } else { None };
+    let indexes = sql_index::TableIndexes::open(path, stored_definition.as_ref(), &definition)?;
+    file.set_definition(&serde_json::to_vec(&definition)?)?;
+
+    Ok((TableWriter { file, definition, indexes, visibility: sql_transactions::visibility(), pruned: BTreeSet::new(), removed: vec![], saved: vec![], changed: true }, stored_definition))
+}
+
+/// Save table to its file (file is created when it doesn't exist): table definition is replaced and all rows seen by statement are replaced by rows of table (e.g: after columns of table have been changed).
+/// Rows are removed page after page and inserted in the same way as by "TableWriter" and versions which can't be seen by any transaction are removed. Indexes of table are changed together with table (for each removed and saved row)
+pub fn write_table(path: &Path, table: &JsonSQLTable) -> io::Result<()> {
+    let (mut writer, _) = replacing_writer(path, table)?;
+    writer.remove_all(|_| ())?;
+
+    for row in table.rows.iter().flatten() {
+        writer.insert_row(row)?;
+        writer.write_changes()?;
+    }
+    writer.save()
+}
+
+/// Remove all rows seen by statement from table file page after page (e.g: "TRUNCATE"). Each removed row is passed to "removed"
+pub fn remove_rows(path: &Path, removed: impl FnMut(Vec<JsonSQLTableColumnRow>)) -> io::Result<()> {
+    let mut writer = table_writer(path)?;
+    writer.remove_all(removed)?;
+    writer.save()
+}
+
+/// Replace table definition by definition of table (e.g: after its column has been renamed) and rows seen by statement by rows returned by "change". Rows are read page after page (typed by previous definition) and
+/// each page of rows is changed at once. Only rows which have been changed are saved and new versions which are placed in pages which haven't been read yet aren't changed again
+pub fn rewrite_table(path: &Path, table: &JsonSQLTable, mut change: impl FnMut(Vec<Vec<JsonSQLTableColumnRow>>) -> io::Result<Vec<Vec<JsonSQLTableColumnRow>>>) -> io::Result<()> {
+    let (mut writer, stored_definition) = replacing_writer(path, table)?;
+    let stored_definition = stored_definition.ok_or_else(corrupted)?;
+    let page_count = writer.file.header.page_count;
+
+    let mut moved = HashSet::new();
+    for number in 1..page_count {
+        let (mut locations, mut rows) = (vec![], vec![]);
+        for (slot, version, row) in writer.file.page_rows(number)? {
+            if writer.visibility.is_visible(version) && !moved.remove(&(number, slot)) {
+                locations.push((number, slot));
+                rows.push(stored_definition.typed_row(serde_json::from_slice(&row)?));
+            };
+        }
+
+        let changed_rows = change(rows.clone())?;
+        for ((location, row), changed_row) in locations.into_iter().zip(rows).zip(changed_rows) {
+            if changed_row != row {
+                let new_location = writer.update_at(location, &changed_row)?;
+                if new_location.0 > number && new_location.0 < page_count {
+                    moved.insert(new_location);
+                };
+            };
+        }
+        writer.write_changes()?;
+    }
+    writer.save()
+}
+
+/// Resolve versions of rows of finished transactions (according to "committed" which tells whether transaction has been committed, None for transaction whose versions are kept, e.g: because some snapshot could still see them differently).
+/// Rows created by transactions which weren't committed and rows removed by committed transactions are removed, ids of other resolved transactions are cleared (so their rows are seen by all transactions).
+/// Pages are resolved one after another and only changed pages are written. Indexes of keys which haven't been built yet (for table saved before keys had got indexes) are built from resolved rows
+pub fn resolve_versions(path: &Path, committed: impl Fn(u64) -> Option<bool>) -> io::Result<()> {
+    let mut file = TableFile::open(path)?;
+    let definition = file.definition()?;
+    let mut indexes = sql_index::TableIndexes::open(path, Some(&definition), &definition)?;
+    let resolve = |id: u64| if id != 0 { committed(id) } else { None };
+
+    for number in 1..file.header.page_count {
+        let mut removed = vec![];
+        for (slot, version, row) in file.page_rows(number)? {
+            let (created, removed_by) = (resolve(version.created), resolve(version.removed));
+            if created == Some(false) || removed_by == Some(true) {
+                removed.push(((number, slot), definition.typed_row(serde_json::from_slice(&row)?)));
+                file.delete_row((number, slot))?;
+                continue;
+            };
+
+            let resolved = Version { created: if created.is_some() { 0 } else { version.created }, removed: if removed_by.is_some() { 0 } else { version.removed } };
+            if resolved != version {
+                file.set_version((number, slot), resolved)?;
+            };
+        }
+        indexes.update(&removed, std::iter::empty(), &mut |_| Ok(false))?;
+
+        if file.changed_pages.len() >= CHANGED_PAGES_LIMIT {
+            file.flush()?;
+            indexes.flush()?;
+        };
+    }
+    rebuild_indexes(&mut file, &definition, &mut indexes, &sql_transactions::visibility())?;
+
+    file.flush()?;
+    indexes.flush()
+}
+
+#[test]
+fn test_write_table() {
+    use super::sql_json::{JsonSQLTableColumn, SupportedSQLDataTypes};
+    use super::sql_expr::RowValue;
+
+    let dir = std::env::temp_dir().join(format!("wastledb_storage_{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    sql_wal::set_data_path(&dir);
+    let path = table_path(&dir, "t");
+    let row = |id: i128, text: &str| vec![JsonSQLTableColumnRow { col: "id".to_string(), value: RowValue::Int(id) }, JsonSQLTableColumnRow { col: "text".to_string(), value: RowValue::Text(text.to_string()) }];
+    let table = JsonSQLTable {
+        name: "t".to_string(),
+        columns: vec![JsonSQLTableColumn { name: "id".to_string(), d_type: SupportedSQLDataTypes::INT, constraints: None }, JsonSQLTableColumn { name: "text".to_string(), d_type: SupportedSQLDataTypes::TEXT, constraints: None }],
+        rows: Some((0..1000).map(|id| row(id, "x")).chain([row(1000, &"long".repeat(5000))]).collect()),
+        constraints: None
+    };
+    write_table(&path, &table).unwrap();
+    let size = std::fs::metadata(&path).unwrap().len();
+    assert_eq!(serde_json::to_string(&read_table(&path).unwrap()).unwrap(), serde_json::to_string(&table).unwrap());
+
+    // Changed rows stay on their positions and space of removed rows (also of blob with long row) is reused
+    let mut rows = table_rows(&path).unwrap();
+    let locations = std::iter::from_fn(|| rows.next_located()).map(|row| row.unwrap().0).collect::<Vec<_>>();
+    let mut writer = table_writer(&path).unwrap();
+    writer.update_at(locations[10], &row(10, "changed")).unwrap();
+    for location in &locations[900..] {
+        writer.delete_at(*location).unwrap();
+    }
+    writer.save().unwrap();
+    let mut writer = table_writer(&path).unwrap();
+    for id in 2000..2050 {
+        writer.insert_row(&row(id, "y")).unwrap();
+    }
+    writer.save().unwrap();
+
+    let stored = read_table(&path).unwrap();
+    assert_eq!(stored.rows.as_ref().unwrap()[10], row(10, "changed"));
+    assert_eq!(stored.rows.unwrap().len(), 950);
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), size);
+
+    // Saved table replaces all rows of table
+    write_table(&path, &table).unwrap();
+    assert_eq!(read_table(&path).unwrap().rows.unwrap().len(), 1001);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,589 @@
+/* Transactions of sessions ("BEGIN", "COMMIT", "ROLLBACK", savepoints and isolation levels) with multi-version concurrency control of rows.
+   Each stored version of row has got id of transaction which created it and id of transaction which removed it (by delete or by update which created newer version of row). Statement sees versions according to snapshot: versions created by transactions committed before snapshot was taken (and by own transaction) which haven't been removed by them.
+   READ COMMITTED transaction takes snapshot for each statement, REPEATABLE READ and SERIALIZABLE transactions use snapshot taken by their first statement. Version removed by another transaction which hasn't been rolled back can't be changed (write-write conflict) and SERIALIZABLE transaction can't be committed when tables read by it have been changed by transactions committed after its snapshot.
+   Rolled back transaction is marked as aborted, so its versions are seen by nobody (they are removed by next change of table). Ids of committed transactions are saved in commit log, so versions of transactions which weren't committed before crash are removed on start (after that all versions are seen by all transactions, commit log is emptied and ids are given from start).
+   Versions of many transactions finished below horizon are resolved in the same way by checkpoint, after which their ids are forgotten (also by commit log).
+   Changes of definitions (e.g: created or altered tables) performed in transaction aren't versioned: they belong to statement of write-ahead log which stays in progress until "COMMIT", so they are reverted by "ROLLBACK" (also to savepoint), when session expires or when server crashed before transaction was committed. Files changed by them can't be used by other sessions until transaction ends */
+use std::{fs, io, path::{Path, PathBuf}, collections::{BTreeMap, BTreeSet, HashMap, HashSet}, cell::RefCell, sync::{Arc, Mutex}};
+
+use serde::{Deserialize, Serialize};
+use sqlparser::ast::TransactionIsolationLevel;
+
+use super::{sql_storage::{self, Version}, sql_wal};
+
+/// Name of log with ids of committed transactions in data directory
+const COMMIT_LOG_FILE: &str = "wastledb.clog";
+/// Number of finished transactions (whose versions can be resolved) after which checkpoint resolves their versions in tables (checkpoint is tried again only after the same number of next finished transactions)
+const CHECKPOINT_TRANSACTIONS: usize = 256;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+/// Supported isolation levels of transactions
+pub enum IsolationLevel {
+    #[default]
+    ReadCommitted,
+    RepeatableRead,
+    Serializable,
+}
+
+impl IsolationLevel {
+    /// Convert level from sqlparser. "READ UNCOMMITTED" works as "READ COMMITTED" because uncommitted changes are never seen by other transactions
+    pub fn convert(level: &TransactionIsolationLevel) -> IsolationLevel {
+        match level {
+            TransactionIsolationLevel::ReadUncommitted | TransactionIsolationLevel::ReadCommitted => IsolationLevel::ReadCommitted,
+            TransactionIsolationLevel::RepeatableRead => IsolationLevel::RepeatableRead,
+            TransactionIsolationLevel::Serializable => IsolationLevel::Serializable
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+/// Transactions whose versions are seen by statement
+pub struct Snapshot {
+    /// versions created by transactions with this or greater id aren't seen (transactions began after snapshot)
+    next_id: u64,
+    /// transactions which were in progress when snapshot was taken
+    active: BTreeSet<u64>,
+    /// number of transactions committed before snapshot
+    commits: u64,
+}
+
+impl Snapshot {
+    /// Lowest id of transaction whose versions could be unseen by snapshot
+    fn horizon(&self) -> u64 {
+        self.active.first().map_or(self.next_id, |first| (*first).min(self.next_id))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Savepoint {
+    name: String,
+    /// number of changes of definitions performed by transaction before savepoint
+    position: usize,
+    /// position of first id of transaction which is used after savepoint
+    version: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+/// Transaction in progress in session
+pub struct Transaction {
+    /// statement of write-ahead log to which changes of definitions performed in transaction belong
+    log_statement: u64,
+    /// ids under which transaction creates and removes versions of rows: first id and next id for each savepoint (so changes performed after savepoint can be rolled back alone). Last id is used by next changes
+    versions: Vec<u64>,
+    /// savepoints in order of creation
+    savepoints: Vec<Savepoint>,
+    isolation: IsolationLevel,
+    /// snapshot used by all statements of REPEATABLE READ and SERIALIZABLE transaction (taken by first statement)
+    snapshot: Option<Snapshot>,
+    /// whether some statement has been performed in transaction (isolation level can't be changed after that)
+    started: bool,
+    /// tables read by transaction
+    read: BTreeSet<PathBuf>,
+    /// tables changed by transaction
+    changed: BTreeSet<PathBuf>,
+}
+
+impl Transaction {
+    /// Statement of write-ahead log inside of which changes of definitions are performed
+    pub fn log_statement(&self) -> u64 {
+        self.log_statement
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Statements which control transaction of session. "AND CHAIN" begins new transaction (with same isolation level) after end of previous one
+pub enum TransactionStatement {
+    /// begin transaction with isolation level (None for isolation level of session)
+    Begin(Option<IsolationLevel>),
+    Commit { chain: bool },
+    Rollback { chain: bool },
+    Savepoint(String),
+    RollbackToSavepoint(String),
+    ReleaseSavepoint(String),
+    /// set isolation level of transaction in progress or (when "session" is set or when no transaction is in progress) of next transactions of session
+    SetIsolationLevel { level: IsolationLevel, session: bool },
+}
+
+#[derive(Default)]
+/// State of transactions of server
+struct Transactions {
+    /// last id given to transaction
+    last_id: u64,
+    /// ids of transactions in progress
+    active: BTreeSet<u64>,
+    /// ids of rolled back transactions (their versions are seen by nobody)
+    aborted: Arc<HashSet<u64>>,
+    /// ids of finished transactions whose versions can be left in tables (committed transactions which changed something and rolled back transactions) with tables changed by them
+    finished: BTreeMap<u64, BTreeSet<PathBuf>>,
+    /// number of transactions finished since last checkpoint was tried
+    finished_since_checkpoint: usize,
+    /// number of committed transactions
+    commits: u64,
+    /// tables changed by committed transactions with number of commit (kept while serializable transaction whose snapshot was taken before commit is in progress)
+    history: Vec<(u64, BTreeSet<PathBuf>)>,
+    /// snapshots in use: first id of transaction which uses snapshot, snapshot and information whether transaction is serializable
+    snapshots: HashMap<u64, (Snapshot, bool)>,
+}
+
+impl Transactions {
+    fn allocate(&mut self) -> u64 {
+        self.last_id += 1;
+        self.active.insert(self.last_id);
+        self.last_id
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot { next_id: self.last_id + 1, active: self.active.clone(), commits: self.commits }
+    }
+
+    /// Take snapshot used by transaction (it replaces previous snapshot of transaction)
+    fn take_snapshot(&mut self, transaction: u64, serializable: bool) -> Snapshot {
+        let snapshot = self.snapshot();
+        self.snapshots.insert(transaction, (snapshot.clone(), serializable));
+        snapshot
+    }
+
+    /// Lowest id of transaction whose versions could be unseen by some snapshot in use (or by snapshot taken now)
+    fn horizon(&self) -> u64 {
+        self.snapshots.values().map(|(snapshot, _)| snapshot.horizon()).fold(self.snapshot().horizon(), u64::min)
+    }
+
+    /// End transaction (all its ids): its versions are seen by next snapshots when it has been committed and they are seen by nobody when it has been rolled back
+    fn finish(&mut self, ids: &[u64], committed: bool, changed: &BTreeSet<PathBuf>) {
+        for id in ids {
+            self.active.remove(id);
+        }
+        if let Some(first) = ids.first() {
+            self.snapshots.remove(first);
+        };
+
+        if committed {
+            self.commits += 1;
+            if !changed.is_empty() {
+                self.finished.extend(ids.iter().map(|id| (*id, changed.clone())));
+                self.finished_since_checkpoint += ids.len();
+            };
+            if !changed.is_empty() && self.snapshots.values().any(|(_, serializable)| *serializable) {
+                self.history.push((self.commits, changed.clone()));
+            };
+        }
+        else {
+            self.abort(ids, changed);
+        };
+
+        // Commits seen by snapshots of all serializable transactions aren't needed anymore
+        let oldest = self.snapshots.values().filter(|(_, serializable)| *serializable).map(|(snapshot, _)| snapshot.commits).min();
+        self.history.retain(|(commit, _)| oldest.is_some_and(|oldest| *commit > oldest));
+    }
+
+    /// Mark ids as rolled back. Their versions can be left only in tables changed by transaction
+    fn abort(&mut self, ids: &[u64], changed: &BTreeSet<PathBuf>) {
+        for id in ids {
+            self.active.remove(id);
+        }
+        Arc::make_mut(&mut self.aborted).extend(ids);
+        self.finished.extend(ids.iter().map(|id| (*id, changed.clone())));
+        self.finished_since_checkpoint += ids.len();
+    }
+
+    fn visibility(&self, snapshot: Snapshot, own: Vec<u64>, writer: u64) -> Visibility {
+        Visibility { snapshot, own, writer, aborted: Arc::clone(&self.aborted), active: self.active.clone(), horizon: self.horizon() }
+    }
+}
+
+/// Transactions of data directories (created on first use)
+static TRANSACTIONS: Mutex<BTreeMap<PathBuf, Transactions>> = Mutex::new(BTreeMap::new());
+
+/// Run function with transactions of data directory used by thread
+fn with_transactions<T>(function: impl FnOnce(&mut Transactions) -> T) -> T {
+    let mut transactions = TRANSACTIONS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    function(transactions.entry(sql_wal::data_path()).or_default())
+}
+
+/// Location of commit log in data directory used by thread
+fn commit_log_path() -> PathBuf {
+    sql_wal::data_path().join(COMMIT_LOG_FILE)
+}
+
+#[derive(Debug, Clone)]
+/// Versions of rows seen by statement and id under which statement changes rows
+pub struct Visibility {
+    snapshot: Snapshot,
+    /// ids of own transaction
+    own: Vec<u64>,
+    /// id under which versions are created and removed (0 for changes performed outside of transaction which replace rows)
+    writer: u64,
+    aborted: Arc<HashSet<u64>>,
+    /// transactions in progress when statement began
+    active: BTreeSet<u64>,
+    /// transactions with lower id which haven't been rolled back are seen by all snapshots
+    horizon: u64,
+}
+
+impl Visibility {
+    /// Whether changes of transaction are seen by snapshot (id 0 is used by versions seen by all transactions)
+    fn sees(&self, id: u64) -> bool {
+        id == 0 || self.own.contains(&id) || (id < self.snapshot.next_id && !self.snapshot.active.contains(&id) && !self.aborted.contains(&id))
+    }
+
+    pub fn is_visible(&self, version: Version) -> bool {
+        self.sees(version.created) && (version.removed == 0 || !self.sees(version.removed))
+    }
+
+    /// Whether version can't be seen by any transaction (so it can be removed from table)
+    pub fn is_dead(&self, version: Version) -> bool {
+        self.aborted.contains(&version.created) || (version.removed != 0 && version.removed < self.horizon && !self.aborted.contains(&version.removed))
+    }
+
+    /// Whether version holds its values (for unique indexes): it hasn't been created by rolled back transaction and it hasn't been removed by committed or own transaction
+    pub fn is_live(&self, version: Version) -> bool {
+        !self.aborted.contains(&version.created)
+            && (version.removed == 0 || self.aborted.contains(&version.removed) || (self.active.contains(&version.removed) && !self.own.contains(&version.removed)))
+    }
+
+    /// Whether version has been created or removed by another transaction which is still in progress (so it isn't known yet whether it will hold its values)
+    pub fn is_pending(&self, version: Version) -> bool {
+        [version.created, version.removed].iter().any(|id| self.active.contains(id) && !self.own.contains(id))
+    }
+
+    /// Whether live version holds its values (see "is_live"). Live version of another transaction in progress is write-write conflict, because row with the same values can't be written until that transaction ends
+    pub fn holds_values(&self, version: Version) -> io::Result<bool> {
+        match self.is_live(version) {
+            true if self.is_pending(version) => Err(conflict()),
+            live => Ok(live)
+        }
+    }
+
+    /// Whether version seen by statement has been removed by another transaction (which is in progress or has been committed after snapshot), so it can't be changed
+    pub fn is_conflicting(&self, version: Version) -> bool {
+        version.removed != 0 && !self.aborted.contains(&version.removed) && !self.own.contains(&version.removed)
+    }
+
+    /// Id under which versions are created and removed (0 when rows are replaced without versions)
+    pub fn writer(&self) -> u64 {
+        self.writer
+    }
+
+    /// Whether version has been created by statements of own transaction performed after last savepoint (so it can be replaced without keeping previous version)
+    pub fn is_written(&self, version: Version) -> bool {
+        self.writer == 0 || version.created == self.writer
+    }
+}
+
+/// State of statement performed by thread
+struct Context {
+    visibility: Visibility,
+    /// own transaction of statement performed outside of transaction of session
+    alone: Option<u64>,
+    read: BTreeSet<PathBuf>,
+    changed: BTreeSet<PathBuf>,
+    /// whether statement couldn't change row because of another transaction
+    conflicted: bool,
+}
+
+thread_local! {
+    static CONTEXT: RefCell<Option<Context>> = const { RefCell::new(None) };
+}
+
+/// Versions seen by statement performed by thread. Outside of statement all committed versions are seen
+pub fn visibility() -> Visibility {
+    CONTEXT
+        .with_borrow(|context| context.as_ref().map(|context| context.visibility.clone()))
+        .unwrap_or_else(|| with_transactions(|transactions| transactions.visibility(transactions.snapshot(), vec![], 0)))
+}
+
+/// Remember table read by statement
+pub fn record_read(path: &Path) {
+    CONTEXT.with_borrow_mut(|context| context.as_mut().map(|context| context.read.insert(path.to_path_buf())));
+}
+
+/// Remember table changed by statement
+pub fn record_change(path: &Path) {
+    CONTEXT.with_borrow_mut(|context| context.as_mut().map(|context| context.changed.insert(path.to_path_buf())));
+}
+
+/// Error of statement which tried to change version removed by another transaction
+pub fn conflict() -> io::Error {
+    CONTEXT.with_borrow_mut(|context| context.as_mut().map(|context| context.conflicted = true));
+    io::Error::new(io::ErrorKind::WouldBlock, "Row has been changed by another transaction")
+}
+
+/// Whether statement performed by thread has been stopped by write-write conflict
+pub fn conflicted() -> bool {
+    CONTEXT.with_borrow(|context| context.as_ref().is_some_and(|context| context.conflicted))
+}
+
+/// Begin statement performed in transaction of session (or in its own transaction when session hasn't got transaction in progress). Statement sees versions of rows according to snapshot of transaction
+pub fn begin_statement(transaction: Option<&mut Transaction>) {
+    let context = with_transactions(|transactions| match transaction {
+        Some(transaction) => {
+            let first = transaction.versions[0];
+            let snapshot = match (&transaction.snapshot, transaction.isolation) {
+                (_, IsolationLevel::ReadCommitted) => transactions.take_snapshot(first, false),
+                (Some(snapshot), _) => snapshot.clone(),
+                (None, isolation) => transaction.snapshot.insert(transactions.take_snapshot(first, isolation == IsolationLevel::Serializable)).clone()
+            };
+            transaction.started = true;
+
+            let visibility = transactions.visibility(snapshot, transaction.versions.clone(), *transaction.versions.last().unwrap());
+            Context { visibility, alone: None, read: BTreeSet::new(), changed: BTreeSet::new(), conflicted: false }
+        },
+        None => {
+            let id = transactions.allocate();
+            let snapshot = transactions.take_snapshot(id, false);
+            Context { visibility: transactions.visibility(snapshot, vec![id], id), alone: Some(id), read: BTreeSet::new(), changed: BTreeSet::new(), conflicted: false }
+        }
+    });
+
+    CONTEXT.set(Some(context));
+}
+
+/// Append ids of committed transaction to commit log (in statement of write-ahead log performed by thread)
+fn log_commit(ids: &[u64]) -> io::Result<()> {
+    let path = &commit_log_path();
+    let length = match fs::metadata(path) {
+        Ok(metadata) => metadata.len(),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => 0,
+        Err(err) => return Err(err)
+    };
+
+    sql_wal::write_at(path, vec![(length, ids.iter().flat_map(|id| id.to_le_bytes()).collect())])
+}
+
+/// End statement begun by "begin_statement". Statement performed in own transaction is committed when it has been performed (in statement of write-ahead log performed by thread) or rolled back.
+/// Tables read and changed by performed statement are remembered by transaction of session
+pub fn end_statement(transaction: Option<&mut Transaction>, performed: bool) -> Result<(), String> {
+    let context = match CONTEXT.take() {
+        Some(context) => context,
+        None => return Ok(())
+    };
+
+    match (transaction, context.alone) {
+        (_, Some(id)) => {
+            let committed = performed && (context.changed.is_empty() || log_commit(&[id]).is_ok());
+            with_transactions(|transactions| transactions.finish(&[id], committed, &context.changed));
+            if performed && !committed {
+                return Err("Couldn't commit changes performed by statement".to_string());
+            };
+        },
+        (Some(transaction), None) if performed => {
+            transaction.read.extend(context.read);
+            transaction.changed.extend(context.changed);
+        },
+        _ => ()
+    };
+
+    Ok(())
+}
+
+fn begin(isolation: IsolationLevel) -> Result<Transaction, String> {
+    let log_statement = sql_wal::begin_transaction().map_err(|_| "Couldn't begin transaction".to_string())?;
+    let id = with_transactions(Transactions::allocate);
+
+    Ok(Transaction { log_statement, versions: vec![id], savepoints: vec![], isolation, snapshot: None, started: false, read: BTreeSet::new(), changed: BTreeSet::new() })
+}
+
+/// Commit transaction. Serializable transaction which changed something is rolled back when tables read by it have been changed by transactions committed after its snapshot
+fn commit(transaction: &Transaction) -> Result<(), String> {
+    if transaction.isolation == IsolationLevel::Serializable && !transaction.changed.is_empty() {
+        let serialized = with_transactions(|transactions| {
+            let commits = transaction.snapshot.as_ref().map_or(transactions.commits, |snapshot| snapshot.commits);
+            !transactions.history.iter().any(|(commit, changed)| *commit > commits && !changed.is_disjoint(&transaction.read))
+        });
+
+        if !serialized {
+            abort(transaction)?;
+            return Err("Transaction couldn't be serialized with transactions committed in the meantime, so it has been rolled back".to_string());
+        };
+    };
+
+    // Commit of transaction is saved together with changes of definitions performed by it
+    let statement = sql_wal::Statement::begin_inside(transaction.log_statement).map_err(|_| "Couldn't commit transaction".to_string())?;
+    let logged = if transaction.changed.is_empty() { Ok(()) } else { log_commit(&transaction.versions) };
+    match logged.and_then(|_| statement.commit()).and_then(|_| sql_wal::commit_transaction(transaction.log_statement)) {
+        Ok(_) => {
+            with_transactions(|transactions| transactions.finish(&transaction.versions, true, &transaction.changed));
+            Ok(())
+        },
+        Err(_) => {
+            abort(transaction)?;
+            Err("Couldn't commit transaction, so it has been rolled back".to_string())
+        }
+    }
+}
+
+/// Position of last savepoint with name (savepoint created later hides savepoint with same name)
+fn savepoint_position(transaction: &Transaction, name: &str) -> Result<usize, String> {
+    transaction.savepoints
+        .iter()
+        .rposition(|savepoint| savepoint.name == name)
+        .ok_or_else(|| format!("Savepoint \"{}\" doesn't exist", name))
+}
+
+/// Perform statement on transaction of session (None when session hasn't got transaction in progress). Isolation level of session is used by transactions begun without isolation level
+pub fn perform(statement: TransactionStatement, transaction: &mut Option<Transaction>, session_isolation: &mut Option<IsolationLevel>) -> Result<(), String> {
+    let current = match (&statement, transaction.as_mut()) {
+        (TransactionStatement::Begin(isolation), None) => {
+            *transaction = Some(begin(isolation.or(*session_isolation).unwrap_or_default())?);
+            return Ok(());
+        },
+        // Outside of transaction level is set for next transactions of session
+        (TransactionStatement::SetIsolationLevel { level, session: true }, _) | (TransactionStatement::SetIsolationLevel { level, .. }, None) => {
+            *session_isolation = Some(*level);
+            return Ok(());
+        },
+        (TransactionStatement::Begin(_), Some(_)) => return Err("Transaction is already in progress".to_string()),
+        (_, None) => return Err("There isn't any transaction in progress".to_string()),
+        (_, Some(current)) => current
+    };
+
+    match statement {
+        TransactionStatement::Commit { chain } => {
+            let isolation = current.isolation;
+            let committed = commit(current);
+            *transaction = None;
+            committed?;
+
+            if chain {
+                *transaction = Some(begin(isolation)?);
+            };
+        },
+        TransactionStatement::Rollback { chain } => {
+            let isolation = current.isolation;
+            abort(current)?;
+            *transaction = if chain { Some(begin(isolation)?) } else { None };
+        },
+        TransactionStatement::Savepoint(name) => {
+            let position = sql_wal::transaction_position(current.log_statement).map_err(|_| "Couldn't create savepoint".to_string())?;
+            current.versions.push(with_transactions(Transactions::allocate));
+            current.savepoints.push(Savepoint { name, position, version: current.versions.len() - 1 });
+        },
+        // Savepoint stays after rollback to it (changes performed after it get new id), savepoints created after it are removed
+        TransactionStatement::RollbackToSavepoint(name) => {
+            let position = savepoint_position(current, &name)?;
+            let savepoint = current.savepoints[position].clone();
+            sql_wal::rollback_transaction(current.log_statement, savepoint.position).map_err(|_| "Couldn't roll back transaction to savepoint".to_string())?;
+
+            let rolled_back = current.versions.split_off(savepoint.version);
+            with_transactions(|transactions| {
+                transactions.abort(&rolled_back, &current.changed);
+                current.versions.push(transactions.allocate());
+            });
+            current.savepoints.truncate(position + 1);
+        },
+        TransactionStatement::ReleaseSavepoint(name) => {
+            let position = savepoint_position(current, &name)?;
+            current.savepoints.truncate(position);
+        },
+        TransactionStatement::SetIsolationLevel { level, .. } => {
+            if current.started {
+                return Err("Isolation level must be set before any statement of transaction".to_string());
+            };
+            current.isolation = level;
+        },
+        TransactionStatement::Begin(_) => ()
+    };
+
+    Ok(())
+}
+
+/// Roll back transaction (e.g: transaction of expired session)
+pub fn abort(transaction: &Transaction) -> Result<(), String> {
+    let reverted = sql_wal::abort_transaction(transaction.log_statement);
+    with_transactions(|transactions| transactions.finish(&transaction.versions, false, &transaction.changed));
+    reverted.map_err(|_| "Couldn't roll back transaction".to_string())
+}
+
+/// Ids of committed transactions saved in commit log
+fn read_commit_log() -> io::Result<Vec<u64>> {
+    match fs::read(commit_log_path()) {
+        Ok(log) => Ok(log.chunks_exact(8).map(|id| u64::from_le_bytes(id.try_into().unwrap())).collect()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(vec![]),
+        Err(err) => Err(err)
+    }
+}
+
+/// Resolve versions of rows in tables changed by transactions. When some of them doesn't exist anymore (e.g: table has been renamed) versions are resolved in tables of all databases
+fn resolve_changed_tables(tables: &BTreeSet<PathBuf>, committed: impl Fn(u64) -> Option<bool>) -> io::Result<()> {
+    if tables.iter().any(|table| !table.exists()) {
+        return resolve_tables(committed);
+    };
+
+    for table in tables {
+        sql_storage::resolve_versions(table, &committed)?;
+    }
+    Ok(())
+}
+
+/// Resolve versions of rows in tables of all databases of data directory (see "sql_storage::resolve_versions")
+fn resolve_tables(committed: impl Fn(u64) -> Option<bool>) -> io::Result<()> {
+    for database in fs::read_dir(sql_wal::data_path())? {
+        let database = database?.path();
+        if !database.is_dir() {
+            continue;
+        };
+
+        for table in fs::read_dir(&database)? {
+            let table = table?.path();
+            if table.extension().is_some_and(|extension| extension == sql_storage::TABLE_EXTENSION) {
+                sql_storage::resolve_versions(&table, &committed)?;
+            };
+        }
+    }
+    Ok(())
+}
+
+/// Resolve versions left in tables of all databases of data directory by previous run of server (called on start before any transaction): versions of committed transactions are seen by all transactions and versions of other transactions are removed. Commit log is emptied after that
+pub fn recover() -> io::Result<()> {
+    let commit_log = commit_log_path();
+    let committed = read_commit_log()?.into_iter().collect::<HashSet<_>>();
+
+    resolve_tables(|id| Some(committed.contains(&id)))?;
+
+    if commit_log.exists() {
+        sql_wal::remove_file(&commit_log)?;
+    };
+    Ok(())
+}
+
+/// Resolve versions of transactions finished below horizon (seen in the same way by all snapshots) in tables changed by them, when there are many of them. Checkpoint is tried only after many transactions have finished since previous try, so it is cheap to call after each statement.
+/// After that their ids aren't needed anymore: they are forgotten by rolled back transactions and removed from commit log. Checkpoint is performed in statement of write-ahead log, so it is reverted together with resolved versions when it couldn't be finished
+/// (e.g: when some table is used by transaction in progress) and reason of failure is returned as Err (versions are kept for next checkpoint)
+pub fn checkpoint() -> Result<(), String> {
+    let due = with_transactions(|transactions| {
+        if transactions.finished_since_checkpoint < CHECKPOINT_TRANSACTIONS {
+            return None;
+        };
+        transactions.finished_since_checkpoint = 0;
+
+        let horizon = transactions.horizon();
+        let resolved = transactions.finished.range(..horizon).map(|(id, _)| *id).collect::<HashSet<_>>();
+        let tables = transactions.finished.range(..horizon).flat_map(|(_, changed)| changed.iter().cloned()).collect::<BTreeSet<_>>();
+        (resolved.len() >= CHECKPOINT_TRANSACTIONS).then(|| (resolved, tables, Arc::clone(&transactions.aborted)))
+    });
+    let (resolved, tables, aborted) = match due {
+        Some(due) => due,
+        None => return Ok(())
+    };
+
+    let statement = sql_wal::Statement::begin(None);
+    let checkpointed = resolve_changed_tables(&tables, |id| resolved.contains(&id).then(|| !aborted.contains(&id))).and_then(|_| {
+        let (commit_log, committed) = (commit_log_path(), read_commit_log()?);
+        let kept = committed.iter().filter(|id| !resolved.contains(id)).flat_map(|id| id.to_le_bytes()).collect::<Vec<_>>();
+        match kept.is_empty() {
+            _ if kept.len() == committed.len() * 8 => Ok(()),
+            true => sql_wal::remove_file(&commit_log),
+            false => sql_wal::write_file(&commit_log, &kept)
+        }
+    });
+    match checkpointed {
+        Ok(_) => statement.commit(),
+        Err(err) => statement.rollback().and(Err(err))
+    }.map_err(|err| format!("Couldn't resolve versions of finished transactions: {}", err))?;
+
+    with_transactions(|transactions| {
+        transactions.finished.retain(|id, _| !resolved.contains(id));
+        Arc::make_mut(&mut transactions.aborted).retain(|id| !resolved.contains(id));
+    });
+    Ok(())
+}
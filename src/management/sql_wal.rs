@@ -0,0 +1,813 @@
+/* Write-ahead log of changes of database files. Every change of file (pages of table, whole file, renaming of file or creation of directory) is described by log record together with previous state of changed data and record is synced to log file before change is applied.
+   Changes are grouped by statements: statement is committed by commit record after all its changes. When no statement is in progress changed files are synced and log is emptied (checkpoint). Statement is in progress from its first change, so statement which hasn't changed anything (e.g: idle transaction) doesn't stop checkpoints.
+   Statement can be also rolled back (whole or only its changes performed after some position, e.g: to savepoint of transaction): its changes are reverted and rollback record is logged, so they are reverted again when log is replayed.
+   Log left by crashed server is replayed on start: all logged changes (and rollbacks) are applied again (so changes of committed statements which weren't saved in files survive) and then changes of uncommitted statements are reverted in reverse order (so partially performed statements and torn writes are rolled back).
+   Files changed by statement which is in progress belong to it until it is committed, so they can't be read or changed by other statements (e.g: by statements of other sessions during transaction). Statement can be performed on behalf of transaction (other statement which is in progress): it can also use files of transaction (files changed by it belong to it unless they already belong to transaction) */
+use std::{fs::{self, File, OpenOptions}, io::{self, Read, Seek, SeekFrom, Write}, path::{Path, PathBuf}, collections::{BTreeMap, BTreeSet, HashMap, HashSet}, cell::{Cell, RefCell}, sync::Mutex};
+use super::sql_transactions;
+
+/// Data directory of server (with databases, write-ahead log and commit log of transactions)
+pub const DATA_PATH: &str = "../source/dbs";
+/// Name of log file in data directory
+const LOG_FILE: &str = "wastledb.wal";
+
+/// Kinds of log records
+const OPERATION_RECORD: u8 = 1;
+const COMMIT_RECORD: u8 = 2;
+const ROLLBACK_RECORD: u8 = 3;
+
+/// Kinds of logged operations
+const PAGES_OPERATION: u8 = 1;
+const FILE_OPERATION: u8 = 2;
+const RENAME_OPERATION: u8 = 3;
+const DIRECTORY_OPERATION: u8 = 4;
+
+/// Record is stored as: 1. length of record content (u32), 2. checksum of content (u64) and 3. content
+const FRAME_HEADER: usize = 12;
+
+/// Logged change of files
+#[derive(Debug, Clone, PartialEq)]
+enum Operation {
+    /// Parts of file overwritten: 1. file, 2. length of file before change (None when file didn't exist) and 3. offset, previous content (only part which was inside of file) and new content of each part
+    Pages { path: PathBuf, length: Option<u64>, pages: Vec<(u64, Vec<u8>, Vec<u8>)> },
+    /// Whole file replaced: content before and after change (None when file doesn't exist)
+    File { path: PathBuf, before: Option<Vec<u8>>, after: Option<Vec<u8>> },
+    /// File moved to not existing path
+    Rename { from: PathBuf, to: PathBuf },
+    /// Directory created
+    Directory { path: PathBuf },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Record {
+    /// 1. statement, 2. change performed by statement
+    Operation(u64, Operation),
+    /// statement which has been performed
+    Commit(u64),
+    /// 1. statement, 2. number of its first changes which are kept (later changes have been reverted)
+    Rollback(u64, u64),
+}
+
+/// Replace file by content or remove it when content is None
+fn set_file(path: &Path, content: &Option<Vec<u8>>) -> io::Result<()> {
+    match content {
+        Some(content) => fs::write(path, content),
+        None => match fs::remove_file(path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(())
+        }
+    }
+}
+
+impl Operation {
+    /// Files changed by operation
+    fn paths(&self) -> Vec<&Path> {
+        match self {
+            Operation::Pages { path, .. } | Operation::File { path, .. } | Operation::Directory { path } => vec![path],
+            Operation::Rename { from, to } => vec![from, to]
+        }
+    }
+
+    /// Apply change. Applying of change which has been already applied doesn't change anything
+    fn redo(&self) -> io::Result<()> {
+        match self {
+            Operation::Pages { path, pages, .. } => {
+                let mut file = OpenOptions::new().write(true).create(true).truncate(false).open(path)?;
+                for (offset, _, after) in pages {
+                    file.seek(SeekFrom::Start(*offset))?;
+                    file.write_all(after)?;
+                }
+                Ok(())
+            },
+            Operation::File { path, after, .. } => set_file(path, after),
+            Operation::Rename { from, to } if from.exists() && !to.exists() => fs::rename(from, to),
+            Operation::Rename { .. } => Ok(()),
+            Operation::Directory { path } => fs::create_dir_all(path)
+        }
+    }
+
+    /// Revert change (also when it wasn't applied)
+    fn undo(&self) -> io::Result<()> {
+        match self {
+            Operation::Pages { path, length: None, .. } => set_file(path, &None),
+            Operation::Pages { path, length: Some(length), pages } => {
+                let mut file = OpenOptions::new().write(true).create(true).truncate(false).open(path)?;
+                for (offset, before, _) in pages {
+                    file.seek(SeekFrom::Start(*offset))?;
+                    file.write_all(before)?;
+                }
+                file.set_len(*length)
+            },
+            Operation::File { path, before, .. } => set_file(path, before),
+            Operation::Rename { from, to } if to.exists() && !from.exists() => fs::rename(to, from),
+            Operation::Rename { .. } => Ok(()),
+            Operation::Directory { path } => match fs::remove_dir(path) {
+                Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+                _ => Ok(())
+            }
+        }
+    }
+}
+
+/// Checksum (FNV-1a) of record content, which detects records torn by crash
+fn checksum(content: &[u8]) -> u64 {
+    content.iter().fold(0xcbf29ce484222325, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x100000001b3))
+}
+
+fn invalid() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "Invalid write-ahead log record")
+}
+
+fn put_bytes(content: &mut Vec<u8>, bytes: &[u8]) -> io::Result<()> {
+    let length = u32::try_from(bytes.len()).map_err(|_| invalid())?;
+    content.extend_from_slice(&length.to_le_bytes());
+    content.extend_from_slice(bytes);
+    Ok(())
+}
+
+fn put_optional_bytes(content: &mut Vec<u8>, bytes: &Option<Vec<u8>>) -> io::Result<()> {
+    match bytes {
+        Some(bytes) => {
+            content.push(1);
+            put_bytes(content, bytes)
+        },
+        None => {
+            content.push(0);
+            Ok(())
+        }
+    }
+}
+
+fn put_path(content: &mut Vec<u8>, path: &Path) -> io::Result<()> {
+    put_bytes(content, path.to_str().ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Path can't be saved in write-ahead log"))?.as_bytes())
+}
+
+/// Reader of record content
+struct Content<'a>(&'a [u8]);
+
+impl<'a> Content<'a> {
+    fn take(&mut self, length: usize) -> io::Result<&'a [u8]> {
+        if self.0.len() < length {
+            return Err(invalid());
+        };
+        let (taken, rest) = self.0.split_at(length);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> io::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> io::Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn bytes(&mut self) -> io::Result<Vec<u8>> {
+        let length = self.u32()? as usize;
+        Ok(self.take(length)?.to_vec())
+    }
+
+    fn optional_bytes(&mut self) -> io::Result<Option<Vec<u8>>> {
+        match self.u8()? {
+            0 => Ok(None),
+            _ => self.bytes().map(Some)
+        }
+    }
+
+    fn path(&mut self) -> io::Result<PathBuf> {
+        String::from_utf8(self.bytes()?).map(PathBuf::from).map_err(|_| invalid())
+    }
+}
+
+impl Record {
+    fn encode(&self) -> io::Result<Vec<u8>> {
+        let mut content = vec![];
+        match self {
+            Record::Operation(statement, operation) => {
+                content.push(OPERATION_RECORD);
+                content.extend_from_slice(&statement.to_le_bytes());
+                match operation {
+                    Operation::Pages { path, length, pages } => {
+                        content.push(PAGES_OPERATION);
+                        put_path(&mut content, path)?;
+                        put_optional_bytes(&mut content, &length.map(|length| length.to_le_bytes().to_vec()))?;
+                        content.extend_from_slice(&(pages.len() as u32).to_le_bytes());
+                        for (offset, before, after) in pages {
+                            content.extend_from_slice(&offset.to_le_bytes());
+                            put_bytes(&mut content, before)?;
+                            put_bytes(&mut content, after)?;
+                        }
+                    },
+                    Operation::File { path, before, after } => {
+                        content.push(FILE_OPERATION);
+                        put_path(&mut content, path)?;
+                        put_optional_bytes(&mut content, before)?;
+                        put_optional_bytes(&mut content, after)?;
+                    },
+                    Operation::Rename { from, to } => {
+                        content.push(RENAME_OPERATION);
+                        put_path(&mut content, from)?;
+                        put_path(&mut content, to)?;
+                    },
+                    Operation::Directory { path } => {
+                        content.push(DIRECTORY_OPERATION);
+                        put_path(&mut content, path)?;
+                    }
+                }
+            },
+            Record::Commit(statement) => {
+                content.push(COMMIT_RECORD);
+                content.extend_from_slice(&statement.to_le_bytes());
+            },
+            Record::Rollback(statement, position) => {
+                content.push(ROLLBACK_RECORD);
+                content.extend_from_slice(&statement.to_le_bytes());
+                content.extend_from_slice(&position.to_le_bytes());
+            }
+        };
+
+        let mut frame = (u32::try_from(content.len()).map_err(|_| invalid())?).to_le_bytes().to_vec();
+        frame.extend_from_slice(&checksum(&content).to_le_bytes());
+        frame.extend(content);
+        Ok(frame)
+    }
+
+    fn decode(content: &[u8]) -> io::Result<Record> {
+        let mut content = Content(content);
+        let kind = content.u8()?;
+        let statement = content.u64()?;
+
+        let record = match kind {
+            OPERATION_RECORD => Record::Operation(statement, match content.u8()? {
+                PAGES_OPERATION => {
+                    let path = content.path()?;
+                    let length = content.optional_bytes()?.map(|length| length.try_into().map(u64::from_le_bytes).map_err(|_| invalid())).transpose()?;
+                    let pages = (0..content.u32()?)
+                        .map(|_| Ok((content.u64()?, content.bytes()?, content.bytes()?)))
+                        .collect::<io::Result<Vec<_>>>()?;
+                    Operation::Pages { path, length, pages }
+                },
+                FILE_OPERATION => Operation::File { path: content.path()?, before: content.optional_bytes()?, after: content.optional_bytes()? },
+                RENAME_OPERATION => Operation::Rename { from: content.path()?, to: content.path()? },
+                DIRECTORY_OPERATION => Operation::Directory { path: content.path()? },
+                _ => return Err(invalid())
+            }),
+            COMMIT_RECORD => Record::Commit(statement),
+            ROLLBACK_RECORD => Record::Rollback(statement, content.u64()?),
+            _ => return Err(invalid())
+        };
+
+        if !content.0.is_empty() {
+            return Err(invalid());
+        };
+        Ok(record)
+    }
+}
+
+/// Read all whole records from log. Reading stops on first torn (not fully saved) record
+fn read_records(log: &[u8]) -> Vec<Record> {
+    let mut records = vec![];
+    let mut position = 0;
+    while log.len() - position >= FRAME_HEADER {
+        let length = u32::from_le_bytes(log[position..position + 4].try_into().unwrap()) as usize;
+        let sum = u64::from_le_bytes(log[position + 4..FRAME_HEADER + position].try_into().unwrap());
+        let content = match log.get(position + FRAME_HEADER..position + FRAME_HEADER + length) {
+            Some(content) if checksum(content) == sum => content,
+            _ => break
+        };
+
+        match Record::decode(content) {
+            Ok(record) => records.push(record),
+            Err(_) => break
+        };
+        position += FRAME_HEADER + length;
+    }
+
+    records
+}
+
+/// Make changes of files (and of directories in which they are placed) durable
+fn sync_paths<'a>(paths: impl IntoIterator<Item = &'a Path>) -> io::Result<()> {
+    let mut directories = BTreeSet::new();
+    for path in paths {
+        if path.is_file() {
+            File::open(path)?.sync_all()?;
+        };
+        if let Some(directory) = path.parent() {
+            directories.insert(directory.to_path_buf());
+        };
+    }
+
+    // Directories can't be synced on all systems
+    for directory in directories {
+        File::open(directory).and_then(|directory| directory.sync_all()).ok();
+    }
+    Ok(())
+}
+
+/// Opened write-ahead log
+struct WriteAheadLog {
+    file: File,
+    /// last statement which obtained number
+    last_statement: u64,
+    /// statements which are in progress (statements which have changed something)
+    active: HashSet<u64>,
+    /// files changed since last checkpoint
+    changed: BTreeSet<PathBuf>,
+    /// changes performed by statements which are in progress (kept to roll them back)
+    performed: HashMap<u64, Vec<Operation>>,
+    /// statements which are in progress to which changed files belong
+    owners: HashMap<PathBuf, u64>,
+}
+
+impl WriteAheadLog {
+    /// Open log and recover files from records left in it (log is empty after opening)
+    fn open(path: &Path) -> io::Result<WriteAheadLog> {
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        };
+        let mut file = OpenOptions::new().read(true).append(true).create(true).open(path)?;
+        let mut log = vec![];
+        file.read_to_end(&mut log)?;
+
+        let records = read_records(&log);
+        let committed = records
+            .iter()
+            .filter_map(|record| match record { Record::Commit(statement) => Some(*statement), _ => None })
+            .collect::<HashSet<_>>();
+
+        // Repeat history (changes and rollbacks) and then revert statements which weren't committed. Each change is kept with its statement and its position in statement
+        let mut changes = vec![] as Vec<(u64, u64, &Operation)>;
+        let mut positions = HashMap::new() as HashMap<u64, u64>;
+        let mut paths = BTreeSet::new();
+        for record in &records {
+            match record {
+                Record::Operation(statement, operation) => {
+                    operation.redo()?;
+                    let position = positions.entry(*statement).or_default();
+                    changes.push((*statement, *position, operation));
+                    *position += 1;
+                    paths.extend(operation.paths());
+                },
+                Record::Rollback(statement, kept) => {
+                    let reverted = |(change_statement, position, _): &(u64, u64, &Operation)| change_statement == statement && position >= kept;
+                    for (_, _, operation) in changes.iter().rev().filter(|change| reverted(change)) {
+                        operation.undo()?;
+                    }
+                    changes.retain(|change| !reverted(change));
+                    positions.insert(*statement, *kept.min(positions.get(statement).unwrap_or(&0)));
+                },
+                Record::Commit(_) => ()
+            }
+        }
+        for (_, _, operation) in changes.iter().rev().filter(|(statement, _, _)| !committed.contains(statement)) {
+            operation.undo()?;
+        }
+
+        sync_paths(paths)?;
+        file.set_len(0)?;
+        file.sync_all()?;
+
+        Ok(WriteAheadLog { file, last_statement: 0, active: HashSet::new(), changed: BTreeSet::new(), performed: HashMap::new(), owners: HashMap::new() })
+    }
+
+    fn append(&mut self, record: &Record) -> io::Result<()> {
+        self.file.write_all(&record.encode()?)?;
+        self.file.sync_data()
+    }
+
+    /// Give number to statement (statement is in progress from its first change)
+    fn begin(&mut self) -> u64 {
+        self.last_statement += 1;
+        self.last_statement
+    }
+
+    /// Check whether file can be used by statement (None for statement which hasn't changed anything yet) performed on behalf of transaction
+    fn check_owner(&self, path: &Path, statement: Option<u64>, transaction: Option<u64>) -> io::Result<()> {
+        match self.owners.get(path) {
+            Some(owner) if Some(*owner) != statement && Some(*owner) != transaction => {
+                CONFLICTED.set(true);
+                Err(io::Error::new(io::ErrorKind::WouldBlock, "File is changed by another statement"))
+            },
+            _ => Ok(())
+        }
+    }
+
+    /// Log operation of statement performed on behalf of transaction and apply it when log record has been saved. Changed files belong to statement (files of transaction stay in it)
+    fn perform(&mut self, statement: u64, transaction: Option<u64>, operation: Operation) -> io::Result<()> {
+        for path in operation.paths() {
+            self.check_owner(path, Some(statement), transaction)?;
+        }
+        self.append(&Record::Operation(statement, operation.clone()))?;
+        self.active.insert(statement);
+        for path in operation.paths() {
+            self.changed.insert(path.to_path_buf());
+            self.owners.entry(path.to_path_buf()).or_insert(statement);
+        }
+        self.performed.entry(statement).or_default().push(operation.clone());
+        operation.redo()
+    }
+
+    /// Number of changes performed by statement
+    fn position(&self, statement: u64) -> usize {
+        self.performed.get(&statement).map_or(0, Vec::len)
+    }
+
+    /// Revert changes performed by statement after position (statement stays in progress)
+    fn rollback(&mut self, statement: u64, position: usize) -> io::Result<()> {
+        if self.position(statement) <= position {
+            return Ok(());
+        };
+        self.append(&Record::Rollback(statement, position as u64))?;
+
+        let performed = self.performed.entry(statement).or_default();
+        for operation in performed.drain(position..).rev() {
+            operation.undo()?;
+        }
+        Ok(())
+    }
+
+    /// Commit statement (statement which hasn't changed anything isn't logged)
+    fn commit(&mut self, statement: u64) -> io::Result<()> {
+        if !self.active.contains(&statement) {
+            return Ok(());
+        };
+        self.append(&Record::Commit(statement))?;
+        self.active.remove(&statement);
+        self.performed.remove(&statement);
+        self.owners.retain(|_, owner| *owner != statement);
+
+        if self.active.is_empty() {
+            self.checkpoint()?;
+        };
+        Ok(())
+    }
+
+    /// Save changed files and empty log (all logged changes are already in files)
+    fn checkpoint(&mut self) -> io::Result<()> {
+        sync_paths(self.changed.iter().map(PathBuf::as_path))?;
+        self.changed.clear();
+        self.file.set_len(0)?;
+        self.file.sync_all()
+    }
+}
+
+/// Logs of data directories (opened on first use)
+static LOGS: Mutex<BTreeMap<PathBuf, WriteAheadLog>> = Mutex::new(BTreeMap::new());
+
+thread_local! {
+    /// Data directory used by thread instead of data directory of server (e.g: temporary directory of test)
+    static THREAD_DATA_PATH: RefCell<Option<PathBuf>> = const { RefCell::new(None) };
+    /// Statement performed by thread: None when no statement has been begun, Some(None) when begun statement hasn't changed anything yet
+    static STATEMENT: Cell<Option<Option<u64>>> = const { Cell::new(None) };
+    /// Transaction on behalf of which statement is performed by thread
+    static TRANSACTION: Cell<Option<u64>> = const { Cell::new(None) };
+    /// Whether statement performed by thread tried to use file which belongs to another statement
+    static CONFLICTED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Data directory used by thread
+pub fn data_path() -> PathBuf {
+    THREAD_DATA_PATH.with_borrow(|path| path.clone()).unwrap_or_else(|| PathBuf::from(DATA_PATH))
+}
+
+/// Use other data directory by thread. Each data directory has got own log and transactions
+pub fn set_data_path(path: &Path) {
+    THREAD_DATA_PATH.set(Some(path.to_path_buf()));
+}
+
+/// Run function with opened log of data directory used by thread
+fn with_log<T>(function: impl FnOnce(&mut WriteAheadLog) -> io::Result<T>) -> io::Result<T> {
+    let mut logs = LOGS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let data_path = data_path();
+    if !logs.contains_key(&data_path) {
+        let log = WriteAheadLog::open(&data_path.join(LOG_FILE))?;
+        logs.insert(data_path.clone(), log);
+    };
+
+    function(logs.get_mut(&data_path).unwrap())
+}
+
+/// Replay log left by previous run of server (called on start before any file is read)
+pub fn recover() -> io::Result<()> {
+    with_log(|_| Ok(()))
+}
+
+/// Log operation (created when log is locked, so previous content of files is up to date) and apply it. Operation performed outside of statement is committed at once
+fn perform(operation: impl FnOnce() -> io::Result<Operation>) -> io::Result<()> {
+    with_log(|log| {
+        let operation = operation()?;
+        match STATEMENT.get() {
+            Some(Some(statement)) => log.perform(statement, TRANSACTION.get(), operation),
+            Some(None) => {
+                let statement = log.begin();
+                STATEMENT.set(Some(Some(statement)));
+                log.perform(statement, TRANSACTION.get(), operation)
+            },
+            None => {
+                let statement = log.begin();
+                let performed = log.perform(statement, None, operation);
+                log.commit(statement)?;
+                performed
+            }
+        }
+    })
+}
+
+/// Content of file (None when file doesn't exist)
+fn file_content(path: &Path) -> io::Result<Option<Vec<u8>>> {
+    match fs::read(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err)
+    }
+}
+
+/// Write parts of file (offset and content of each part). File is created when it doesn't exist
+pub fn write_at(path: &Path, parts: Vec<(u64, Vec<u8>)>) -> io::Result<()> {
+    perform(|| {
+        let mut file = match File::open(path) {
+            Ok(file) => Some(file),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+            Err(err) => return Err(err)
+        };
+        let length = file.as_ref().map(|file| file.metadata().map(|metadata| metadata.len())).transpose()?;
+
+        let mut pages = vec![];
+        for (offset, after) in parts {
+            let mut before = vec![0; length.unwrap_or(0).saturating_sub(offset).min(after.len() as u64) as usize];
+            if let Some(file) = file.as_mut() {
+                file.seek(SeekFrom::Start(offset))?;
+                file.read_exact(&mut before)?;
+            };
+            pages.push((offset, before, after));
+        }
+
+        Ok(Operation::Pages { path: path.to_path_buf(), length, pages })
+    })
+}
+
+/// Replace whole content of file
+pub fn write_file(path: &Path, content: &[u8]) -> io::Result<()> {
+    perform(|| Ok(Operation::File { path: path.to_path_buf(), before: file_content(path)?, after: Some(content.to_vec()) }))
+}
+
+pub fn remove_file(path: &Path) -> io::Result<()> {
+    perform(|| match file_content(path)? {
+        Some(content) => Ok(Operation::File { path: path.to_path_buf(), before: Some(content), after: None }),
+        None => Err(io::Error::from(io::ErrorKind::NotFound))
+    })
+}
+
+/// Move file to path which mustn't exist
+pub fn rename(from: &Path, to: &Path) -> io::Result<()> {
+    perform(|| match (from.exists(), to.exists()) {
+        (true, false) => Ok(Operation::Rename { from: from.to_path_buf(), to: to.to_path_buf() }),
+        (false, _) => Err(io::Error::from(io::ErrorKind::NotFound)),
+        (true, true) => Err(io::Error::from(io::ErrorKind::AlreadyExists))
+    })
+}
+
+/// Create directory which mustn't exist
+pub fn create_dir(path: &Path) -> io::Result<()> {
+    perform(|| match path.exists() {
+        false => Ok(Operation::Directory { path: path.to_path_buf() }),
+        true => Err(io::Error::from(io::ErrorKind::AlreadyExists))
+    })
+}
+
+#[derive(Debug, Clone, Copy)]
+/// Statement together with transaction on behalf of which it is performed, which reads files (e.g: statement performed by thread, captured to read files for it by other thread)
+pub struct Access {
+    statement: Option<u64>,
+    transaction: Option<u64>,
+}
+
+/// Statement performed by thread
+pub fn access() -> Access {
+    Access { statement: STATEMENT.get().flatten(), transaction: TRANSACTION.get() }
+}
+
+/// Check whether file can be read by statement (file changed by another statement which is in progress can't be read)
+pub fn check_access(path: &Path, access: Access) -> io::Result<()> {
+    with_log(|log| log.check_owner(path, access.statement, access.transaction))
+}
+
+/// Begin transaction: statement of log which stays in progress (also between statements of query) from its first change until it is committed or rolled back
+pub fn begin_transaction() -> io::Result<u64> {
+    with_log(|log| Ok(log.begin()))
+}
+
+/// Number of changes performed by transaction (position to which transaction can be rolled back)
+pub fn transaction_position(transaction: u64) -> io::Result<usize> {
+    with_log(|log| Ok(log.position(transaction)))
+}
+
+/// Revert changes performed by transaction after position
+pub fn rollback_transaction(transaction: u64, position: usize) -> io::Result<()> {
+    with_log(|log| log.rollback(transaction, position))
+}
+
+pub fn commit_transaction(transaction: u64) -> io::Result<()> {
+    with_log(|log| log.commit(transaction))
+}
+
+/// Revert all changes of transaction and end it
+pub fn abort_transaction(transaction: u64) -> io::Result<()> {
+    with_log(|log| {
+        log.rollback(transaction, 0)?;
+        log.commit(transaction)
+    })
+}
+
+/// Statement whose changes are committed together. Changes performed by thread while statement exists belong to it (or to transaction inside of which statement is performed)
+pub struct Statement {
+    committed: bool,
+    /// transaction inside of which statement is performed and number of changes performed by transaction before statement
+    inside: Option<(u64, usize)>,
+}
+
+impl Statement {
+    /// Begin statement which is committed by its commit. Statement performed on behalf of transaction can use files which belong to transaction
+    pub fn begin(transaction: Option<u64>) -> Statement {
+        STATEMENT.set(Some(None));
+        TRANSACTION.set(transaction);
+        CONFLICTED.set(false);
+        Statement { committed: false, inside: None }
+    }
+
+    /// Begin statement whose changes belong to transaction (they are committed or rolled back together with transaction)
+    pub fn begin_inside(transaction: u64) -> io::Result<Statement> {
+        let position = transaction_position(transaction)?;
+        STATEMENT.set(Some(Some(transaction)));
+        TRANSACTION.set(None);
+        CONFLICTED.set(false);
+        Ok(Statement { committed: false, inside: Some((transaction, position)) })
+    }
+
+    /// Whether statement couldn't use some file because it belongs to another statement (e.g: to transaction of another session)
+    pub fn conflicted(&self) -> bool {
+        CONFLICTED.get()
+    }
+
+    /// Statement performed by thread which is taken over by this statement (state of thread is cleared)
+    fn take(&mut self) -> Option<Option<u64>> {
+        self.committed = true;
+        TRANSACTION.set(None);
+        STATEMENT.replace(None)
+    }
+
+    /// Mark all changes of statement as durable (statement which hasn't changed anything isn't logged). Changes of statement performed inside of transaction stay in it
+    pub fn commit(mut self) -> io::Result<()> {
+        match (self.inside, self.take()) {
+            (None, Some(Some(statement))) => with_log(|log| log.commit(statement)),
+            _ => Ok(())
+        }
+    }
+
+    /// Revert all changes of statement
+    pub fn rollback(mut self) -> io::Result<()> {
+        match (self.inside, self.take()) {
+            (Some((transaction, position)), _) => rollback_transaction(transaction, position),
+            (None, Some(Some(statement))) => abort_transaction(statement),
+            _ => Ok(())
+        }
+    }
+}
+
+impl Drop for Statement {
+    fn drop(&mut self) {
+        // Statement interrupted (e.g: by panic) is rolled back together with its own transaction, which otherwise would stay in progress
+        if !self.committed {
+            match (self.inside, self.take()) {
+                (Some((transaction, position)), _) => rollback_transaction(transaction, position).ok(),
+                (None, Some(Some(statement))) => abort_transaction(statement).ok(),
+                _ => None
+            };
+            sql_transactions::end_statement(None, false).ok();
+        };
+    }
+}
+
+#[test]
+fn test_recover() {
+    let dir = std::env::temp_dir().join(format!("wastledb_wal_{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&dir).unwrap();
+    let log_path = dir.join("wastledb.wal");
+    let (committed, torn) = (dir.join("committed.tbl"), dir.join("torn.tbl"));
+    fs::write(&torn, "old content").unwrap();
+
+    let mut log = WriteAheadLog::open(&log_path).unwrap();
+    // Committed statement which crashed before its change was written to file
+    let statement = log.begin();
+    log.append(&Record::Operation(statement, Operation::File { path: committed.clone(), before: None, after: Some(b"new".to_vec()) })).unwrap();
+    log.append(&Record::Commit(statement)).unwrap();
+    // Statement which crashed in the middle of writing (after partially written record of next change)
+    let statement = log.begin();
+    log.perform(statement, None, Operation::Pages { path: torn.clone(), length: Some(11), pages: vec![(0, b"old".to_vec(), b"new".to_vec()), (11, vec![], b"!".to_vec())] }).unwrap();
+    let record = Record::Operation(statement, Operation::Rename { from: torn.clone(), to: dir.join("renamed.tbl") }).encode().unwrap();
+    log.file.write_all(&record[..record.len() - 1]).unwrap();
+    assert_eq!(fs::read(&torn).unwrap(), b"new content!");
+    drop(log);
+
+    WriteAheadLog::open(&log_path).unwrap();
+    assert_eq!(fs::read(&committed).unwrap(), b"new");
+    assert_eq!(fs::read(&torn).unwrap(), b"old content");
+    assert_eq!(fs::metadata(&log_path).unwrap().len(), 0);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_rollback() {
+    let dir = std::env::temp_dir().join(format!("wastledb_wal_{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&dir).unwrap();
+    let log_path = dir.join("wastledb.wal");
+    let (committed, uncommitted, created) = (dir.join("committed.tbl"), dir.join("uncommitted.tbl"), dir.join("created.tbl"));
+    fs::write(&committed, "old").unwrap();
+    fs::write(&uncommitted, "old").unwrap();
+
+    let mut log = WriteAheadLog::open(&log_path).unwrap();
+    let change = |path: &Path, before: &str, after: &str| Operation::File { path: path.to_path_buf(), before: Some(before.into()), after: Some(after.into()) };
+    // Both statements revert their last change (like rollback to savepoint) and perform next one
+    let (first, second) = (log.begin(), log.begin());
+    for (statement, path) in [(first, &committed), (second, &uncommitted)] {
+        log.perform(statement, None, change(path, "old", "kept")).unwrap();
+        log.perform(statement, None, change(path, "kept", "reverted")).unwrap();
+        log.rollback(statement, 1).unwrap();
+        assert_eq!(fs::read(path).unwrap(), b"kept");
+    }
+    log.perform(second, None, Operation::File { path: created.clone(), before: None, after: Some(b"new".to_vec()) }).unwrap();
+    log.commit(first).unwrap();
+
+    // File changed by statement which is in progress can't be changed by another statement
+    let third = log.begin();
+    assert_eq!(log.perform(third, None, change(&uncommitted, "kept", "other")).unwrap_err().kind(), io::ErrorKind::WouldBlock);
+    log.perform(third, None, change(&committed, "kept", "other")).unwrap();
+    drop(log);
+
+    WriteAheadLog::open(&log_path).unwrap();
+    assert_eq!(fs::read(&committed).unwrap(), b"kept");
+    assert_eq!(fs::read(&uncommitted).unwrap(), b"old");
+    assert!(!created.exists());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_idle_transaction() {
+    let dir = std::env::temp_dir().join(format!("wastledb_wal_{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&dir).unwrap();
+    let log_path = dir.join("wastledb.wal");
+    let path = dir.join("changed.tbl");
+    let mut log = WriteAheadLog::open(&log_path).unwrap();
+
+    // Transaction which hasn't changed anything doesn't stop checkpoint after committed statement
+    let transaction = log.begin();
+    let statement = log.begin();
+    log.perform(statement, None, Operation::File { path: path.clone(), before: None, after: Some(b"new".to_vec()) }).unwrap();
+    log.commit(statement).unwrap();
+    assert_eq!(fs::metadata(&log_path).unwrap().len(), 0);
+
+    // After its first change log is kept until it ends
+    log.perform(transaction, None, Operation::File { path: path.clone(), before: Some(b"new".to_vec()), after: Some(b"changed".to_vec()) }).unwrap();
+    let statement = log.begin();
+    log.perform(statement, None, Operation::Directory { path: dir.join("created") }).unwrap();
+    log.commit(statement).unwrap();
+    assert_ne!(fs::metadata(&log_path).unwrap().len(), 0);
+    log.commit(transaction).unwrap();
+    assert_eq!(fs::metadata(&log_path).unwrap().len(), 0);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_interrupted_statement() {
+    let dir = std::env::temp_dir().join(format!("wastledb_wal_{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&dir).unwrap();
+    set_data_path(&dir);
+    let path = dir.join("interrupted.tbl");
+
+    // Changes of statement interrupted by panic are reverted
+    let interrupted = std::panic::catch_unwind(|| {
+        let _statement = Statement::begin(None);
+        sql_transactions::begin_statement(None);
+        write_file(&path, b"partial").unwrap();
+        panic!("Statement has been interrupted");
+    });
+    assert!(interrupted.is_err());
+    assert!(!path.exists());
+
+    // File doesn't belong to interrupted statement anymore
+    let statement = Statement::begin(None);
+    write_file(&path, b"new").unwrap();
+    statement.commit().unwrap();
+    assert_eq!(fs::read(&path).unwrap(), b"new");
+
+    fs::remove_dir_all(&dir).unwrap();
+}